@@ -32,6 +32,57 @@ describe('Identity', () => {
     )
     expect(resp.status).toBe(401)
   })
+  it('known ens with the L2 chain fallback to mainnet', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/identity/${knownAddress}?chainId=base&projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(typeof resp.data.name).toBe('string')
+    expect(['eip155:8453', 'eip155:1']).toContain(resp.data.chainId)
+    expect(resp.data.resolver).toMatch(/^0x[a-fA-F0-9]{40}$/)
+  })
+  it('unsupported primary name chain', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/identity/${knownAddress}?chainId=eip155%3A56&projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(400)
+  })
+  it('known ens with the text records', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/identity/${knownAddress}?projectId=${projectId}&apiVersion=2&records=url,com.twitter`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.name).toBe('cyberdrk.eth')
+    expect(typeof resp.data.records).toBe('object')
+    for (const record of Object.keys(resp.data.records)) {
+      expect(['url', 'com.twitter']).toContain(record)
+    }
+
+    // Text records are not supported by the version 1
+    resp = await httpClient.get(
+      `${baseUrl}/v1/identity/${knownAddress}?projectId=${projectId}&records=url`,
+    )
+    expect(resp.status).toBe(400)
+  })
+  it('batch lookup', async () => {
+    let resp: any = await httpClient.post(
+      `${baseUrl}/v1/identity/batch?projectId=${projectId}`,
+      { addresses: [knownAddress, unknownAddress, 'invalid'] },
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.identities).toHaveLength(3)
+    expect(resp.data.identities[0].address).toBe(knownAddress)
+    expect(resp.data.identities[0].identity.name).toBe('cyberdrk.eth')
+    expect(resp.data.identities[1].identity.name).toBe(null)
+    expect(typeof resp.data.identities[2].error).toBe('string')
+
+    // Empty batch
+    resp = await httpClient.post(
+      `${baseUrl}/v1/identity/batch?projectId=${projectId}`,
+      { addresses: [] },
+    )
+    expect(resp.status).toBe(400)
+  })
   it('unknown ens', async () => {
     let resp: any = await httpClient.get(
       `${baseUrl}/v1/identity/${unknownAddress}?projectId=${projectId}`,
@@ -47,8 +98,10 @@ describe('Identity', () => {
     )
     expect(resp.status).toBe(200)
     expect(resp.headers['cache-control']).toContain('public, max-age=')
+    // Addresses without a name are cached for a shorter time
     const age1 = +resp.headers['cache-control'].match(/max-age=(\d+)/)[1];
-    expect(age1).toBeGreaterThan(86397)
+    expect(age1).toBeLessThanOrEqual(3600)
+    expect(age1).toBeGreaterThan(3597)
     expect(resp.data.name).toBe(null)
 
     await new Promise(resolve => setTimeout(resolve, 2000));
@@ -57,8 +110,8 @@ describe('Identity', () => {
     )
     expect(resp2.status).toBe(200)
     const age2 = +resp2.headers['cache-control'].match(/max-age=(\d+)/)[1];
-    expect(age2).toBeLessThan(86400)
-    expect(age2).toBeGreaterThan(86395)
+    expect(age2).toBeLessThan(3600)
+    expect(age2).toBeGreaterThan(3595)
     expect(resp2.data.name).toBe(null)
   })
 })
@@ -305,6 +305,16 @@ describe('Account profile names', () => {
     expect(resp.data.length).toBe(0)
   })
 
+  it('name resolution (internal name)', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/resolve/${name}?projectId=${projectId}`
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.name).toBe(name)
+    expect(resp.data.source).toBe('internal')
+    expect(resp.data.addresses['eip155:1']).toBe(address)
+  })
+
   it('name reverse lookup (name found)', async () => {
     let resp: any = await httpClient.get(
       `${baseUrl}/v1/profile/reverse/${address}`
@@ -379,6 +389,51 @@ describe('Account profile names', () => {
     expect(resp.data['bio']).toBe(updatedAttributes['bio'])
   })
 
+  it('update name address (Solana)', async () => {
+    const solana_coin_type = 501; // SLIP-44 Solana
+    const solana_address = 'CKfatsPMUf8SkiURsDXs7eK6GWb4Jsd6UDbs7twMCWxo';
+
+    // Prepare updated address payload
+    const UpdateAddressMessageObject = {
+      coin_type: solana_coin_type,
+      address: solana_address,
+      timestamp: Math.round(Date.now() / 1000)
+    };
+    const updateMessage = JSON.stringify(UpdateAddressMessageObject);
+
+    // Sign the message
+    const signature = await wallet.signMessage(updateMessage);
+
+    const payload = {
+      message: updateMessage,
+      signature,
+      coin_type,
+      address,
+    };
+
+    // Update the address
+    let resp: any = await httpClient.post(
+      `${baseUrl}/v1/profile/account/${name}/address`,
+      payload
+    );
+    expect(resp.status).toBe(200)
+    expect(resp.data[solana_coin_type].address).toBe(solana_address)
+
+    // Solana address in the wrong format
+    UpdateAddressMessageObject.address = address;
+    const wrongAddressMessage = JSON.stringify(UpdateAddressMessageObject);
+    resp = await httpClient.post(
+      `${baseUrl}/v1/profile/account/${name}/address`,
+      {
+        message: wrongAddressMessage,
+        signature: await wallet.signMessage(wrongAddressMessage),
+        coin_type,
+        address,
+      }
+    );
+    expect(resp.status).toBe(400)
+  })
+
   it('update name address', async () => {
     // Generate a new eth wallet
     const new_address = ethers.Wallet.createRandom().address;
@@ -420,6 +475,67 @@ describe('Account profile names', () => {
     expect(first.address).toBe(new_address)
   })
 
+  it('transfer name', async () => {
+    // Register a new name to transfer
+    const randomString = Array.from({ length: 10 }, 
+      () => (Math.random().toString(36)[2] || '0')).join('')
+    const name = `integration-test-${randomString}.${zone}`;
+    const registerMessage = JSON.stringify({
+      name,
+      attributes,
+      timestamp: Math.round(Date.now() / 1000)
+    });
+    let resp: any = await httpClient.post(
+      `${baseUrl}/v1/profile/account`,
+      {
+        message: registerMessage,
+        signature: await wallet.signMessage(registerMessage),
+        coin_type,
+        address,
+      }
+    )
+    expect(resp.status).toBe(200)
+
+    // Transfer the name to the new owner with the acceptance signature
+    const recipient = ethers.Wallet.createRandom();
+    const transferMessage = JSON.stringify({
+      name,
+      coin_type,
+      address: recipient.address,
+      timestamp: Math.round(Date.now() / 1000)
+    });
+    const payload = {
+      message: transferMessage,
+      signature: await wallet.signMessage(transferMessage),
+      coin_type,
+      address,
+      recipient_signature: await wallet.signMessage(transferMessage),
+    };
+
+    // The acceptance signature is not from the recipient
+    resp = await httpClient.post(
+      `${baseUrl}/v1/profile/account/${name}/transfer`,
+      payload
+    )
+    expect(resp.status).toBe(401)
+
+    payload.recipient_signature = await recipient.signMessage(transferMessage);
+    resp = await httpClient.post(
+      `${baseUrl}/v1/profile/account/${name}/transfer`,
+      payload
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.name).toBe(name)
+    expect(resp.data.addresses[coin_type].address).toBe(recipient.address)
+
+    // The previous owner can't transfer the name anymore
+    resp = await httpClient.post(
+      `${baseUrl}/v1/profile/account/${name}/transfer`,
+      payload
+    )
+    expect(resp.status).toBe(401)
+  })
+
   it('name suggestions', async () => {
     const test_name_suggest = 'max';
     let resp: any = await httpClient.get(
@@ -0,0 +1,44 @@
+import { getTestSetup } from './init';
+
+describe('Name resolution', () => {
+  const { baseUrl, projectId, httpClient } = getTestSetup();
+  const knownName = 'cyberdrk.eth';
+  const knownAddress = '0xf3ea39310011333095CFCcCc7c4Ad74034CABA63';
+
+  it('known ens name', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/resolve/${knownName}?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.name).toBe(knownName)
+    expect(resp.data.source).toBe('ens')
+    expect(resp.data.addresses['eip155:1']).toBe(knownAddress)
+    expect(typeof resp.data.resolver).toBe('string')
+  })
+  it('ens name with the uppercase characters', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/resolve/${knownName.toUpperCase()}?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(200)
+    expect(resp.data.name).toBe(knownName)
+  })
+  it('unregistered ens name', async () => {
+    const randomString = Array.from({ length: 20 },
+      () => (Math.random().toString(36)[2] || '0')).join('')
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/resolve/${randomString}.eth?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(404)
+  })
+  it('wrong name format', async () => {
+    let resp: any = await httpClient.get(
+      `${baseUrl}/v1/resolve/name..eth?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(400)
+
+    resp = await httpClient.get(
+      `${baseUrl}/v1/resolve/eth?projectId=${projectId}`,
+    )
+    expect(resp.status).toBe(400)
+  })
+})
@@ -9,6 +9,24 @@ describe('Supported chains', () => {
     expect(resp.data.http).toContain('eip155:1')
     expect(resp.data.http).toContain('eip155:8453')
     expect(resp.data.ws).toContain('eip155:1')
-    expect(resp.data.ws).not.toContain('eip155:8453')
+    expect(resp.data.ws).toContain('eip155:8453')
+  })
+
+  it('Returns chain aliases', async () => {
+    const resp = await httpClient.get(`${baseUrl}/v1/supported-chains`)
+    expect(resp.status).toBe(200)
+    expect(resp.data.aliases['eip155:137']).toContain('polygon')
+    expect(resp.data.aliases['eip155:8453']).toContain('base')
+  })
+
+  it('Returns chains metadata', async () => {
+    const resp = await httpClient.get(`${baseUrl}/v1/supported-chains?apiVersion=2`)
+    expect(resp.status).toBe(200)
+    const ethereum = resp.data.chains.find((chain: any) => chain.chainId === 'eip155:1')
+    expect(ethereum.name).toBe('Ethereum')
+    expect(ethereum.nativeCurrency).toEqual({ symbol: 'ETH', decimals: 18 })
+    expect(ethereum.testnet).toBe(false)
+    expect(ethereum.capabilities.http).toBe(true)
+    expect(ethereum.capabilities.ws).toBe(true)
   })
 })
@@ -0,0 +1,4 @@
+-- Adding the non-EVM namespaces, the `chain_id` of the addresses is the
+-- ENSIP-11 coin type same as for the `eip155` namespace
+ALTER TYPE namespaces ADD VALUE IF NOT EXISTS 'solana';
+ALTER TYPE namespaces ADD VALUE IF NOT EXISTS 'bip122';
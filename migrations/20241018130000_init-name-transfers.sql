@@ -0,0 +1,16 @@
+-- Initializing the name ownership transfers history table
+CREATE TABLE name_transfers (
+  id BIGSERIAL PRIMARY KEY,
+  -- Not referencing the names to keep the history of the deleted names
+  name VARCHAR(255) NOT NULL,
+  from_address VARCHAR(255) NOT NULL,
+  to_address VARCHAR(255) NOT NULL,
+
+  -- Whether the transfer was accepted by the recipient signature
+  accepted BOOLEAN NOT NULL DEFAULT false,
+  transferred_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Creating index for the name transfers lookups
+CREATE INDEX index_name_transfers_name
+  ON name_transfers (name);
@@ -1,5 +1,5 @@
 use {
-    crate::handlers::identity::{IdentityLookupSource, IdentityQueryParams, ETHEREUM_MAINNET},
+    crate::handlers::identity::{IdentityLookupSource, IdentityQueryParams},
     ethers::types::H160,
     parquet_derive::ParquetRecordWriter,
     serde::Serialize,
@@ -59,7 +59,7 @@ impl IdentityLookupInfo {
             latency_secs: latency.as_secs_f64(),
 
             project_id: query_params.project_id.to_owned(),
-            chain_id: ETHEREUM_MAINNET.to_owned(),
+            chain_id: query_params.lookup_chain_id(),
 
             origin,
 
@@ -1,5 +1,5 @@
 use {
-    crate::{handlers::RpcQueryParams, json_rpc::JsonRpcRequest, providers::ProviderKind},
+    crate::{handlers::RpcQueryParams, providers::ProviderKind},
     parquet_derive::ParquetRecordWriter,
     serde::{Deserialize, Serialize},
     std::sync::Arc,
@@ -27,7 +27,7 @@ pub struct MessageInfo {
 impl MessageInfo {
     pub fn new(
         query_params: &RpcQueryParams,
-        request: &JsonRpcRequest,
+        method: Arc<str>,
         region: Option<Vec<String>>,
         country: Option<Arc<str>>,
         continent: Option<Arc<str>>,
@@ -39,7 +39,7 @@ impl MessageInfo {
 
             project_id: query_params.project_id.to_owned(),
             chain_id: query_params.chain_id.to_lowercase(),
-            method: request.method.clone(),
+            method,
             source: query_params
                 .source
                 .as_ref()
@@ -62,10 +62,12 @@ impl MessageInfo {
 pub enum MessageSource {
     Rpc,
     Identity,
+    NameResolve,
     Balance,
     ProfileAddressSigValidate,
     ProfileAttributesSigValidate,
     ProfileRegisterSigValidate,
+    ProfileTransferSigValidate,
     SessionCoSignSigValidate,
     WalletPrepareCalls,
     WalletSendPreparedCalls,
@@ -83,6 +85,9 @@ mod tests {
         let source = MessageSource::Identity;
         assert_eq!(source.to_string(), "identity");
 
+        let source = MessageSource::NameResolve;
+        assert_eq!(source.to_string(), "name_resolve");
+
         let source = MessageSource::Balance;
         assert_eq!(source.to_string(), "balance");
 
@@ -95,6 +100,9 @@ mod tests {
         let source = MessageSource::ProfileRegisterSigValidate;
         assert_eq!(source.to_string(), "profile_register_sig_validate");
 
+        let source = MessageSource::ProfileTransferSigValidate;
+        assert_eq!(source.to_string(), "profile_transfer_sig_validate");
+
         let source = MessageSource::SessionCoSignSigValidate;
         assert_eq!(source.to_string(), "session_co_sign_sig_validate");
     }
@@ -0,0 +1,545 @@
+//! Registry of the supported chains metadata and their human-readable aliases
+//! that are accepted by the proxy endpoints in place of the CAIP-2 chain ids.
+//!
+//! The registry is the single source of the known chains. The human-readable
+//! chain ids conversions of the third-party APIs and the supported EVM chains
+//! checks are limited to the [`CONVERTED_CHAINS`] subset of the registry.
+
+use {
+    once_cell::sync::Lazy,
+    serde::{Deserialize, Deserializer, Serialize},
+    std::collections::HashMap,
+    tracing::warn,
+};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct NativeCurrency {
+    pub symbol: &'static str,
+    pub decimals: u8,
+}
+
+impl NativeCurrency {
+    const fn new(symbol: &'static str, decimals: u8) -> Self {
+        Self { symbol, decimals }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChainInfo {
+    /// CAIP-2 chain id
+    pub chain_id: &'static str,
+    pub name: &'static str,
+    pub native_currency: NativeCurrency,
+    /// Block explorer base URL
+    pub explorer_url: &'static str,
+    pub testnet: bool,
+    /// Lowercase human-readable aliases of the chain id, the first one is
+    /// the canonical human-readable chain id used by the third-party APIs
+    pub aliases: &'static [&'static str],
+}
+
+pub static CHAINS: &[ChainInfo] = &[
+    ChainInfo {
+        chain_id: "eip155:1",
+        name: "Ethereum",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://etherscan.io",
+        testnet: false,
+        aliases: &["ethereum", "mainnet", "eth"],
+    },
+    ChainInfo {
+        chain_id: "eip155:5",
+        name: "Ethereum Goerli",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://goerli.etherscan.io",
+        testnet: true,
+        aliases: &["goerli"],
+    },
+    ChainInfo {
+        chain_id: "eip155:10",
+        name: "Optimism",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://optimistic.etherscan.io",
+        testnet: false,
+        aliases: &["optimism", "op"],
+    },
+    ChainInfo {
+        chain_id: "eip155:56",
+        name: "Binance Smart Chain",
+        native_currency: NativeCurrency::new("BNB", 18),
+        explorer_url: "https://bscscan.com",
+        testnet: false,
+        aliases: &["binance-smart-chain", "bsc", "binance_smart_chain", "bnb"],
+    },
+    ChainInfo {
+        chain_id: "eip155:97",
+        name: "Binance Smart Chain Testnet",
+        native_currency: NativeCurrency::new("TBNB", 18),
+        explorer_url: "https://testnet.bscscan.com",
+        testnet: true,
+        aliases: &["bsc-testnet"],
+    },
+    ChainInfo {
+        chain_id: "eip155:100",
+        name: "Gnosis Chain",
+        native_currency: NativeCurrency::new("XDAI", 18),
+        explorer_url: "https://gnosisscan.io",
+        testnet: false,
+        aliases: &[
+            "xdai",
+            "gnosis",
+            "gnosis-chain",
+            "gnosis_chain",
+            "gnosischain",
+        ],
+    },
+    ChainInfo {
+        chain_id: "eip155:137",
+        name: "Polygon",
+        native_currency: NativeCurrency::new("POL", 18),
+        explorer_url: "https://polygonscan.com",
+        testnet: false,
+        aliases: &["polygon", "matic"],
+    },
+    ChainInfo {
+        chain_id: "eip155:250",
+        name: "Fantom",
+        native_currency: NativeCurrency::new("FTM", 18),
+        explorer_url: "https://ftmscan.com",
+        testnet: false,
+        aliases: &["fantom"],
+    },
+    ChainInfo {
+        chain_id: "eip155:300",
+        name: "zkSync Era Sepolia Testnet",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia.explorer.zksync.io",
+        testnet: true,
+        aliases: &["zksync-sepolia"],
+    },
+    ChainInfo {
+        chain_id: "eip155:324",
+        name: "zkSync Era",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://explorer.zksync.io",
+        testnet: false,
+        aliases: &["zksync-era", "zksync", "zksyncera"],
+    },
+    ChainInfo {
+        chain_id: "eip155:1101",
+        name: "Polygon Zkevm",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://zkevm.polygonscan.com",
+        testnet: false,
+        aliases: &["polygon-zkevm"],
+    },
+    ChainInfo {
+        chain_id: "eip155:1329",
+        name: "Sei Network",
+        native_currency: NativeCurrency::new("SEI", 18),
+        explorer_url: "https://seitrace.com",
+        testnet: false,
+        aliases: &["sei"],
+    },
+    ChainInfo {
+        chain_id: "eip155:5000",
+        name: "Mantle",
+        native_currency: NativeCurrency::new("MNT", 18),
+        explorer_url: "https://explorer.mantle.xyz",
+        testnet: false,
+        aliases: &["mantle"],
+    },
+    ChainInfo {
+        chain_id: "eip155:5003",
+        name: "Mantle Testnet",
+        native_currency: NativeCurrency::new("MNT", 18),
+        explorer_url: "https://explorer.sepolia.mantle.xyz",
+        testnet: true,
+        aliases: &["mantle-testnet"],
+    },
+    ChainInfo {
+        chain_id: "eip155:8217",
+        name: "Klaytn Mainnet",
+        native_currency: NativeCurrency::new("KLAY", 18),
+        explorer_url: "https://klaytnscope.com",
+        testnet: false,
+        aliases: &["klaytn"],
+    },
+    ChainInfo {
+        chain_id: "eip155:8453",
+        name: "Base",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://basescan.org",
+        testnet: false,
+        aliases: &["base"],
+    },
+    ChainInfo {
+        chain_id: "eip155:17000",
+        name: "Ethereum Holesky",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://holesky.etherscan.io",
+        testnet: true,
+        aliases: &["holesky"],
+    },
+    ChainInfo {
+        chain_id: "eip155:81457",
+        name: "Blast",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://blastscan.io",
+        testnet: false,
+        aliases: &["blast"],
+    },
+    ChainInfo {
+        chain_id: "eip155:42161",
+        name: "Arbitrum",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://arbiscan.io",
+        testnet: false,
+        aliases: &["arbitrum", "arb"],
+    },
+    ChainInfo {
+        chain_id: "eip155:42220",
+        name: "Celo",
+        native_currency: NativeCurrency::new("CELO", 18),
+        explorer_url: "https://celoscan.io",
+        testnet: false,
+        aliases: &["celo"],
+    },
+    ChainInfo {
+        chain_id: "eip155:43113",
+        name: "Avalanche Fuji Testnet",
+        native_currency: NativeCurrency::new("AVAX", 18),
+        explorer_url: "https://testnet.snowtrace.io",
+        testnet: true,
+        aliases: &["avalanche-fuji", "fuji"],
+    },
+    ChainInfo {
+        chain_id: "eip155:43114",
+        name: "Avalanche C-Chain",
+        native_currency: NativeCurrency::new("AVAX", 18),
+        explorer_url: "https://snowtrace.io",
+        testnet: false,
+        aliases: &["avalanche", "avax"],
+    },
+    ChainInfo {
+        chain_id: "eip155:59144",
+        name: "Linea",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://lineascan.build",
+        testnet: false,
+        aliases: &["linea"],
+    },
+    ChainInfo {
+        chain_id: "eip155:80002",
+        name: "Polygon Amoy",
+        native_currency: NativeCurrency::new("POL", 18),
+        explorer_url: "https://amoy.polygonscan.com",
+        testnet: true,
+        aliases: &["polygon-amoy", "amoy"],
+    },
+    ChainInfo {
+        chain_id: "eip155:80084",
+        name: "Berachain bArtio",
+        native_currency: NativeCurrency::new("BERA", 18),
+        explorer_url: "https://bartio.beratrail.io",
+        testnet: true,
+        aliases: &["berachain-bartio"],
+    },
+    ChainInfo {
+        chain_id: "eip155:84532",
+        name: "Base Sepolia",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia.basescan.org",
+        testnet: true,
+        aliases: &[
+            "base-sepolia-testnet",
+            "base-sepolia",
+            "base_sepolia_testnet",
+        ],
+    },
+    ChainInfo {
+        chain_id: "eip155:421614",
+        name: "Arbitrum Sepolia",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia.arbiscan.io",
+        testnet: true,
+        aliases: &["arbitrum-sepolia"],
+    },
+    ChainInfo {
+        chain_id: "eip155:534351",
+        name: "Scroll Sepolia Testnet",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia.scrollscan.com",
+        testnet: true,
+        aliases: &["scroll-sepolia"],
+    },
+    ChainInfo {
+        chain_id: "eip155:534352",
+        name: "Scroll Mainnet",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://scrollscan.com",
+        testnet: false,
+        aliases: &["scroll"],
+    },
+    ChainInfo {
+        chain_id: "eip155:7777777",
+        name: "Zora",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://explorer.zora.energy",
+        testnet: false,
+        aliases: &["zora"],
+    },
+    ChainInfo {
+        chain_id: "eip155:11155111",
+        name: "Ethereum Sepolia",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia.etherscan.io",
+        testnet: true,
+        aliases: &["sepolia"],
+    },
+    ChainInfo {
+        chain_id: "eip155:11155420",
+        name: "Optimism Sepolia",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia-optimism.etherscan.io",
+        testnet: true,
+        aliases: &["optimism-sepolia"],
+    },
+    ChainInfo {
+        chain_id: "eip155:999999999",
+        name: "Zora Sepolia",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://sepolia.explorer.zora.energy",
+        testnet: true,
+        aliases: &["zora-sepolia"],
+    },
+    ChainInfo {
+        chain_id: "eip155:1313161554",
+        name: "Aurora",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://explorer.aurora.dev",
+        testnet: false,
+        aliases: &["aurora"],
+    },
+    ChainInfo {
+        chain_id: "eip155:1313161555",
+        name: "Aurora Testnet",
+        native_currency: NativeCurrency::new("ETH", 18),
+        explorer_url: "https://explorer.testnet.aurora.dev",
+        testnet: true,
+        aliases: &["aurora-testnet"],
+    },
+    ChainInfo {
+        chain_id: "near:mainnet",
+        name: "Near Mainnet",
+        native_currency: NativeCurrency::new("NEAR", 24),
+        explorer_url: "https://nearblocks.io",
+        testnet: false,
+        aliases: &["near", "near-mainnet"],
+    },
+    ChainInfo {
+        chain_id: "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp",
+        name: "Solana Mainnet",
+        native_currency: NativeCurrency::new("SOL", 9),
+        explorer_url: "https://explorer.solana.com",
+        testnet: false,
+        aliases: &["solana", "solana-mainnet"],
+    },
+    ChainInfo {
+        chain_id: "solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1",
+        name: "Solana Devnet",
+        native_currency: NativeCurrency::new("SOL", 9),
+        explorer_url: "https://explorer.solana.com/?cluster=devnet",
+        testnet: true,
+        aliases: &["solana-devnet"],
+    },
+    ChainInfo {
+        chain_id: "solana:4uhcVJyU9pJkvQyS88uRDiswHXSCkY3z",
+        name: "Solana Testnet",
+        native_currency: NativeCurrency::new("SOL", 9),
+        explorer_url: "https://explorer.solana.com/?cluster=testnet",
+        testnet: true,
+        aliases: &["solana-testnet"],
+    },
+];
+
+/// Canonical human-readable chain ids of the chains the names registry, the
+/// sessions co-signer and the Zerion and Coinbase providers work with, the
+/// other registry chains are not passed to them
+const CONVERTED_CHAINS: &[&str] = &[
+    "arbitrum",
+    "aurora",
+    "avalanche",
+    "base",
+    "base-sepolia-testnet",
+    "binance-smart-chain",
+    "blast",
+    "celo",
+    "ethereum",
+    "fantom",
+    "goerli",
+    "linea",
+    "optimism",
+    "polygon",
+    "scroll",
+    "sepolia",
+    "xdai",
+    "zksync-era",
+    "zora",
+];
+
+static CHAIN_ALIASES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    CHAINS
+        .iter()
+        .flat_map(|chain| chain.aliases.iter().map(|alias| (*alias, chain.chain_id)))
+        .collect()
+});
+
+static CHAINS_BY_ID: Lazy<HashMap<&'static str, &'static ChainInfo>> =
+    Lazy::new(|| CHAINS.iter().map(|chain| (chain.chain_id, chain)).collect());
+
+/// Resolves the human-readable chain alias (e.g. `polygon`) to the CAIP-2
+/// chain id (e.g. `eip155:137`), chain ids that are not aliases are returned
+/// as is
+pub fn resolve_chain_id(chain_id: &str) -> String {
+    CHAIN_ALIASES
+        .get(chain_id.to_lowercase().as_str())
+        .map(|chain_id| (*chain_id).to_owned())
+        .unwrap_or_else(|| chain_id.to_owned())
+}
+
+/// Returns the registry entry of the CAIP-2 chain id
+pub fn get_chain_info(chain_id: &str) -> Option<&'static ChainInfo> {
+    CHAINS_BY_ID.get(chain_id).copied()
+}
+
+/// Canonical human-readable chain id of the chain if it's one of the
+/// [`CONVERTED_CHAINS`]
+fn converted_chain_name(chain_id: &str) -> Option<&'static str> {
+    get_chain_info(chain_id)
+        .and_then(|chain| chain.aliases.first())
+        .copied()
+        .filter(|chain_name| CONVERTED_CHAINS.contains(chain_name))
+}
+
+/// Converts the human-readable chain id (e.g. `polygon`) to the CAIP-2 chain
+/// id (e.g. `eip155:137`)
+pub fn to_caip2(chain_name: &str) -> Option<String> {
+    let chain_id = CHAIN_ALIASES
+        .get(chain_name.to_lowercase().as_str())
+        .filter(|chain_id| converted_chain_name(chain_id).is_some());
+    if chain_id.is_none() {
+        warn!("CAIP-2 Convertion: Chain name is not found: {}", chain_name);
+    }
+    chain_id.map(|chain_id| (*chain_id).to_owned())
+}
+
+/// Converts the CAIP-2 chain id (e.g. `eip155:137`) to the canonical
+/// human-readable chain id (e.g. `polygon`)
+pub fn from_caip2(chain_id: &str) -> Option<String> {
+    let chain_name = converted_chain_name(chain_id);
+    if chain_name.is_none() {
+        warn!("CAIP-2 Convertion: Chain id is not found: {}", chain_id);
+    }
+    chain_name.map(str::to_owned)
+}
+
+/// Whether the EVM chain is supported by the names registry and the sessions
+/// co-signer
+pub fn is_evm_chain_supported(chain_id: u64) -> bool {
+    converted_chain_name(&format!("eip155:{chain_id}")).is_some()
+}
+
+/// Deserializes the `chainId` query parameter resolving the chain aliases
+pub fn deserialize_chain_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(|chain_id| resolve_chain_id(&chain_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::HashSet};
+
+    #[test]
+    fn resolves_aliases() {
+        assert_eq!(resolve_chain_id("polygon"), "eip155:137");
+        assert_eq!(resolve_chain_id("Base"), "eip155:8453");
+        assert_eq!(resolve_chain_id("bsc"), "eip155:56");
+        assert_eq!(
+            resolve_chain_id("solana"),
+            "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"
+        );
+        assert_eq!(resolve_chain_id("near"), "near:mainnet");
+        // CAIP-2 chain ids and unknown chains are passed through
+        assert_eq!(resolve_chain_id("eip155:10"), "eip155:10");
+        assert_eq!(resolve_chain_id("unknown"), "unknown");
+    }
+
+    #[test]
+    fn converts_human_readable_chain_ids() {
+        for (chain_name, chain_id) in [
+            ("ethereum", "eip155:1"),
+            ("mainnet", "eip155:1"),
+            ("goerli", "eip155:5"),
+            ("optimism", "eip155:10"),
+            ("bsc", "eip155:56"),
+            ("gnosis", "eip155:100"),
+            ("xdai", "eip155:100"),
+            ("polygon", "eip155:137"),
+            ("base", "eip155:8453"),
+            ("base_sepolia_testnet", "eip155:84532"),
+            ("linea", "eip155:59144"),
+            ("zora", "eip155:7777777"),
+        ] {
+            assert_eq!(to_caip2(chain_name).as_deref(), Some(chain_id));
+        }
+        assert_eq!(to_caip2("unknown"), None);
+
+        for (chain_id, chain_name) in [
+            ("eip155:1", "ethereum"),
+            ("eip155:5", "goerli"),
+            ("eip155:10", "optimism"),
+            ("eip155:56", "binance-smart-chain"),
+            ("eip155:100", "xdai"),
+            ("eip155:137", "polygon"),
+            ("eip155:324", "zksync-era"),
+            ("eip155:8453", "base"),
+            ("eip155:84532", "base-sepolia-testnet"),
+        ] {
+            assert_eq!(from_caip2(chain_id).as_deref(), Some(chain_name));
+        }
+        assert_eq!(from_caip2("eip155:7854577"), None);
+
+        assert!(is_evm_chain_supported(59144));
+        assert!(!is_evm_chain_supported(59160));
+    }
+
+    #[test]
+    fn converts_only_converted_chains() {
+        for chain_name in CONVERTED_CHAINS {
+            let chain_id = to_caip2(chain_name).unwrap();
+            assert_eq!(from_caip2(&chain_id).as_deref(), Some(*chain_name));
+        }
+
+        // Other registry chains including the testnets are not converted
+        assert_eq!(to_caip2("avalanche-fuji"), None);
+        assert_eq!(from_caip2("eip155:43113"), None);
+        assert_eq!(from_caip2("eip155:97"), None);
+        assert_eq!(from_caip2("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"), None);
+        assert!(!is_evm_chain_supported(43113));
+        assert!(!is_evm_chain_supported(97));
+        assert!(is_evm_chain_supported(84532));
+    }
+
+    #[test]
+    fn aliases_are_unique() {
+        let mut aliases = HashSet::new();
+        for alias in CHAINS.iter().flat_map(|chain| chain.aliases.iter()) {
+            assert_eq!(alias.to_lowercase(), *alias);
+            assert!(aliases.insert(alias), "Duplicate chain alias: {alias}");
+        }
+        assert_eq!(CHAINS_BY_ID.len(), CHAINS.len());
+    }
+}
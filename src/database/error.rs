@@ -6,6 +6,8 @@ pub enum DatabaseError {
     BadArgument(String),
     #[error("Address required: {0}")]
     AddressRequired(String),
+    #[error("Name owner was changed: {0}")]
+    OwnerChanged(String),
     #[error("{0:?}")]
     SerdeJson(#[from] serde_json::Error),
 }
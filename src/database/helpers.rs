@@ -153,14 +153,19 @@ pub async fn get_addresses_by_name(
 
     let mut result_map = types::ENSIP11AddressesMap::new();
 
+    // Chain ID of the address is the ENSIP-11 coin type for all of the namespaces
     for row in rows_result {
-        if row.namespace != types::SupportedNamespaces::Eip155 {
-            error!("Unsupported namespace: {:?}", row.namespace);
+        let coin_type = row.chain_id.parse::<u32>().unwrap_or_default();
+        if types::SupportedNamespaces::from_slip44(coin_type).as_ref() != Some(&row.namespace) {
+            error!(
+                "Coin type {} is not in the namespace: {:?}",
+                coin_type, row.namespace
+            );
             continue;
         }
 
         result_map.insert(
-            row.chain_id.parse::<u32>().unwrap_or_default(),
+            coin_type,
             types::Address {
                 address: row.address,
                 created_at: Some(row.created_at),
@@ -284,6 +289,91 @@ pub async fn insert_or_update_address<'e>(
     Ok(result_map)
 }
 
+/// Name ownership transfer, the name addresses are replaced with the new owner
+/// addresses and the transfer is recorded. Fails if the current owner address
+/// of the coin type was changed before the transfer.
+#[instrument(skip(postgres))]
+pub async fn transfer_name(
+    transfer: types::NameTransfer,
+    owner_coin_type: u32,
+    namespace: types::SupportedNamespaces,
+    addresses: types::ENSIP11AddressesMap,
+    postgres: &PgPool,
+) -> Result<(), DatabaseError> {
+    if addresses.is_empty() {
+        return Err(DatabaseError::BadArgument(
+            "At least one address is required for the new owner".to_string(),
+        ));
+    }
+    let mut transaction = postgres.begin().await?;
+
+    // Locking the name row to serialize the concurrent transfers of the name
+    let lock_name_query = "
+      SELECT name FROM names
+        WHERE name = $1
+        FOR UPDATE
+    ";
+    let locked_name = sqlx::query::<Postgres>(lock_name_query)
+        .bind(&transfer.name)
+        .fetch_optional(&mut *transaction)
+        .await?;
+    if locked_name.is_none() {
+        return Err(DatabaseError::OwnerChanged(transfer.name));
+    }
+
+    let delete_addresses_query = "
+      DELETE FROM addresses
+        WHERE name = $1
+        RETURNING namespace, chain_id, address, created_at
+    ";
+    let deleted_addresses = sqlx::query_as::<Postgres, RowAddress>(delete_addresses_query)
+        .bind(&transfer.name)
+        .fetch_all(&mut *transaction)
+        .await?;
+    let is_owner_address = deleted_addresses.iter().any(|row| {
+        row.chain_id.parse::<u32>().ok() == Some(owner_coin_type)
+            && row.address == transfer.from_address
+    });
+    if !is_owner_address {
+        // Transaction is rolled back on drop
+        return Err(DatabaseError::OwnerChanged(transfer.name));
+    }
+
+    for address in addresses {
+        insert_or_update_address(
+            transfer.name.clone(),
+            namespace.clone(),
+            format!("{}", address.0),
+            address.1.address,
+            &mut *transaction,
+        )
+        .await?;
+    }
+
+    let update_name_query = "
+      UPDATE names SET updated_at = NOW()
+        WHERE name = $1
+    ";
+    sqlx::query::<Postgres>(update_name_query)
+        .bind(&transfer.name)
+        .execute(&mut *transaction)
+        .await?;
+
+    let insert_transfer_query = "
+      INSERT INTO name_transfers (name, from_address, to_address, accepted)
+        VALUES ($1, $2, $3, $4)
+    ";
+    sqlx::query::<Postgres>(insert_transfer_query)
+        .bind(&transfer.name)
+        .bind(&transfer.from_address)
+        .bind(&transfer.to_address)
+        .bind(transfer.accepted)
+        .execute(&mut *transaction)
+        .await?;
+
+    transaction.commit().await.map_err(DatabaseError::SqlxError)
+}
+
 #[instrument(skip(postgres), level = "debug")]
 pub async fn get_account_names_stats(
     postgres: &PgPool,
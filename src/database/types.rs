@@ -1,4 +1,5 @@
 use {
+    crate::utils::crypto::{CaipNamespaces, SLIP44_BITCOIN_COIN_TYPE, SLIP44_SOLANA_COIN_TYPE},
     chrono::{DateTime, Utc},
     serde::{Deserialize, Serialize},
     sqlx::{FromRow, Type},
@@ -11,13 +12,21 @@ use {
 pub enum SupportedNamespaces {
     /// Ethereum
     Eip155,
+    /// Solana
+    Solana,
+    /// Bitcoin
+    Bip122,
 }
 
 impl SupportedNamespaces {
-    // Convert a SLIP-44 coin type to the SupportedNamespaces enum
+    // Convert a SLIP-44 coin type to the SupportedNamespaces enum, ENSIP-11
+    // coin types of the EVM chains are converted to the Ethereum namespace
     pub fn from_slip44(coin_type: u32) -> Option<SupportedNamespaces> {
         match coin_type {
             60 => Some(SupportedNamespaces::Eip155),
+            SLIP44_SOLANA_COIN_TYPE => Some(SupportedNamespaces::Solana),
+            SLIP44_BITCOIN_COIN_TYPE => Some(SupportedNamespaces::Bip122),
+            coin_type if coin_type & 0x80000000 != 0 => Some(SupportedNamespaces::Eip155),
             _ => None,
         }
     }
@@ -26,6 +35,17 @@ impl SupportedNamespaces {
     pub fn to_slip44(&self) -> u32 {
         match self {
             SupportedNamespaces::Eip155 => 60,
+            SupportedNamespaces::Solana => SLIP44_SOLANA_COIN_TYPE,
+            SupportedNamespaces::Bip122 => SLIP44_BITCOIN_COIN_TYPE,
+        }
+    }
+
+    // Convert from the enum to the CAIP-2 namespace
+    pub fn to_caip_namespace(&self) -> CaipNamespaces {
+        match self {
+            SupportedNamespaces::Eip155 => CaipNamespaces::Eip155,
+            SupportedNamespaces::Solana => CaipNamespaces::Solana,
+            SupportedNamespaces::Bip122 => CaipNamespaces::Bip122,
         }
     }
 }
@@ -60,3 +80,13 @@ pub struct NameAndAddresses {
     pub attributes: Option<sqlx::types::Json<HashMap<String, String>>>,
     pub addresses: ENSIP11AddressesMap,
 }
+
+/// Represents the name ownership transfer record
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NameTransfer {
+    pub name: String,
+    pub from_address: String,
+    pub to_address: String,
+    /// Whether the transfer was accepted by the recipient signature
+    pub accepted: bool,
+}
@@ -5,15 +5,89 @@ use {
     tracing::error,
 };
 
+/// GetBlock shared nodes endpoints by the continent of their location:
+/// Frankfurt, New York and Singapore. Access tokens are issued for the exact
+/// endpoint location
+const REGIONAL_API_URLS: &[(&str, &str)] = &[
+    ("EU", "https://go.getblock.io"),
+    ("NA", "https://go.getblock.us"),
+    ("AS", "https://go.getblock.asia"),
+];
+/// Continent of the default endpoint the `supported_chains` tokens are
+/// issued for
+const DEFAULT_CONTINENT: &str = "EU";
+
 #[derive(Debug)]
 pub struct GetBlockConfig {
     pub supported_chains: HashMap<String, (String, Weight)>,
+    pub supported_ws_chains: HashMap<String, (String, Weight)>,
+    /// Regional endpoints API URLs and the access tokens by the chain id by
+    /// the continent
+    pub regional_chains: HashMap<String, (String, HashMap<String, String>)>,
 }
 
 impl GetBlockConfig {
     pub fn new(access_tokens_json: String) -> Self {
         Self {
-            supported_chains: extract_supported_chains(access_tokens_json),
+            supported_chains: extract_supported_chains(access_tokens_json, supported_chain_ids()),
+            supported_ws_chains: HashMap::new(),
+            regional_chains: HashMap::new(),
+        }
+    }
+
+    /// Adds the access tokens of the regional endpoints in JSON format keyed
+    /// by the continent and the chain id, e.g. `{"NA": {"eip155:1": "token"}}`
+    pub fn with_regional_access_tokens(mut self, access_tokens_json: Option<&str>) -> Self {
+        let Some(access_tokens_json) = access_tokens_json else {
+            return self;
+        };
+        let access_tokens: HashMap<String, HashMap<String, String>> =
+            match serde_json::from_str(access_tokens_json) {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    error!(
+                        "Failed to parse JSON with GetBlock regional access tokens: {}",
+                        e
+                    );
+                    return self;
+                }
+            };
+
+        let supported_chain_ids = supported_chain_ids();
+        for (continent, tokens) in access_tokens {
+            let Some(api_url) = regional_api_url(&continent) else {
+                error!(
+                    "GetBlock has no regional endpoint for {}, skipping it",
+                    continent
+                );
+                continue;
+            };
+            let tokens = tokens
+                .into_iter()
+                .filter(|(chain_id, _)| supported_chain_ids.contains_key(chain_id.as_str()))
+                .collect::<HashMap<_, _>>();
+            if !tokens.is_empty() {
+                self.regional_chains
+                    .insert(continent.to_ascii_uppercase(), (api_url.to_owned(), tokens));
+            }
+        }
+        self
+    }
+
+    /// API URL of the endpoint the `supported_chains` tokens are issued for
+    pub fn default_api_url() -> &'static str {
+        regional_api_url(DEFAULT_CONTINENT).unwrap_or_default()
+    }
+
+    /// GetBlock issues separate access tokens for the WebSocket endpoints
+    pub fn new_ws(ws_access_tokens_json: String) -> Self {
+        Self {
+            supported_chains: HashMap::new(),
+            supported_ws_chains: extract_supported_chains(
+                ws_access_tokens_json,
+                supported_ws_chain_ids(),
+            ),
+            regional_chains: HashMap::new(),
         }
     }
 }
@@ -24,15 +98,60 @@ impl ProviderConfig for GetBlockConfig {
     }
 
     fn supported_ws_chains(self) -> HashMap<String, (String, Weight)> {
-        HashMap::new()
+        self.supported_ws_chains
     }
 
     fn provider_kind(&self) -> crate::providers::ProviderKind {
         crate::providers::ProviderKind::GetBlock
     }
+
+    fn continents(&self) -> Vec<String> {
+        let mut continents = self
+            .regional_chains
+            .keys()
+            .cloned()
+            .chain([DEFAULT_CONTINENT.to_owned()])
+            .collect::<Vec<_>>();
+        continents.sort();
+        continents.dedup();
+        continents
+    }
 }
 
-fn extract_supported_chains(access_tokens_json: String) -> HashMap<String, (String, Weight)> {
+fn regional_api_url(continent: &str) -> Option<&'static str> {
+    REGIONAL_API_URLS
+        .iter()
+        .find(|(regional_continent, _)| regional_continent.eq_ignore_ascii_case(continent))
+        .map(|(_, api_url)| *api_url)
+}
+
+fn supported_chain_ids() -> HashMap<&'static str, Priority> {
+    // Keep in-sync with SUPPORTED_CHAINS.md
+    HashMap::from([
+        ("eip155:1", Priority::Low),
+        ("eip155:56", Priority::Low),
+        ("eip155:137", Priority::Normal),
+        ("eip155:324", Priority::Normal),
+        ("eip155:17000", Priority::Normal),
+        ("eip155:11155111", Priority::Normal),
+        ("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp", Priority::Normal),
+    ])
+}
+
+fn supported_ws_chain_ids() -> HashMap<&'static str, Priority> {
+    // Keep in-sync with SUPPORTED_CHAINS.md
+    HashMap::from([
+        ("eip155:1", Priority::Low),
+        ("eip155:56", Priority::Normal),
+        ("eip155:137", Priority::Normal),
+        ("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp", Priority::Normal),
+    ])
+}
+
+fn extract_supported_chains(
+    access_tokens_json: String,
+    supported_chain_ids: HashMap<&'static str, Priority>,
+) -> HashMap<String, (String, Weight)> {
     let access_tokens: HashMap<String, String> = match serde_json::from_str(&access_tokens_json) {
         Ok(tokens) => tokens,
         Err(_) => {
@@ -44,17 +163,6 @@ fn extract_supported_chains(access_tokens_json: String) -> HashMap<String, (Stri
         }
     };
 
-    // Keep in-sync with SUPPORTED_CHAINS.md
-    let supported_chain_ids = HashMap::from([
-        ("eip155:1", Priority::Low),
-        ("eip155:56", Priority::Low),
-        ("eip155:137", Priority::Normal),
-        ("eip155:324", Priority::Normal),
-        ("eip155:17000", Priority::Normal),
-        ("eip155:11155111", Priority::Normal),
-        ("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp", Priority::Normal),
-    ]);
-
     let access_tokens_with_weights: HashMap<String, (String, Weight)> = supported_chain_ids
         .iter()
         .filter_map(|(&key, &weight)| {
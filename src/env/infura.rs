@@ -35,6 +35,10 @@ impl ProviderConfig for InfuraConfig {
     fn provider_kind(&self) -> crate::providers::ProviderKind {
         crate::providers::ProviderKind::Infura
     }
+
+    fn is_archive(&self) -> bool {
+        true
+    }
 }
 
 fn default_supported_chains() -> HashMap<String, (String, Weight)> {
@@ -80,6 +80,17 @@ pub trait ProviderConfig {
     fn supported_chains(self) -> HashMap<String, (String, Weight)>;
     fn supported_ws_chains(self) -> HashMap<String, (String, Weight)>;
     fn provider_kind(&self) -> ProviderKind;
+
+    /// Continents codes the provider endpoints are located in, the provider is
+    /// considered global if empty
+    fn continents(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Whether the provider endpoints serve the archive state of the chains
+    fn is_archive(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +124,7 @@ mod test {
             ("RPC_PROXY_GEOIP_DB_KEY", "GEOIP_DB_KEY"),
             // Integration tests config.
             ("RPC_PROXY_TESTING_PROJECT_ID", "TESTING_PROJECT_ID"),
+            ("RPC_PROXY_ADMIN_API_TOKEN", "ADMIN_API_TOKEN"),
             // Registry config.
             ("RPC_PROXY_REGISTRY_API_URL", "API_URL"),
             ("RPC_PROXY_REGISTRY_API_AUTH_TOKEN", "API_AUTH_TOKEN"),
@@ -163,6 +175,35 @@ mod test {
             ("RPC_PROXY_PROVIDER_ONE_INCH_API_KEY", "ONE_INCH_API_KEY"),
             ("RPC_PROXY_PROVIDER_ONE_INCH_REFERRER", "ONE_INCH_REFERRER"),
             ("RPC_PROXY_PROVIDER_GETBLOCK_ACCESS_TOKENS", "{}"),
+            ("RPC_PROXY_PROVIDER_GETBLOCK_WS_ACCESS_TOKENS", "{}"),
+            (
+                "RPC_PROXY_PROVIDER_GETBLOCK_REGIONAL_ACCESS_TOKENS",
+                r#"{"NA":{}}"#,
+            ),
+            (
+                "RPC_PROXY_PROVIDER_SHADOW_PROVIDERS",
+                r#"{"eip155:1":["Publicnode"]}"#,
+            ),
+            (
+                "RPC_PROXY_PROVIDER_SHADOW_TRAFFIC_PERCENTAGES",
+                r#"{"eip155:1":5}"#,
+            ),
+            (
+                "RPC_PROXY_PROVIDER_REQUEST_COSTS",
+                r#"{"eip155:1/Infura":10}"#,
+            ),
+            ("RPC_PROXY_PROVIDER_ROUTING_SLO_SUCCESS_PERCENTAGE", "98"),
+            ("RPC_PROXY_PROVIDER_ROUTING_SLO_P95_LATENCY_MS", "800"),
+            ("RPC_PROXY_PROVIDER_CONTINENTS", r#"{"Pokt":["NA","EU"]}"#),
+            (
+                "RPC_PROXY_PROVIDER_CALL_POLICIES",
+                r#"{"eip155:10":{"timeoutMs":3000}}"#,
+            ),
+            ("RPC_PROXY_PROVIDER_HTTP_POOL_MAX_IDLE_PER_HOST", "32"),
+            ("RPC_PROXY_PROVIDER_HTTP_POOL_IDLE_TIMEOUT_SECS", "60"),
+            ("RPC_PROXY_PROVIDER_HTTP_TCP_KEEPALIVE_SECS", "30"),
+            ("RPC_PROXY_PROVIDER_HTTP_CONNECT_TIMEOUT_MS", "2000"),
+            ("RPC_PROXY_PROVIDER_HTTP2_KEEPALIVE_INTERVAL_SECS", "20"),
             ("RPC_PROXY_PROVIDER_PIMLICO_API_KEY", "PIMLICO_API_KEY"),
             (
                 "RPC_PROXY_PROVIDER_SOLSCAN_API_V1_TOKEN",
@@ -194,6 +235,10 @@ mod test {
                 "RPC_PROXY_RATE_LIMITING_IP_WHITELIST",
                 "127.0.0.1,127.0.0.2",
             ),
+            ("RPC_PROXY_RATE_LIMITING_WS_MAX_TOKENS", "50"),
+            ("RPC_PROXY_RATE_LIMITING_WS_REFILL_RATE", "5"),
+            ("RPC_PROXY_RATE_LIMITING_WS_MAX_SUBSCRIPTIONS", "20"),
+            ("RPC_PROXY_RATE_LIMITING_WS_QUOTA_CHECK_INTERVAL_SEC", "30"),
             // IRN config.
             ("RPC_PROXY_IRN_NODE", "node"),
             ("RPC_PROXY_IRN_KEY", "key"),
@@ -201,6 +246,22 @@ mod test {
             ("RPC_PROXY_IRN_NAMESPACE_SECRET", "namespace"),
             // Names configuration
             ("RPC_PROXY_NAMES_ALLOWED_ZONES", "test1.id,test2.id"),
+            ("RPC_PROXY_NAMES_IPFS_GATEWAY", "https://ipfs.example.com"),
+            (
+                "RPC_PROXY_NAMES_ARWEAVE_GATEWAY",
+                "https://arweave.example.com",
+            ),
+            ("RPC_PROXY_NAMES_AVATAR_PROXY_ENABLED", "true"),
+            ("RPC_PROXY_NAMES_GATEWAY_SIGNING_KEY", "signing_key"),
+            ("RPC_PROXY_NAMES_GATEWAY_RESPONSE_TTL", "600"),
+            (
+                "RPC_PROXY_NAMES_IDENTITY_SOURCES",
+                r#"{"farcaster":{"url":"https://example.com/{address}"}}"#,
+            ),
+            (
+                "RPC_PROXY_NAMES_IDENTITY_SOURCES_PRIORITY",
+                r#"{"*":["ens","local","farcaster"]}"#,
+            ),
         ];
 
         values.iter().for_each(set_env_var);
@@ -225,6 +286,7 @@ mod test {
                     geoip_db_key: Some("GEOIP_DB_KEY".to_owned()),
                     testing_project_id: Some("TESTING_PROJECT_ID".to_owned()),
                     validate_project_id: true,
+                    admin_api_token: Some("ADMIN_API_TOKEN".to_owned()),
                 },
                 registry: project::Config {
                     api_url: Some("API_URL".to_owned()),
@@ -270,6 +332,20 @@ mod test {
                     one_inch_api_key: Some("ONE_INCH_API_KEY".to_owned()),
                     one_inch_referrer: Some("ONE_INCH_REFERRER".to_owned()),
                     getblock_access_tokens: Some("{}".to_owned()),
+                    getblock_ws_access_tokens: Some("{}".to_owned()),
+                    getblock_regional_access_tokens: Some(r#"{"NA":{}}"#.to_owned()),
+                    shadow_providers: Some(r#"{"eip155:1":["Publicnode"]}"#.to_owned()),
+                    shadow_traffic_percentages: Some(r#"{"eip155:1":5}"#.to_owned()),
+                    request_costs: Some(r#"{"eip155:1/Infura":10}"#.to_owned()),
+                    routing_slo_success_percentage: Some(98),
+                    routing_slo_p95_latency_ms: Some(800),
+                    continents: Some(r#"{"Pokt":["NA","EU"]}"#.to_owned()),
+                    call_policies: Some(r#"{"eip155:10":{"timeoutMs":3000}}"#.to_owned()),
+                    http_pool_max_idle_per_host: Some(32),
+                    http_pool_idle_timeout_secs: Some(60),
+                    http_tcp_keepalive_secs: Some(30),
+                    http_connect_timeout_ms: Some(2000),
+                    http2_keepalive_interval_secs: Some(20),
                     pimlico_api_key: "PIMLICO_API_KEY".to_string(),
                     solscan_api_v1_token: "SOLSCAN_API_V1_TOKEN".to_string(),
                     solscan_api_v2_token: "SOLSCAN_API_V2_TOKEN".to_string(),
@@ -279,6 +355,10 @@ mod test {
                     refill_interval_sec: Some(1),
                     refill_rate: Some(10),
                     ip_whitelist: Some(vec!["127.0.0.1".into(), "127.0.0.2".into()]),
+                    ws_max_tokens: Some(50),
+                    ws_refill_rate: Some(5),
+                    ws_max_subscriptions: Some(20),
+                    ws_quota_check_interval_sec: Some(30),
                 },
                 irn: IrnConfig {
                     node: Some("node".to_owned()),
@@ -288,6 +368,17 @@ mod test {
                 },
                 names: NamesConfig {
                     allowed_zones: Some(vec!["test1.id".to_owned(), "test2.id".to_owned()]),
+                    ipfs_gateway: Some("https://ipfs.example.com".to_owned()),
+                    arweave_gateway: Some("https://arweave.example.com".to_owned()),
+                    avatar_proxy_enabled: Some(true),
+                    gateway_signing_key: Some("signing_key".to_owned()),
+                    gateway_response_ttl: Some(600),
+                    identity_sources: Some(
+                        r#"{"farcaster":{"url":"https://example.com/{address}"}}"#.to_owned(),
+                    ),
+                    identity_sources_priority: Some(
+                        r#"{"*":["ens","local","farcaster"]}"#.to_owned(),
+                    ),
                 }
             }
         );
@@ -7,12 +7,14 @@ use {
 #[derive(Debug)]
 pub struct PublicnodeConfig {
     pub supported_chains: HashMap<String, (String, Weight)>,
+    pub supported_ws_chains: HashMap<String, (String, Weight)>,
 }
 
 impl Default for PublicnodeConfig {
     fn default() -> Self {
         Self {
             supported_chains: default_supported_chains(),
+            supported_ws_chains: default_ws_supported_chains(),
         }
     }
 }
@@ -23,7 +25,7 @@ impl ProviderConfig for PublicnodeConfig {
     }
 
     fn supported_ws_chains(self) -> HashMap<String, (String, Weight)> {
-        HashMap::new()
+        self.supported_ws_chains
     }
 
     fn provider_kind(&self) -> crate::providers::ProviderKind {
@@ -117,3 +119,43 @@ fn default_supported_chains() -> HashMap<String, (String, Weight)> {
         ),
     ])
 }
+
+fn default_ws_supported_chains() -> HashMap<String, (String, Weight)> {
+    // Keep in-sync with SUPPORTED_CHAINS.md
+
+    HashMap::from([
+        // Ethereum mainnet
+        (
+            "eip155:1".into(),
+            ("ethereum".into(), Weight::new(Priority::Low).unwrap()),
+        ),
+        // Base mainnet
+        (
+            "eip155:8453".into(),
+            ("base".into(), Weight::new(Priority::Normal).unwrap()),
+        ),
+        // Binance Smart Chain mainnet
+        (
+            "eip155:56".into(),
+            ("bsc".into(), Weight::new(Priority::Normal).unwrap()),
+        ),
+        // Binance Smart Chain testnet
+        (
+            "eip155:97".into(),
+            ("bsc-testnet".into(), Weight::new(Priority::Normal).unwrap()),
+        ),
+        // Avalanche c chain
+        (
+            "eip155:43114".into(),
+            (
+                "avalanche-c-chain".into(),
+                Weight::new(Priority::Normal).unwrap(),
+            ),
+        ),
+        // Polygon bor mainnet
+        (
+            "eip155:137".into(),
+            ("polygon-bor".into(), Weight::new(Priority::Normal).unwrap()),
+        ),
+    ])
+}
@@ -8,15 +8,17 @@ use {
 #[derive(Debug)]
 pub struct QuicknodeConfig {
     pub supported_chains: HashMap<String, (String, Weight)>,
+    pub supported_ws_chains: HashMap<String, (String, Weight)>,
     pub chain_subdomains: HashMap<String, String>,
 }
 
 impl QuicknodeConfig {
     pub fn new(api_tokens_json: String) -> Self {
-        let (supported_chains, chain_subdomains) =
+        let (supported_chains, supported_ws_chains, chain_subdomains) =
             extract_supported_chains_and_subdomains(api_tokens_json);
         Self {
             supported_chains,
+            supported_ws_chains,
             chain_subdomains,
         }
     }
@@ -28,17 +30,27 @@ impl ProviderConfig for QuicknodeConfig {
     }
 
     fn supported_ws_chains(self) -> HashMap<String, (String, Weight)> {
-        HashMap::new()
+        self.supported_ws_chains
     }
 
     fn provider_kind(&self) -> crate::providers::ProviderKind {
         crate::providers::ProviderKind::Quicknode
     }
+
+    fn is_archive(&self) -> bool {
+        true
+    }
 }
 
+type ChainsWithWeights = HashMap<String, (String, Weight)>;
+
 fn extract_supported_chains_and_subdomains(
     access_tokens_json: String,
-) -> (HashMap<String, (String, Weight)>, HashMap<String, String>) {
+) -> (
+    ChainsWithWeights,
+    ChainsWithWeights,
+    HashMap<String, String>,
+) {
     let access_tokens: HashMap<String, String> = match serde_json::from_str(&access_tokens_json) {
         Ok(tokens) => tokens,
         Err(_) => {
@@ -46,7 +58,7 @@ fn extract_supported_chains_and_subdomains(
                 "Failed to parse JSON with API access tokens for QuickNode provider. Using empty \
                  tokens."
             );
-            return (HashMap::new(), HashMap::new());
+            return (HashMap::new(), HashMap::new(), HashMap::new());
         }
     };
 
@@ -94,10 +106,39 @@ fn extract_supported_chains_and_subdomains(
             }
         })
         .collect();
+    // Keep in-sync with SUPPORTED_CHAINS.md
+    // WebSocket endpoints use the same access tokens and subdomains
+    let supported_ws_chain_ids = HashMap::from([
+        ("eip155:324", Priority::Normal),
+        ("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp", Priority::Normal),
+        ("solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1", Priority::Normal),
+        ("solana:4uhcVJyU9pJkvQyS88uRDiswHXSCkY3z", Priority::Normal),
+    ]);
+    let ws_access_tokens_with_weights: HashMap<String, (String, Weight)> = supported_ws_chain_ids
+        .iter()
+        .filter_map(|(&key, &weight)| {
+            let token = access_tokens.get(key)?;
+            match Weight::new(weight) {
+                Ok(weight) => Some((key.to_string(), (token.to_string(), weight))),
+                Err(_) => {
+                    error!(
+                        "Failed to create WebSocket Weight for key {} in QuickNode provider",
+                        key
+                    );
+                    None
+                }
+            }
+        })
+        .collect();
+
     let chain_ids_subdomains: HashMap<String, String> = supported_chain_ids
         .iter()
         .map(|(&key, (subdomain, _))| (key.to_string(), subdomain.to_string()))
         .collect();
 
-    (access_tokens_with_weights, chain_ids_subdomains)
+    (
+        access_tokens_with_weights,
+        ws_access_tokens_with_weights,
+        chain_ids_subdomains,
+    )
 }
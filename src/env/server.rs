@@ -18,6 +18,9 @@ pub struct ServerConfig {
     pub geoip_db_key: Option<String>,
     pub testing_project_id: Option<String>,
     pub validate_project_id: bool,
+    /// Bearer token for the admin API on the private server, the admin API
+    /// is disabled when not set
+    pub admin_api_token: Option<String>,
 }
 
 impl Default for ServerConfig {
@@ -34,6 +37,7 @@ impl Default for ServerConfig {
             geoip_db_key: None,
             testing_project_id: None,
             validate_project_id: true,
+            admin_api_token: None,
         }
     }
 }
@@ -105,6 +105,9 @@ pub enum RpcError {
     #[error(transparent)]
     AxumTungstenite(#[from] axum_tungstenite::Error),
 
+    #[error("WebSocket emulation error: {0}")]
+    WebSocketEmulation(String),
+
     #[error(transparent)]
     RateLimited(#[from] wc::rate_limit::RateLimitExceeded),
 
@@ -155,6 +158,21 @@ pub enum RpcError {
     #[error("No name is found for address")]
     NameByAddressNotFound,
 
+    #[error("Avatar proxy is disabled")]
+    AvatarProxyDisabled,
+
+    #[error("No avatar is found for address")]
+    AvatarNotFound,
+
+    #[error("Avatar fetch error: {0}")]
+    AvatarFetchError(String),
+
+    #[error("Names gateway is disabled")]
+    NamesGatewayDisabled,
+
+    #[error("Invalid names gateway request: {0}")]
+    InvalidGatewayRequest(String),
+
     #[error("Internal name resolver error")]
     InternalNameResolverError,
 
@@ -185,6 +203,12 @@ pub enum RpcError {
     #[error("Name owner validation error")]
     NameOwnerValidationError,
 
+    #[error("Name transfer error: {0}")]
+    NameTransferError(String),
+
+    #[error("Invalid admin API token")]
+    InvalidAdminToken,
+
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
@@ -425,6 +449,46 @@ impl IntoResponse for RpcError {
                 )),
             )
                 .into_response(),
+            Self::AvatarProxyDisabled => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    "".to_string(),
+                    "Avatar proxy is disabled".into(),
+                )),
+            )
+                .into_response(),
+            Self::AvatarNotFound => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    "address".to_string(),
+                    "No avatar for address is found".into(),
+                )),
+            )
+                .into_response(),
+            Self::AvatarFetchError(e) => (
+                StatusCode::BAD_GATEWAY,
+                Json(new_error_response(
+                    "avatar".to_string(),
+                    format!("Avatar fetch error: {}", e),
+                )),
+            )
+                .into_response(),
+            Self::NamesGatewayDisabled => (
+                StatusCode::NOT_FOUND,
+                Json(new_error_response(
+                    "".to_string(),
+                    "Names gateway is disabled".into(),
+                )),
+            )
+                .into_response(),
+            Self::InvalidGatewayRequest(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(new_error_response(
+                    "data".to_string(),
+                    format!("Invalid names gateway request: {}", e),
+                )),
+            )
+                .into_response(),
             Self::ExpiredTimestamp(e) => (
                 StatusCode::BAD_REQUEST,
                 Json(new_error_response(
@@ -449,6 +513,22 @@ impl IntoResponse for RpcError {
                 )),
             )
                 .into_response(),
+            Self::NameTransferError(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(new_error_response(
+                    "name".to_string(),
+                    format!("Name transfer error: {}", e),
+                )),
+            )
+                .into_response(),
+            Self::InvalidAdminToken => (
+                StatusCode::UNAUTHORIZED,
+                Json(new_error_response(
+                    "authorization".to_string(),
+                    "Invalid admin API token".into(),
+                )),
+            )
+                .into_response(),
             Self::SerdeJson(e) => (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 Json(new_error_response(
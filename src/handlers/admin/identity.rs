@@ -0,0 +1,35 @@
+use {
+    super::{super::HANDLER_TASK_METRICS, authorize},
+    crate::{error::RpcError, handlers::identity, state::AppState},
+    axum::extract::{Path, State},
+    ethers::types::H160,
+    hyper::{HeaderMap, StatusCode},
+    std::sync::Arc,
+    tracing::info,
+    wc::future::FutureExt,
+};
+
+pub async fn invalidate_handler(
+    state: State<Arc<AppState>>,
+    headers: HeaderMap,
+    address: Path<String>,
+) -> Result<StatusCode, RpcError> {
+    invalidate_handler_internal(state, headers, address)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("admin_identity_invalidate"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn invalidate_handler_internal(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+) -> Result<StatusCode, RpcError> {
+    authorize(&state, &headers)?;
+    let address = address
+        .parse::<H160>()
+        .map_err(|_| RpcError::InvalidAddress)?;
+    identity::invalidate_identity_cache(&state, address).await?;
+    info!("Identity cache of {address:#x} is invalidated");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,32 @@
+//! Admin API served on the private server for the on-call engineers.
+
+use {
+    crate::{error::RpcError, state::AppState, utils::crypto},
+    hyper::{header::AUTHORIZATION, HeaderMap},
+};
+
+pub mod identity;
+pub mod providers;
+
+/// Validates the admin bearer token, the admin API is disabled when the
+/// token is not configured
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), RpcError> {
+    let Some(admin_api_token) = state
+        .config
+        .server
+        .admin_api_token
+        .as_deref()
+        .filter(|token| !token.is_empty())
+    else {
+        return Err(RpcError::InvalidAdminToken);
+    };
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(RpcError::InvalidAdminToken)?;
+    if !crypto::constant_time_eq(admin_api_token, token) {
+        return Err(RpcError::InvalidAdminToken);
+    }
+    Ok(())
+}
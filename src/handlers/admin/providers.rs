@@ -0,0 +1,213 @@
+use {
+    super::{super::HANDLER_TASK_METRICS, authorize},
+    crate::{
+        error::RpcError,
+        providers::{ProviderKind, ProviderStatus, WeightOverride},
+        state::AppState,
+    },
+    axum::{
+        extract::{Query, State},
+        Json,
+    },
+    hyper::HeaderMap,
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+    tracing::info,
+    wc::future::FutureExt,
+};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvidersStatusQueryParams {
+    /// Returns all of the supported chains if not set
+    pub chain_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvidersStatusResponse {
+    pub chains: Vec<ChainProvidersStatus>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainProvidersStatus {
+    pub chain_id: String,
+    pub providers: Vec<ProviderStatus>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightOverrideRequest {
+    pub chain_id: String,
+    pub provider: String,
+    /// Clears the override if not set
+    #[serde(rename = "override")]
+    pub weight_override: Option<WeightOverride>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetOverridesQueryParams {
+    /// Resets the overrides for all of the chains if not set
+    pub chain_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunQueryParams {
+    pub chain_id: String,
+    /// Defaults to the number of providers of the chain call policy
+    pub max_providers: Option<usize>,
+    /// Client continent code to route for
+    pub continent: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunResponse {
+    pub chain_id: String,
+    /// Providers in the order the RPC proxy would try them
+    pub providers: Vec<String>,
+}
+
+pub async fn status_handler(
+    state: State<Arc<AppState>>,
+    query: Query<ProvidersStatusQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<ProvidersStatusResponse>, RpcError> {
+    status_handler_internal(state, query, headers)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("admin_providers_status"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn status_handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ProvidersStatusQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<ProvidersStatusResponse>, RpcError> {
+    authorize(&state, &headers)?;
+    let chains = state
+        .providers
+        .get_providers_status(query.chain_id.as_deref())?
+        .into_iter()
+        .map(|(chain_id, providers)| ChainProvidersStatus {
+            chain_id,
+            providers,
+        })
+        .collect();
+    Ok(Json(ProvidersStatusResponse { chains }))
+}
+
+pub async fn dry_run_handler(
+    state: State<Arc<AppState>>,
+    query: Query<DryRunQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<DryRunResponse>, RpcError> {
+    dry_run_handler_internal(state, query, headers)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("admin_providers_dry_run"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn dry_run_handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DryRunQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<DryRunResponse>, RpcError> {
+    authorize(&state, &headers)?;
+    let max_providers = query.max_providers.unwrap_or_else(|| {
+        state
+            .providers
+            .call_policies
+            .resolve(&query.chain_id, None)
+            .max_providers
+    });
+    let providers = state
+        .providers
+        .get_provider_kinds_for_chain_id(
+            &query.chain_id,
+            max_providers,
+            query.continent.as_deref(),
+        )?
+        .into_iter()
+        .map(|provider_kind| provider_kind.to_string())
+        .collect();
+    Ok(Json(DryRunResponse {
+        chain_id: query.chain_id,
+        providers,
+    }))
+}
+
+pub async fn override_handler(
+    state: State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<WeightOverrideRequest>,
+) -> Result<Json<ProvidersStatusResponse>, RpcError> {
+    override_handler_internal(state, headers, request)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("admin_providers_override"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn override_handler_internal(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    request: WeightOverrideRequest,
+) -> Result<Json<ProvidersStatusResponse>, RpcError> {
+    authorize(&state, &headers)?;
+    let provider_kind = ProviderKind::from_str(&request.provider)
+        .ok_or_else(|| RpcError::UnsupportedProvider(request.provider.clone()))?;
+    state
+        .providers
+        .set_weight_override(&request.chain_id, provider_kind, request.weight_override)
+        .await?;
+    info!(
+        "Provider {} weight override for {} is set to {:?}",
+        provider_kind, request.chain_id, request.weight_override
+    );
+    status_handler_internal(
+        State(state),
+        Query(ProvidersStatusQueryParams {
+            chain_id: Some(request.chain_id),
+        }),
+        headers,
+    )
+    .await
+}
+
+pub async fn reset_overrides_handler(
+    state: State<Arc<AppState>>,
+    query: Query<ResetOverridesQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<ProvidersStatusResponse>, RpcError> {
+    reset_overrides_handler_internal(state, query, headers)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("admin_providers_reset_overrides"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn reset_overrides_handler_internal(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ResetOverridesQueryParams>,
+    headers: HeaderMap,
+) -> Result<Json<ProvidersStatusResponse>, RpcError> {
+    authorize(&state, &headers)?;
+    state
+        .providers
+        .reset_weight_overrides(query.chain_id.as_deref())
+        .await?;
+    info!(
+        "Provider weight overrides are reset for {}",
+        query.chain_id.as_deref().unwrap_or("all chains")
+    );
+    status_handler_internal(
+        State(state),
+        Query(ProvidersStatusQueryParams {
+            chain_id: query.chain_id,
+        }),
+        headers,
+    )
+    .await
+}
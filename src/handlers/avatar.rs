@@ -0,0 +1,107 @@
+use {
+    super::{
+        identity::{lookup_identity, IdentityQueryParams},
+        HANDLER_TASK_METRICS,
+    },
+    crate::{error::RpcError, state::AppState},
+    axum::{
+        extract::{ConnectInfo, Path, Query, State},
+        response::{IntoResponse, Response},
+    },
+    ethers::abi::Address,
+    hyper::{
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        HeaderMap,
+    },
+    serde::Deserialize,
+    std::{net::SocketAddr, sync::Arc},
+    tracing::debug,
+    wc::future::FutureExt,
+};
+
+const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+const MIN_THUMBNAIL_SIZE: u32 = 16;
+const MAX_THUMBNAIL_SIZE: u32 = 512;
+const THUMBNAIL_CACHE_CONTROL: &str = "public, max-age=86400, s-maxage=86400";
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AvatarQueryParams {
+    pub project_id: String,
+    /// Width and height of the thumbnail square in pixels
+    pub size: Option<u32>,
+    /// Optional chain of the primary name, defaults to the mainnet
+    pub chain_id: Option<String>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    query: Query<AvatarQueryParams>,
+    headers: HeaderMap,
+    address: Path<String>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, connect_info, query, headers, address)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("avatar"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    Query(query): Query<AvatarQueryParams>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+) -> Result<Response, RpcError> {
+    if !state.config.names.avatar_proxy_enabled() {
+        return Err(RpcError::AvatarProxyDisabled);
+    }
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    let size = query.size.unwrap_or(DEFAULT_THUMBNAIL_SIZE);
+    if !(MIN_THUMBNAIL_SIZE..=MAX_THUMBNAIL_SIZE).contains(&size) {
+        return Err(RpcError::InvalidParameter(format!(
+            "The size must be between {MIN_THUMBNAIL_SIZE} and {MAX_THUMBNAIL_SIZE}"
+        )));
+    }
+    let address = address
+        .parse::<Address>()
+        .map_err(|_| RpcError::InvalidAddress)?;
+
+    let (_, identity) = lookup_identity(
+        address,
+        State(state.clone()),
+        connect_info,
+        Query(IdentityQueryParams {
+            project_id: query.project_id,
+            use_cache: None,
+            client_id: None,
+            sender: None,
+            chain_id: query.chain_id,
+            records: None,
+            api_version: None,
+        }),
+        headers,
+    )
+    .await?;
+    let avatar = identity.avatar().ok_or(RpcError::AvatarNotFound)?;
+
+    let thumbnail = state.avatar_proxy.thumbnail(avatar, size).await;
+    state.metrics.add_avatar_proxy_request(thumbnail.is_ok());
+    let thumbnail = thumbnail.map_err(|e| {
+        debug!("Avatar proxy error for {address:#x}: {e}");
+        RpcError::AvatarFetchError(e.to_string())
+    })?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "image/png"),
+            (CACHE_CONTROL, THUMBNAIL_CACHE_CONTROL),
+        ],
+        thumbnail,
+    )
+        .into_response())
+}
@@ -1,7 +1,7 @@
 use {
     super::HANDLER_TASK_METRICS,
     crate::{
-        error::RpcError, providers::SupportedBundlerOps, state::AppState,
+        chains, error::RpcError, providers::SupportedBundlerOps, state::AppState,
         utils::crypto::disassemble_caip2,
     },
     axum::{
@@ -24,6 +24,7 @@ pub enum SupportedBundlers {
 #[serde(rename_all = "camelCase")]
 pub struct BundlerQueryParams {
     pub project_id: String,
+    #[serde(deserialize_with = "chains::deserialize_chain_id")]
     pub chain_id: String,
     pub bundler: SupportedBundlers,
 }
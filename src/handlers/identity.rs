@@ -1,10 +1,16 @@
 use {
-    super::{proxy::rpc_call, RpcQueryParams, HANDLER_TASK_METRICS},
+    super::{proxy::rpc_call, resolve::resolve_ens_address, RpcQueryParams, HANDLER_TASK_METRICS},
     crate::{
-        analytics::IdentityLookupInfo,
+        analytics::{IdentityLookupInfo, MessageSource},
+        chains,
         database::helpers::get_names_by_address,
         error::RpcError,
-        json_rpc::{JsonRpcError, JsonRpcResponse},
+        json_rpc::{JsonRpcError, JsonRpcResponse, RequestEnvelope},
+        names::{
+            avatar,
+            sources::{self, IdentitySource},
+            Config as NamesConfig, LOCAL_ATTRIBUTES_RECORDS,
+        },
         state::AppState,
         utils::{crypto, network},
     },
@@ -18,15 +24,20 @@ use {
     core::fmt,
     ethers::{
         abi::Address,
-        providers::{JsonRpcClient, Middleware, Provider, ProviderError},
+        contract::{abigen, ContractError},
+        providers::{ens, JsonRpcClient, Middleware, Provider, ProviderError},
         types::H160,
         utils::to_checksum,
     },
+    futures_util::{future, stream, StreamExt},
     hyper::{body::to_bytes, header::CACHE_CONTROL, HeaderMap, StatusCode},
+    once_cell::sync::Lazy,
     serde::{de::DeserializeOwned, Deserialize, Serialize},
     std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        iter,
         net::SocketAddr,
-        sync::Arc,
+        sync::{Arc, Mutex},
         time::{Duration, SystemTime, UNIX_EPOCH},
     },
     tap::TapFallible,
@@ -37,16 +48,83 @@ use {
 const CACHE_TTL: u64 = 60 * 60 * 24;
 const CACHE_TTL_DELTA: TimeDelta = TimeDelta::seconds(CACHE_TTL as i64);
 const CACHE_TTL_STD: Duration = Duration::from_secs(CACHE_TTL);
+/// Cached identities are refreshed in the background once they are older
+/// than the soft TTL, while still being served from the cache
+const CACHE_SOFT_TTL_DELTA: TimeDelta = TimeDelta::seconds(60 * 60 * 12);
+
+/// Identities without a name are cached for a shorter time
+const NEGATIVE_CACHE_TTL: u64 = 60 * 60;
+const NEGATIVE_CACHE_TTL_DELTA: TimeDelta = TimeDelta::seconds(NEGATIVE_CACHE_TTL as i64);
+const NEGATIVE_CACHE_TTL_STD: Duration = Duration::from_secs(NEGATIVE_CACHE_TTL);
+const NEGATIVE_CACHE_SOFT_TTL_DELTA: TimeDelta = TimeDelta::seconds(60 * 15);
+
+/// Cache keys of the identities being refreshed in the background
+static IDENTITY_REFRESHES: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
 
 const SELF_PROVIDER_ERROR_PREFIX: &str = "SelfProviderError: ";
 const EMPTY_RPC_RESPONSE: &str = "0x";
 pub const ETHEREUM_MAINNET: &str = "eip155:1";
 
+/// ENSIP-19 L2 reverse registrar, deployed to the same address on all of the
+/// chains below
+const L2_REVERSE_REGISTRAR: &str = "0x0000000000D8e504002cC26E3Ec46D81971C1664";
+/// Chains with the ENSIP-19 primary names support
+pub const L2_PRIMARY_NAME_CHAINS: [&str; 5] = [
+    "eip155:10",
+    "eip155:8453",
+    "eip155:42161",
+    "eip155:59144",
+    "eip155:534352",
+];
+
+abigen!(
+    EnsRegistry,
+    r#"[
+        function resolver(bytes32 node) external view returns (address)
+    ]"#,
+);
+
+abigen!(
+    MulticoinAddrResolver,
+    r#"[
+        function addr(bytes32 node, uint256 coinType) external view returns (bytes)
+    ]"#,
+);
+
+abigen!(
+    ReverseResolver,
+    r#"[
+        function name(bytes32 node) external view returns (string)
+    ]"#,
+);
+
+abigen!(
+    L2ReverseRegistrar,
+    r#"[
+        function nameForAddr(address addr) external view returns (string)
+    ]"#,
+);
+
 /// Error codes that reflect an `execution reverted` and should proceed with Ok() during
 /// the identity avatar lookup because of an absence of the ERC-721 contract address or
 /// token ID in the ENS avatar record.
 const JSON_RPC_OK_ERROR_CODES: [&str; 3] = ["-32000", "-32015", "3"];
 
+/// ENS text records that can be selected with the `records` parameter
+pub const SUPPORTED_TEXT_RECORDS: [&str; 6] = [
+    "description",
+    "url",
+    "com.twitter",
+    "com.github",
+    "email",
+    "header",
+];
+
+/// Maximum number of the addresses in the batch lookup request
+const BATCH_MAX_ADDRESSES: usize = 100;
+/// Maximum number of the concurrent lookups of the batch cache misses
+const BATCH_LOOKUP_CONCURRENCY: usize = 10;
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityResponse {
@@ -56,6 +134,50 @@ pub struct IdentityResponse {
     // getting the current TTL requires a second command & round trip to Redis
     // Optional to support DB migration, can switch to required in the future
     resolved_at: Option<DateTime<Utc>>,
+    /// Chain of the reverse record the name was resolved from
+    chain_id: Option<String>,
+    /// Reverse resolver or registrar contract address that produced the name
+    resolver: Option<String>,
+    /// Selected text records, only resolved for the API version 2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    records: Option<BTreeMap<String, String>>,
+}
+
+impl IdentityResponse {
+    pub fn avatar(&self) -> Option<&str> {
+        self.avatar.as_deref()
+    }
+
+    fn cache_ttl(&self) -> TimeDelta {
+        if self.name.is_some() {
+            CACHE_TTL_DELTA
+        } else {
+            NEGATIVE_CACHE_TTL_DELTA
+        }
+    }
+
+    /// Whether the cached identity has passed the soft TTL and should be
+    /// refreshed
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        let soft_ttl = if self.name.is_some() {
+            CACHE_SOFT_TTL_DELTA
+        } else {
+            NEGATIVE_CACHE_SOFT_TTL_DELTA
+        };
+        match self.resolved_at {
+            Some(resolved_at) => now - resolved_at >= soft_ttl,
+            None => true,
+        }
+    }
+
+    /// Keeps only the requested text records, all of the supported records
+    /// are resolved and cached
+    fn select_records(mut self, selected: &[&str]) -> Self {
+        if let Some(records) = self.records.as_mut() {
+            records.retain(|key, _| selected.contains(&key.as_str()));
+        }
+        self
+    }
 }
 
 pub async fn handler(
@@ -147,7 +269,7 @@ async fn handler_internal(
 
     let now = Utc::now();
     let ttl_secs = res.resolved_at
-        .map(|resolved_at| ttl_from_resolved_at(resolved_at, now))
+        .map(|resolved_at| ttl_from_resolved_at(resolved_at, res.cache_ttl(), now))
         // Only happens during initial rollout when `resolved_at` is None, so we don't need to go overboard on the cache
         .unwrap_or(TimeDelta::hours(1))
         .num_seconds();
@@ -156,11 +278,158 @@ async fn handler_internal(
     Ok(([(CACHE_CONTROL, cache_control)], Json(res)).into_response())
 }
 
-fn ttl_from_resolved_at(resolved_at: DateTime<Utc>, now: DateTime<Utc>) -> TimeDelta {
-    let expires = resolved_at + CACHE_TTL_DELTA;
+fn ttl_from_resolved_at(
+    resolved_at: DateTime<Utc>,
+    cache_ttl: TimeDelta,
+    now: DateTime<Utc>,
+) -> TimeDelta {
+    let expires = resolved_at + cache_ttl;
     (expires - now).max(TimeDelta::zero())
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct IdentityBatchRequest {
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentityBatchResponse {
+    /// Lookup results in the order of the requested addresses
+    pub identities: Vec<IdentityBatchItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentityBatchItem {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<IdentityResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IdentityBatchItem {
+    fn new(address: String, result: Result<IdentityResponse, RpcError>) -> Self {
+        match result {
+            Ok(identity) => Self {
+                address,
+                identity: Some(identity),
+                error: None,
+            },
+            Err(e) => Self {
+                address,
+                identity: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+pub async fn batch_handler(
+    state: State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    query: Query<IdentityQueryParams>,
+    headers: HeaderMap,
+    Json(request): Json<IdentityBatchRequest>,
+) -> Result<Response, RpcError> {
+    batch_handler_internal(state, connect_info, query, headers, request)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("identity_batch"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn batch_handler_internal(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    Query(query): Query<IdentityQueryParams>,
+    headers: HeaderMap,
+    request: IdentityBatchRequest,
+) -> Result<Response, RpcError> {
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    if request.addresses.is_empty() || request.addresses.len() > BATCH_MAX_ADDRESSES {
+        return Err(RpcError::InvalidParameter(format!(
+            "The number of addresses must be between 1 and {BATCH_MAX_ADDRESSES}"
+        )));
+    }
+    let options = LookupOptions::new(&state, &query)?;
+    state
+        .metrics
+        .add_identity_batch_lookup(request.addresses.len());
+
+    // Serving the cache hits first, so only the misses are queued to the
+    // bounded concurrent resolution
+    let cached = future::join_all(request.addresses.iter().map(|address| {
+        let state = state.clone();
+        let options = &options;
+        async move {
+            let address = address
+                .parse::<Address>()
+                .map_err(|_| RpcError::InvalidAddress)?;
+            let cached = if options.enable_cache {
+                get_cached_identity(&state, address, options).await?
+            } else {
+                None
+            };
+            Ok::<_, RpcError>((address, cached))
+        }
+    }))
+    .await;
+
+    let identities = stream::iter(request.addresses.into_iter().zip(cached))
+        .map(|(requested_address, cached)| {
+            let state = state.clone();
+            let project_id = query.project_id.clone();
+            let options = &options;
+            let headers = headers.clone();
+            async move {
+                let result = match cached {
+                    Ok((address, Some(identity))) => {
+                        state.metrics.add_identity_batch_item("cache_hit");
+                        if identity.is_stale(Utc::now()) {
+                            spawn_identity_refresh(
+                                address,
+                                &identity,
+                                state.clone(),
+                                connect_info,
+                                project_id,
+                                options.clone(),
+                                headers,
+                            );
+                        }
+                        Ok(identity)
+                    }
+                    Ok((address, None)) => {
+                        state.metrics.add_identity_batch_item("cache_miss");
+                        resolve_identity(
+                            address,
+                            state.clone(),
+                            connect_info,
+                            project_id,
+                            options,
+                            headers,
+                            None,
+                        )
+                        .await
+                        .map(|(_, identity)| identity)
+                    }
+                    Err(e) => Err(e),
+                };
+                if result.is_err() {
+                    state.metrics.add_identity_batch_item("error");
+                }
+                let result = result.map(|identity| identity.select_records(&options.records));
+                IdentityBatchItem::new(requested_address, result)
+            }
+        })
+        .buffered(BATCH_LOOKUP_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    Ok(Json(IdentityBatchResponse { identities }).into_response())
+}
+
 #[derive(Serialize, Clone)]
 pub enum IdentityLookupSource {
     /// Redis cached results
@@ -169,6 +438,10 @@ pub enum IdentityLookupSource {
     Rpc,
     /// Local name resolver
     Local,
+    /// Farcaster usernames
+    Farcaster,
+    /// Lens handles
+    Lens,
 }
 
 impl IdentityLookupSource {
@@ -177,6 +450,19 @@ impl IdentityLookupSource {
             Self::Cache => "cache",
             Self::Rpc => "rpc",
             Self::Local => "local",
+            Self::Farcaster => "farcaster",
+            Self::Lens => "lens",
+        }
+    }
+}
+
+impl From<IdentitySource> for IdentityLookupSource {
+    fn from(source: IdentitySource) -> Self {
+        match source {
+            IdentitySource::Ens => Self::Rpc,
+            IdentitySource::Local => Self::Local,
+            IdentitySource::Farcaster => Self::Farcaster,
+            IdentitySource::Lens => Self::Lens,
         }
     }
 }
@@ -192,97 +478,422 @@ pub struct IdentityQueryParams {
     pub client_id: Option<String>,
     /// Request sender address for analytics
     pub sender: Option<String>,
+    /// Optional chain to resolve the ENSIP-19 primary name on, falls back to
+    /// the mainnet primary name. Defaults to the mainnet
+    pub chain_id: Option<String>,
+    /// Comma-separated list of the text records to resolve, requires the API
+    /// version 2
+    pub records: Option<String>,
+    /// Optional version parameter, the version `2` responds with the text
+    /// records
+    pub api_version: Option<usize>,
+}
+
+impl IdentityQueryParams {
+    pub fn lookup_chain_id(&self) -> String {
+        self.chain_id
+            .as_deref()
+            .map(chains::resolve_chain_id)
+            .unwrap_or_else(|| ETHEREUM_MAINNET.to_owned())
+    }
+
+    /// Requested text records, the version 2 responds with all of the
+    /// supported records when no records are selected
+    pub fn text_records(&self) -> Result<Vec<&'static str>, RpcError> {
+        if self.api_version != Some(2) {
+            if self.records.is_some() {
+                return Err(RpcError::InvalidParameter(
+                    "The `records` parameter requires the `apiVersion=2`".into(),
+                ));
+            }
+            return Ok(vec![]);
+        }
+        let Some(records) = &self.records else {
+            return Ok(SUPPORTED_TEXT_RECORDS.to_vec());
+        };
+        records
+            .split(',')
+            .map(str::trim)
+            .filter(|record| !record.is_empty())
+            .map(|record| {
+                SUPPORTED_TEXT_RECORDS
+                    .iter()
+                    .find(|supported| **supported == record)
+                    .copied()
+                    .ok_or_else(|| {
+                        RpcError::InvalidParameter(format!("Unsupported text record: {record}"))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Identity lookup options shared by the single and the batch lookups
+#[derive(Debug, Clone)]
+struct LookupOptions {
+    chain_id: String,
+    enable_cache: bool,
+    /// Selected text records, the records are not resolved when empty
+    records: Vec<&'static str>,
+    /// Identity sources in the priority order of the project
+    sources: Vec<IdentitySource>,
+    /// Whether the project sources priority differs from the default
+    custom_sources: bool,
+}
+
+impl LookupOptions {
+    fn new(state: &AppState, query: &IdentityQueryParams) -> Result<Self, RpcError> {
+        let sources = state.identity_sources.priority(&query.project_id);
+        Ok(Self {
+            chain_id: primary_name_chain_id(query)?,
+            enable_cache: is_cache_enabled(state, query)?,
+            records: query.text_records()?,
+            sources: sources.to_vec(),
+            custom_sources: sources != state.identity_sources.default_priority(),
+        })
+    }
+
+    fn with_records(&self) -> bool {
+        !self.records.is_empty()
+    }
+
+    fn cache_key(&self, address: H160) -> String {
+        identity_cache_key(
+            address,
+            &self.chain_id,
+            self.with_records(),
+            self.custom_sources.then_some(self.sources.as_slice()),
+        )
+    }
 }
 
 #[tracing::instrument(skip_all, level = "debug")]
-async fn lookup_identity(
+pub async fn lookup_identity(
     address: H160,
     State(state): State<Arc<AppState>>,
     ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
     Query(query): Query<IdentityQueryParams>,
     headers: HeaderMap,
 ) -> Result<(IdentityLookupSource, IdentityResponse), RpcError> {
-    let address_with_checksum = to_checksum(&address, None);
-    let cache_record_key = format!("{}-v1", address_with_checksum);
-
-    // Check if we should enable cache control for allow listed Project ID
-    // The cache is enabled by default
-    let enable_cache = if let Some(use_cache) = query.use_cache {
-        if let Some(ref testing_project_id) = state.config.server.testing_project_id {
-            if crypto::constant_time_eq(testing_project_id, &query.project_id) {
-                use_cache
-            } else {
-                return Err(RpcError::InvalidParameter(format!(
-                    "The project ID {} is not allowed to use `use_cache` parameter",
-                    query.project_id
-                )));
+    let options = LookupOptions::new(&state, &query)?;
+    if options.enable_cache {
+        if let Some(response) = get_cached_identity(&state, address, &options).await? {
+            if response.is_stale(Utc::now()) {
+                spawn_identity_refresh(
+                    address,
+                    &response,
+                    state.clone(),
+                    connect_info,
+                    query.project_id.clone(),
+                    options.clone(),
+                    headers.clone(),
+                );
             }
-        } else {
-            return Err(RpcError::InvalidParameter(
-                "Use of `use_cache` parameter is disabled".into(),
+            return Ok((
+                IdentityLookupSource::Cache,
+                response.select_records(&options.records),
             ));
         }
-    } else {
-        true
-    };
-
-    if enable_cache {
-        if let Some(cache) = &state.identity_cache {
-            debug!("Checking cache for identity");
-            let cache_start = SystemTime::now();
-            let value = cache.get(&cache_record_key).await?;
-            state.metrics.add_identity_lookup_cache_latency(cache_start);
-            if let Some(response) = value {
-                return Ok((IdentityLookupSource::Cache, response));
-            }
-        }
     }
 
-    // Lookup for the name in ENS first
-    let mut resolved_by = IdentityLookupSource::Rpc;
-    let mut res = lookup_identity_rpc(
+    let (source, response) = resolve_identity(
         address,
-        state.clone(),
+        state,
         connect_info,
         query.project_id,
+        &options,
         headers,
+        None,
     )
     .await?;
+    Ok((source, response.select_records(&options.records)))
+}
 
-    // Lookup for the name in local name resolver if no ENS found
-    if res.name.is_none() {
-        match get_names_by_address(address_with_checksum.clone(), &state.postgres).await {
-            Ok(names) => {
-                // Our API v1 support only one name per address, using the first name
-                if let Some(name_first) = names.first() {
-                    let avatar = name_first
-                        .attributes
-                        .as_ref()
-                        .and_then(|attributes| attributes.get("avatar"))
-                        .map(|v| v.to_string());
-
-                    resolved_by = IdentityLookupSource::Local;
-                    res.name = Some(name_first.name.clone());
-                    res.avatar = avatar;
-                }
+/// Chain of the primary name lookup, only the mainnet and the chains with the
+/// ENSIP-19 primary names are supported
+fn primary_name_chain_id(query: &IdentityQueryParams) -> Result<String, RpcError> {
+    let chain_id = query.lookup_chain_id();
+    if chain_id != ETHEREUM_MAINNET && !L2_PRIMARY_NAME_CHAINS.contains(&chain_id.as_str()) {
+        return Err(RpcError::UnsupportedChain(chain_id));
+    }
+    Ok(chain_id)
+}
+
+/// Check if we should enable cache control for allow listed Project ID.
+/// The cache is enabled by default
+fn is_cache_enabled(state: &AppState, query: &IdentityQueryParams) -> Result<bool, RpcError> {
+    let Some(use_cache) = query.use_cache else {
+        return Ok(true);
+    };
+    if let Some(ref testing_project_id) = state.config.server.testing_project_id {
+        if crypto::constant_time_eq(testing_project_id, &query.project_id) {
+            Ok(use_cache)
+        } else {
+            Err(RpcError::InvalidParameter(format!(
+                "The project ID {} is not allowed to use `use_cache` parameter",
+                query.project_id
+            )))
+        }
+    } else {
+        Err(RpcError::InvalidParameter(
+            "Use of `use_cache` parameter is disabled".into(),
+        ))
+    }
+}
+
+/// Identities with the text records and the identities resolved with the
+/// custom sources priority are cached separately, the mainnet identities keep
+/// the keys without the chain id
+fn identity_cache_key(
+    address: H160,
+    chain_id: &str,
+    with_records: bool,
+    custom_sources: Option<&[IdentitySource]>,
+) -> String {
+    let address = to_checksum(&address, None);
+    let records = if with_records { "-records" } else { "" };
+    let sources = custom_sources
+        .map(|sources| format!("-{}", sources::priority_key(sources)))
+        .unwrap_or_default();
+    let chain_id = if chain_id == ETHEREUM_MAINNET {
+        String::new()
+    } else {
+        format!("-{chain_id}")
+    };
+    format!("{address}{chain_id}{records}{sources}-v1")
+}
+
+/// Removes the cached identities of the address for all of the lookup chains
+/// and the sources priority orders
+pub async fn invalidate_identity_cache(state: &AppState, address: H160) -> Result<(), RpcError> {
+    let Some(cache) = &state.identity_cache else {
+        return Ok(());
+    };
+    let custom_sources = state.identity_sources.project_priorities().map(Some);
+    let sources = iter::once(None).chain(custom_sources).collect::<Vec<_>>();
+    let mut keys = Vec::new();
+    for chain_id in iter::once(ETHEREUM_MAINNET).chain(L2_PRIMARY_NAME_CHAINS) {
+        for with_records in [false, true] {
+            for custom_sources in &sources {
+                keys.push(identity_cache_key(
+                    address,
+                    chain_id,
+                    with_records,
+                    *custom_sources,
+                ));
+            }
+        }
+    }
+    // Keys are deleted with a single command instead of a round trip per key
+    cache.del_many(&keys).await?;
+    Ok(())
+}
+
+/// Invalidates the cached identities of the name addresses after the name
+/// attributes or addresses are changed, non-EVM addresses are skipped
+pub async fn invalidate_name_identities<'a>(
+    state: &AppState,
+    addresses: impl IntoIterator<Item = &'a str>,
+) {
+    for address in addresses {
+        let Ok(address) = address.parse::<H160>() else {
+            continue;
+        };
+        if let Err(e) = invalidate_identity_cache(state, address).await {
+            warn!("Failed to invalidate the identity cache of {address:#x}: {e}");
+        }
+    }
+}
+
+/// Refreshes the stale cached identity in the background, the refresh is
+/// skipped if the same identity is already being refreshed
+fn spawn_identity_refresh(
+    address: H160,
+    stale: &IdentityResponse,
+    state: Arc<AppState>,
+    connect_info: SocketAddr,
+    project_id: String,
+    options: LookupOptions,
+    headers: HeaderMap,
+) {
+    let Some(refresh) = IdentityRefresh::start(options.cache_key(address)) else {
+        return;
+    };
+    let stale = stale.clone();
+
+    tokio::spawn(async move {
+        let cache_key = &refresh.cache_key;
+        debug!("Refreshing the stale identity (cache_key:{cache_key})");
+        let result = resolve_identity(
+            address,
+            state.clone(),
+            connect_info,
+            project_id,
+            &options,
+            headers,
+            Some(&stale),
+        )
+        .await;
+        if let Err(e) = &result {
+            warn!("Failed to refresh the identity (cache_key:{cache_key}): {e}");
+        }
+        state.metrics.add_identity_cache_refresh(result.is_ok());
+    });
+}
+
+/// Background refresh of the cached identity, the cache key is released when
+/// the refresh is finished, failed or panicked
+struct IdentityRefresh {
+    cache_key: String,
+}
+
+impl IdentityRefresh {
+    /// Starts the refresh unless the identity is already being refreshed
+    fn start(cache_key: String) -> Option<Self> {
+        let is_started = IDENTITY_REFRESHES
+            .lock()
+            .expect("Identity refreshes lock should not be poisoned")
+            .insert(cache_key.clone());
+        is_started.then(|| Self { cache_key })
+    }
+}
+
+impl Drop for IdentityRefresh {
+    fn drop(&mut self) {
+        // Not panicking on the poisoned lock, since the guard can be dropped
+        // while the refresh is already panicking
+        if let Ok(mut refreshes) = IDENTITY_REFRESHES.lock() {
+            refreshes.remove(&self.cache_key);
+        }
+    }
+}
+
+async fn get_cached_identity(
+    state: &AppState,
+    address: H160,
+    options: &LookupOptions,
+) -> Result<Option<IdentityResponse>, RpcError> {
+    let Some(cache) = &state.identity_cache else {
+        return Ok(None);
+    };
+    debug!("Checking cache for identity");
+    let cache_start = SystemTime::now();
+    let value = cache.get(&options.cache_key(address)).await?;
+    state.metrics.add_identity_lookup_cache_latency(cache_start);
+    Ok(value)
+}
+
+/// Resolves the identity bypassing the cache read, the result is cached when
+/// the cache is enabled. The refreshed identity replaces the stale cached
+/// identity only if it is still cached and wasn't invalidated meanwhile.
+#[tracing::instrument(skip_all, level = "debug")]
+async fn resolve_identity(
+    address: H160,
+    state: Arc<AppState>,
+    connect_info: SocketAddr,
+    project_id: String,
+    options: &LookupOptions,
+    headers: HeaderMap,
+    refreshed: Option<&IdentityResponse>,
+) -> Result<(IdentityLookupSource, IdentityResponse), RpcError> {
+    let cache_record_key = options.cache_key(address);
+
+    // Sources are consulted in the priority order until the name is found
+    let mut resolved = None;
+    for source in &options.sources {
+        let lookup_start = SystemTime::now();
+        let result = match source {
+            IdentitySource::Ens => lookup_identity_rpc(
+                address,
+                state.clone(),
+                connect_info,
+                project_id.clone(),
+                options,
+                headers.clone(),
+            )
+            .await
+            .map(|res| res.name.is_some().then_some(res)),
+            IdentitySource::Local => lookup_identity_local(&state, address, options).await,
+            IdentitySource::Farcaster | IdentitySource::Lens => {
+                lookup_identity_http(&state, *source, address, options).await
             }
-            Err(e) => {
-                error!("Error on local name resolution: {}", e);
-                return Err(RpcError::InternalNameResolverError);
+        };
+        let result_label = match &result {
+            Ok(Some(_)) => "found",
+            Ok(None) => "not_found",
+            Err(_) => "error",
+        };
+        state.metrics.add_identity_source_lookup(
+            source.as_str(),
+            result_label,
+            lookup_start.elapsed().unwrap_or_default(),
+        );
+
+        let res = match result {
+            Ok(res) => res,
+            // Third-party sources are not failing the lookup
+            Err(e) if source.is_http() => {
+                warn!("Failed to lookup the identity in {}: {e}", source.as_str());
+                None
             }
+            Err(e) => return Err(e),
+        };
+        if let Some(res) = res {
+            resolved = Some((IdentityLookupSource::from(*source), res));
+            break;
         }
     }
+    let (resolved_by, res) = resolved.unwrap_or_else(|| {
+        let resolved_by = options
+            .sources
+            .first()
+            .map_or(IdentityLookupSource::Rpc, |source| (*source).into());
+        let res = IdentityResponse {
+            name: None,
+            avatar: None,
+            resolved_at: Some(Utc::now()),
+            chain_id: None,
+            resolver: None,
+            records: options.with_records().then(BTreeMap::new),
+        };
+        (resolved_by, res)
+    });
 
-    if enable_cache {
+    if options.enable_cache {
         if let Some(cache) = &state.identity_cache {
             debug!("Saving to cache");
             let cache = cache.clone();
             let res = res.clone();
+            let cache_ttl = if res.name.is_some() {
+                CACHE_TTL_STD
+            } else {
+                NEGATIVE_CACHE_TTL_STD
+            };
+            let refreshed_resolved_at = refreshed.map(|refreshed| refreshed.resolved_at);
             // Do not block on cache write.
             tokio::spawn(async move {
+                if let Some(refreshed_resolved_at) = refreshed_resolved_at {
+                    match cache.get(&cache_record_key).await {
+                        Ok(Some(cached)) if cached.resolved_at == refreshed_resolved_at => {}
+                        Ok(_) => {
+                            debug!(
+                                "Skipping the refreshed identity of the invalidated or \
+                                 updated cache entry (cache_key:{cache_record_key})"
+                            );
+                            return;
+                        }
+                        Err(err) => {
+                            warn!(
+                                "failed to check the refreshed identity cache entry \
+                                 (cache_key:{cache_record_key}): {err:?}"
+                            );
+                            return;
+                        }
+                    }
+                }
                 let cache_start = SystemTime::now();
                 cache
-                    .set(&cache_record_key, &res, Some(CACHE_TTL_STD))
+                    .set(&cache_record_key, &res, Some(cache_ttl))
                     .await
                     .tap_err(|err| {
                         warn!(
@@ -306,25 +917,42 @@ async fn lookup_identity_rpc(
     state: Arc<AppState>,
     connect_info: SocketAddr,
     project_id: String,
+    options: &LookupOptions,
     headers: HeaderMap,
 ) -> Result<IdentityResponse, RpcError> {
-    let provider = Provider::new(SelfProvider {
-        state: state.clone(),
-        connect_info,
-        query: RpcQueryParams {
-            project_id,
-            // ENS registry contract is only deployed on mainnet
-            chain_id: ETHEREUM_MAINNET.to_owned(),
-            provider_id: None,
-            source: Some(crate::analytics::MessageSource::Identity),
-        },
-        headers,
-    });
+    let chain_id = options.chain_id.as_str();
+    let self_provider = |chain_id: &str| {
+        self_provider(
+            state.clone(),
+            connect_info,
+            project_id.clone(),
+            chain_id,
+            MessageSource::Identity,
+            headers.clone(),
+        )
+    };
+    // ENS registry contract is only deployed on mainnet
+    let provider = self_provider(ETHEREUM_MAINNET);
+    let l2_provider = (chain_id != ETHEREUM_MAINNET).then(|| (chain_id, self_provider(chain_id)));
 
-    let name = {
+    let primary_name = {
         debug!("Beginning name lookup");
         let name_lookup_start = SystemTime::now();
-        let name_result = lookup_name(&provider, address).await;
+        let name_result = match &l2_provider {
+            Some((chain_id, l2_provider)) => {
+                // Mainnet primary name is used when the L2 name can't be
+                // looked up as well
+                match lookup_l2_name(&provider, chain_id, l2_provider, address).await {
+                    Ok(Some(name)) => Ok(Some(name)),
+                    Ok(None) => lookup_name(&provider, address).await,
+                    Err(e) => {
+                        debug!("Failed to look up the {chain_id} primary name: {e}");
+                        lookup_name(&provider, address).await
+                    }
+                }
+            }
+            None => lookup_name(&provider, address).await,
+        };
 
         state.metrics.add_identity_lookup_name();
         let name = name_result?;
@@ -335,11 +963,19 @@ async fn lookup_identity_rpc(
 
         name
     };
+    let (name, chain_id, resolver) = match primary_name {
+        Some(primary_name) => (
+            Some(primary_name.name),
+            Some(primary_name.chain_id),
+            Some(to_checksum(&primary_name.resolver, None)),
+        ),
+        None => (None, None, None),
+    };
 
     let avatar = if let Some(name) = &name {
         debug!("Beginning avatar lookup");
         let avatar_lookup_start = SystemTime::now();
-        let avatar_result = lookup_avatar(&provider, name).await;
+        let avatar_result = lookup_avatar(&provider, name, &state.config.names).await;
 
         state.metrics.add_identity_lookup_avatar();
         let avatar = avatar_result?;
@@ -353,13 +989,129 @@ async fn lookup_identity_rpc(
         None
     };
 
+    let records = if !options.with_records() {
+        None
+    } else if let Some(name) = &name {
+        Some(lookup_text_records(&provider, name).await?)
+    } else {
+        Some(BTreeMap::new())
+    };
+
     Ok(IdentityResponse {
         name,
         avatar,
         resolved_at: Some(Utc::now()),
+        chain_id,
+        resolver,
+        records,
     })
 }
 
+#[tracing::instrument(skip(state, options), level = "debug")]
+async fn lookup_identity_local(
+    state: &AppState,
+    address: H160,
+    options: &LookupOptions,
+) -> Result<Option<IdentityResponse>, RpcError> {
+    let names = get_names_by_address(to_checksum(&address, None), &state.postgres)
+        .await
+        .map_err(|e| {
+            error!("Error on local name resolution: {}", e);
+            RpcError::InternalNameResolverError
+        })?;
+    // Our API v1 support only one name per address, using the first name
+    let Some(name_first) = names.first() else {
+        return Ok(None);
+    };
+
+    let avatar = name_first
+        .attributes
+        .as_ref()
+        .and_then(|attributes| attributes.get("avatar"))
+        .and_then(|v| avatar::normalize_avatar_uri(v, &state.config.names));
+    let records = options.with_records().then(|| {
+        name_first
+            .attributes
+            .as_ref()
+            .map(|attributes| local_attributes_records(attributes))
+            .unwrap_or_default()
+    });
+    Ok(Some(IdentityResponse {
+        name: Some(name_first.name.clone()),
+        avatar,
+        resolved_at: Some(Utc::now()),
+        chain_id: None,
+        resolver: None,
+        records,
+    }))
+}
+
+/// Identity from the social graph source, the text records are not resolved
+#[tracing::instrument(skip(state, options), level = "debug")]
+async fn lookup_identity_http(
+    state: &AppState,
+    source: IdentitySource,
+    address: H160,
+    options: &LookupOptions,
+) -> Result<Option<IdentityResponse>, RpcError> {
+    let identity = state
+        .identity_sources
+        .lookup_http(&state.http_client, source, address)
+        .await
+        .map_err(|e| RpcError::IdentityLookup(e.to_string()))?;
+    Ok(identity.map(|identity| IdentityResponse {
+        name: identity.name,
+        avatar: identity
+            .avatar
+            .and_then(|avatar| avatar::normalize_avatar_uri(&avatar, &state.config.names)),
+        resolved_at: Some(Utc::now()),
+        chain_id: None,
+        resolver: None,
+        records: options.with_records().then(BTreeMap::new),
+    }))
+}
+
+/// Supported text records of the local name attributes
+fn local_attributes_records(attributes: &HashMap<String, String>) -> BTreeMap<String, String> {
+    attributes
+        .iter()
+        .filter_map(|(attribute, value)| {
+            let record = LOCAL_ATTRIBUTES_RECORDS
+                .iter()
+                .find(|(local_attribute, _)| local_attribute == attribute)
+                .map_or(attribute.as_str(), |(_, record)| *record);
+            SUPPORTED_TEXT_RECORDS
+                .contains(&record)
+                .then(|| (record.to_owned(), value.clone()))
+        })
+        .collect()
+}
+
+#[tracing::instrument(skip(provider), level = "debug")]
+async fn lookup_text_records(
+    provider: &Arc<Provider<SelfProvider>>,
+    name: &str,
+) -> Result<BTreeMap<String, String>, RpcError> {
+    let values = future::join_all(
+        SUPPORTED_TEXT_RECORDS
+            .iter()
+            .map(|record| provider.resolve_field(name, record)),
+    )
+    .await;
+
+    let mut records = BTreeMap::new();
+    for (record, value) in SUPPORTED_TEXT_RECORDS.iter().zip(values) {
+        match value {
+            Ok(value) if !value.is_empty() => {
+                records.insert(record.to_string(), value);
+            }
+            Ok(_) => {}
+            Err(error) => handle_rpc_error(error)?,
+        }
+    }
+    Ok(records)
+}
+
 #[tracing::instrument(level = "debug")]
 pub fn handle_rpc_error(error: ProviderError) -> Result<(), RpcError> {
     match error {
@@ -406,45 +1158,184 @@ pub fn handle_rpc_error(error: ProviderError) -> Result<(), RpcError> {
     }
 }
 
+/// Contract call errors are handled as the JSON-RPC provider errors
+pub fn handle_contract_error(error: ContractError<Provider<SelfProvider>>) -> Result<(), RpcError> {
+    match error {
+        ContractError::MiddlewareError { e } | ContractError::ProviderError { e } => {
+            handle_rpc_error(e)
+        }
+        _ => {
+            debug!("Contract error while looking up identity: {:?}", error);
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PrimaryName {
+    name: String,
+    chain_id: String,
+    resolver: Address,
+}
+
+/// Mainnet primary name from the reverse resolver, the name is verified by
+/// the forward resolution of the address
 #[tracing::instrument(skip_all, level = "debug")]
 async fn lookup_name(
-    provider: &Provider<SelfProvider>,
+    provider: &Arc<Provider<SelfProvider>>,
     address: Address,
-) -> Result<Option<String>, RpcError> {
-    provider.lookup_address(address).await.map_or_else(
-        |error| match handle_rpc_error(error) {
-            Ok(_) => Ok(None),
-            Err(e) => Err(e),
-        },
-        |name| Ok(Some(name)),
-    )
+) -> Result<Option<PrimaryName>, RpcError> {
+    let reverse_node = ens::namehash(&ens::reverse_address(address));
+    let registry = EnsRegistry::new(ens::ENS_ADDRESS, provider.clone());
+    let resolver = match registry.resolver(reverse_node.0).call().await {
+        Ok(resolver) if !resolver.is_zero() => resolver,
+        Ok(_) => return Ok(None),
+        Err(error) => {
+            handle_contract_error(error)?;
+            return Ok(None);
+        }
+    };
+    let name = match ReverseResolver::new(resolver, provider.clone())
+        .name(reverse_node.0)
+        .call()
+        .await
+    {
+        Ok(name) if !name.is_empty() => name,
+        Ok(_) => return Ok(None),
+        Err(error) => {
+            handle_contract_error(error)?;
+            return Ok(None);
+        }
+    };
+
+    let coin_type = crypto::convert_evm_chain_id_to_coin_type(1);
+    if !is_name_resolving_to(provider, &name, coin_type, address).await? {
+        return Ok(None);
+    }
+    Ok(Some(PrimaryName {
+        name,
+        chain_id: ETHEREUM_MAINNET.to_owned(),
+        resolver,
+    }))
 }
 
-#[tracing::instrument(skip(provider), level = "debug")]
+/// ENSIP-19 primary name lookup from the L2 reverse registrar, the name is
+/// verified by the forward resolution of the address for the chain coin type
+/// or the default EVM coin type on mainnet
+#[tracing::instrument(skip(provider, l2_provider), level = "debug")]
+async fn lookup_l2_name(
+    provider: &Arc<Provider<SelfProvider>>,
+    chain_id: &str,
+    l2_provider: &Arc<Provider<SelfProvider>>,
+    address: Address,
+) -> Result<Option<PrimaryName>, RpcError> {
+    let evm_chain_id = crypto::disassemble_caip2(chain_id)
+        .ok()
+        .and_then(|(_, reference)| reference.parse::<u32>().ok())
+        .ok_or_else(|| RpcError::InvalidChainIdFormat(chain_id.to_owned()))?;
+    let registrar_address = L2_REVERSE_REGISTRAR
+        .parse::<Address>()
+        .expect("Invalid L2 reverse registrar address");
+
+    let registrar = L2ReverseRegistrar::new(registrar_address, l2_provider.clone());
+    let name = match registrar.name_for_addr(address).call().await {
+        Ok(name) if !name.is_empty() => name,
+        Ok(_) => return Ok(None),
+        Err(error) => {
+            handle_contract_error(error)?;
+            return Ok(None);
+        }
+    };
+
+    let coin_type = crypto::convert_evm_chain_id_to_coin_type(evm_chain_id);
+    if !is_name_resolving_to(provider, &name, coin_type, address).await? {
+        return Ok(None);
+    }
+    Ok(Some(PrimaryName {
+        name,
+        chain_id: chain_id.to_owned(),
+        resolver: registrar_address,
+    }))
+}
+
+/// Forward verification of the primary name, the names of the wildcard
+/// resolvers and the offchain names are resolved as well
+async fn is_name_resolving_to(
+    provider: &Arc<Provider<SelfProvider>>,
+    name: &str,
+    coin_type: u32,
+    address: Address,
+) -> Result<bool, RpcError> {
+    let forward_address = resolve_ens_address(provider, name, coin_type).await?;
+    if forward_address != Some(address) {
+        debug!("Primary name {name} is not resolving to {address:#x}");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Avatar record normalized to the URL that clients can render
+#[tracing::instrument(skip(provider, names_config), level = "debug")]
 async fn lookup_avatar(
-    provider: &Provider<SelfProvider>,
+    provider: &Arc<Provider<SelfProvider>>,
     name: &str,
+    names_config: &NamesConfig,
 ) -> Result<Option<String>, RpcError> {
-    provider
-        .resolve_avatar(name)
-        .await
-        .map(|url| url.to_string())
-        .map_or_else(
-            |error| match handle_rpc_error(error) {
-                Ok(_) => Ok(None),
-                Err(e) => Err(e),
-            },
-            |avatar| Ok(Some(avatar)),
-        )
+    let record = match provider.resolve_field(name, "avatar").await {
+        Ok(record) if !record.is_empty() => record,
+        Ok(_) => return Ok(None),
+        Err(error) => {
+            handle_rpc_error(error)?;
+            return Ok(None);
+        }
+    };
+
+    // NFT avatars are resolved to the image of the token metadata verifying
+    // the token ownership
+    let avatar = if record.starts_with("eip155:") {
+        match provider.resolve_avatar(name).await {
+            Ok(url) => url.to_string(),
+            Err(error) => {
+                handle_rpc_error(error)?;
+                return Ok(None);
+            }
+        }
+    } else {
+        record
+    };
+    Ok(avatar::normalize_avatar_uri(&avatar, names_config))
 }
 
-struct SelfProvider {
+pub struct SelfProvider {
     state: Arc<AppState>,
     connect_info: SocketAddr,
     query: RpcQueryParams,
     headers: HeaderMap,
 }
 
+/// Provider of the chain that is sending the JSON-RPC calls through the proxy
+/// itself
+pub fn self_provider(
+    state: Arc<AppState>,
+    connect_info: SocketAddr,
+    project_id: String,
+    chain_id: &str,
+    source: MessageSource,
+    headers: HeaderMap,
+) -> Arc<Provider<SelfProvider>> {
+    Arc::new(Provider::new(SelfProvider {
+        state,
+        connect_info,
+        query: RpcQueryParams {
+            project_id,
+            chain_id: chain_id.to_owned(),
+            provider_id: None,
+            source: Some(source),
+        },
+        headers,
+    }))
+}
+
 impl fmt::Debug for SelfProvider {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SelfProvider").finish()
@@ -516,14 +1407,16 @@ impl JsonRpcClient for SelfProvider {
             self.connect_info,
             self.query.clone(),
             self.headers.clone(),
-            serde_json::to_vec(&crypto::JsonRpcRequest {
-                id,
-                jsonrpc: crypto::JSON_RPC_VERSION.clone(),
-                method: method.to_owned().into(),
-                params,
-            })
-            .expect("Should be able to serialize a JsonRpcRequest")
-            .into(),
+            RequestEnvelope::parse(
+                serde_json::to_vec(&crypto::JsonRpcRequest {
+                    id,
+                    jsonrpc: crypto::JSON_RPC_VERSION.clone(),
+                    method: method.to_owned().into(),
+                    params,
+                })
+                .expect("Should be able to serialize a JsonRpcRequest")
+                .into(),
+            ),
         )
         .await
         .map_err(SelfProviderError::RpcError)?;
@@ -576,14 +1469,17 @@ mod tests {
     #[test]
     fn full_ttl_when_resolved_now() {
         let now = Utc::now();
-        assert_eq!(ttl_from_resolved_at(now, now), CACHE_TTL_DELTA);
+        assert_eq!(
+            ttl_from_resolved_at(now, CACHE_TTL_DELTA, now),
+            CACHE_TTL_DELTA
+        );
     }
 
     #[test]
     fn expires_now() {
         let now = Utc::now();
         assert_eq!(
-            ttl_from_resolved_at(now - CACHE_TTL_DELTA, now),
+            ttl_from_resolved_at(now - CACHE_TTL_DELTA, CACHE_TTL_DELTA, now),
             TimeDelta::zero()
         );
     }
@@ -592,11 +1488,137 @@ mod tests {
     fn expires_past() {
         let now = Utc::now();
         assert_eq!(
-            ttl_from_resolved_at(now - CACHE_TTL_DELTA - TimeDelta::days(1), now),
+            ttl_from_resolved_at(
+                now - CACHE_TTL_DELTA - TimeDelta::days(1),
+                CACHE_TTL_DELTA,
+                now,
+            ),
             TimeDelta::zero()
         );
     }
 
+    #[test]
+    fn cache_keys() {
+        let address = H160::zero();
+        let address_key = to_checksum(&address, None);
+        assert_eq!(
+            identity_cache_key(address, ETHEREUM_MAINNET, false, None),
+            format!("{address_key}-v1")
+        );
+        assert_eq!(
+            identity_cache_key(address, ETHEREUM_MAINNET, true, None),
+            format!("{address_key}-records-v1")
+        );
+        assert_eq!(
+            identity_cache_key(address, "eip155:10", false, None),
+            format!("{address_key}-eip155:10-v1")
+        );
+        assert_eq!(
+            identity_cache_key(
+                address,
+                ETHEREUM_MAINNET,
+                false,
+                Some(&[IdentitySource::Farcaster, IdentitySource::Ens]),
+            ),
+            format!("{address_key}-sources.farcaster.ens-v1")
+        );
+    }
+
+    #[test]
+    fn releases_identity_refreshes() {
+        let cache_key = "0x0000000000000000000000000000000000000001-v1".to_owned();
+        let refresh = IdentityRefresh::start(cache_key.clone()).unwrap();
+        assert!(IdentityRefresh::start(cache_key.clone()).is_none());
+        assert!(IDENTITY_REFRESHES.lock().unwrap().contains(&cache_key));
+
+        let panicked = std::thread::spawn(move || {
+            let _refresh = refresh;
+            panic!("Refresh panicked");
+        })
+        .join();
+        assert!(panicked.is_err());
+        assert!(!IDENTITY_REFRESHES.lock().unwrap().contains(&cache_key));
+        assert!(IdentityRefresh::start(cache_key).is_some());
+    }
+
+    #[test]
+    fn negative_cache_expires_sooner() {
+        let now = Utc::now();
+        let identity = |name: Option<&str>| IdentityResponse {
+            name: name.map(ToOwned::to_owned),
+            avatar: None,
+            resolved_at: Some(now - TimeDelta::minutes(30)),
+            chain_id: None,
+            resolver: None,
+            records: None,
+        };
+        assert_eq!(identity(None).cache_ttl(), NEGATIVE_CACHE_TTL_DELTA);
+        assert!(identity(None).is_stale(now));
+        assert_eq!(identity(Some("name.eth")).cache_ttl(), CACHE_TTL_DELTA);
+        assert!(!identity(Some("name.eth")).is_stale(now));
+    }
+
+    #[test]
+    fn lookup_chain_id_defaults_to_mainnet() {
+        let query = |chain_id: Option<&str>| IdentityQueryParams {
+            project_id: "project".to_owned(),
+            use_cache: None,
+            client_id: None,
+            sender: None,
+            chain_id: chain_id.map(ToOwned::to_owned),
+            records: None,
+            api_version: None,
+        };
+        assert_eq!(query(None).lookup_chain_id(), ETHEREUM_MAINNET);
+        assert_eq!(query(Some("base")).lookup_chain_id(), "eip155:8453");
+        assert_eq!(query(Some("eip155:10")).lookup_chain_id(), "eip155:10");
+    }
+
+    #[test]
+    fn text_records_require_api_version() {
+        let query = |records: Option<&str>, api_version: Option<usize>| IdentityQueryParams {
+            project_id: "project".to_owned(),
+            use_cache: None,
+            client_id: None,
+            sender: None,
+            chain_id: None,
+            records: records.map(ToOwned::to_owned),
+            api_version,
+        };
+        assert!(query(None, None).text_records().unwrap().is_empty());
+        assert!(query(Some("url"), None).text_records().is_err());
+        assert_eq!(
+            query(None, Some(2)).text_records().unwrap(),
+            SUPPORTED_TEXT_RECORDS.to_vec()
+        );
+        assert_eq!(
+            query(Some("url, com.github"), Some(2))
+                .text_records()
+                .unwrap(),
+            vec!["url", "com.github"]
+        );
+        assert!(query(Some("com.discord"), Some(2)).text_records().is_err());
+    }
+
+    #[test]
+    fn maps_local_attributes_to_text_records() {
+        let attributes = HashMap::from([
+            ("bio".to_owned(), "Some bio".to_owned()),
+            ("url".to_owned(), "https://example.com".to_owned()),
+            (
+                "avatar".to_owned(),
+                "https://example.com/avatar.png".to_owned(),
+            ),
+        ]);
+        assert_eq!(
+            local_attributes_records(&attributes),
+            BTreeMap::from([
+                ("description".to_owned(), "Some bio".to_owned()),
+                ("url".to_owned(), "https://example.com".to_owned()),
+            ])
+        );
+    }
+
     #[test]
     fn deserialize_identity_response_with_no_resolved_at() {
         serde_json::from_value::<IdentityResponse>(json!({
@@ -1,5 +1,5 @@
 use {
-    crate::{analytics::MessageSource, error::RpcError, state::AppState, utils::network},
+    crate::{analytics::MessageSource, chains, error::RpcError, state::AppState, utils::network},
     axum::{
         extract::{MatchedPath, State},
         http::Request,
@@ -12,6 +12,8 @@ use {
     wc::metrics::TaskMetrics,
 };
 
+pub mod admin;
+pub mod avatar;
 pub mod balance;
 pub mod bundler;
 pub mod convert;
@@ -25,6 +27,7 @@ pub mod onramp;
 pub mod portfolio;
 pub mod profile;
 pub mod proxy;
+pub mod resolve;
 pub mod sessions;
 pub mod supported_chains;
 pub mod wallet;
@@ -35,6 +38,8 @@ static HANDLER_TASK_METRICS: TaskMetrics = TaskMetrics::new("handler_task");
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcQueryParams {
+    /// CAIP-2 chain id, the human-readable chain aliases are resolved
+    #[serde(deserialize_with = "chains::deserialize_chain_id")]
     pub chain_id: String,
     pub project_id: String,
     /// Optional provider ID for the exact provider request
@@ -1,7 +1,7 @@
 use {
     super::{
-        super::HANDLER_TASK_METRICS, RegisterRequest, UpdateAddressPayload,
-        UNIXTIMESTAMP_SYNC_THRESHOLD,
+        super::HANDLER_TASK_METRICS, check_name_ownership, verify_request_signature,
+        RegisterRequest, UpdateAddressPayload, UNIXTIMESTAMP_SYNC_THRESHOLD,
     },
     crate::{
         analytics::MessageSource,
@@ -10,22 +10,19 @@ use {
             types::SupportedNamespaces,
         },
         error::RpcError,
+        handlers::identity::invalidate_name_identities,
         names::utils::is_timestamp_within_interval,
         state::AppState,
-        utils::crypto::{
-            constant_time_eq, convert_coin_type_to_evm_chain_id, is_coin_type_supported,
-            verify_message_signature,
-        },
+        utils::crypto::{is_address_valid, is_coin_type_supported},
     },
     axum::{
         extract::{Path, State},
         response::{IntoResponse, Response},
         Json,
     },
-    ethers::types::H160,
     hyper::StatusCode,
     sqlx::Error as SqlxError,
-    std::{str::FromStr, sync::Arc},
+    std::sync::Arc,
     tracing::log::error,
     wc::future::FutureExt,
 };
@@ -62,8 +59,10 @@ pub async fn handler_internal(
         return Err(RpcError::UnsupportedCoinType(payload.coin_type));
     }
 
-    // Check the new address format
-    if H160::from_str(&payload.address).is_err() {
+    // Check the new address format of the coin type namespace
+    let namespace = SupportedNamespaces::from_slip44(payload.coin_type)
+        .ok_or(RpcError::UnsupportedCoinType(payload.coin_type))?;
+    if !is_address_valid(&payload.address, &namespace.to_caip_namespace()) {
         return Err(RpcError::InvalidAddress);
     }
 
@@ -89,78 +88,40 @@ pub async fn handler_internal(
         return Err(RpcError::ExpiredTimestamp(payload.timestamp));
     }
 
-    let payload_owner = match H160::from_str(&request_payload.address) {
-        Ok(owner) => owner,
-        Err(_) => return Err(RpcError::InvalidAddress),
-    };
-
     // Check the signature
-    let chain_id_caip2 = format!(
-        "eip155:{}",
-        convert_coin_type_to_evm_chain_id(payload.coin_type) as u64
-    );
-    let rpc_project_id = state
-        .config
-        .server
-        .testing_project_id
-        .as_ref()
-        .ok_or_else(|| {
-            RpcError::InvalidConfiguration(
-                "Missing testing project id in the configuration for eip1271 lookups".to_string(),
-            )
-        })?;
-    let sinature_check = match verify_message_signature(
-        raw_payload,
-        &request_payload.signature,
-        &request_payload.address,
-        &chain_id_caip2,
-        rpc_project_id,
+    verify_request_signature(
+        &state,
+        &request_payload,
         MessageSource::ProfileAddressSigValidate,
     )
-    .await
-    {
-        Ok(sinature_check) => sinature_check,
-        Err(_) => {
-            return Err(RpcError::SignatureValidationError(
-                "Invalid signature".into(),
-            ))
-        }
-    };
-    if !sinature_check {
-        return Err(RpcError::SignatureValidationError(
-            "Signature verification error".into(),
-        ));
-    }
+    .await?;
 
     // Check for the name address ownership and address from the signed payload
-    let mut address_is_authorized = false;
-    for (coint_type, address) in name_addresses.addresses.iter() {
-        if coint_type == &request_payload.coin_type {
-            let name_owner = match ethers::types::H160::from_str(&address.address) {
-                Ok(owner) => owner,
-                Err(_) => return Err(RpcError::InvalidAddress),
-            };
-            if !constant_time_eq(payload_owner, name_owner) {
-                return Err(RpcError::NameOwnerValidationError);
-            } else {
-                address_is_authorized = true;
-            }
-        }
-    }
-    if !address_is_authorized {
-        return Err(RpcError::NameOwnerValidationError);
-    }
+    check_name_ownership(&name_addresses, &request_payload)?;
 
+    let new_address = payload.address.clone();
     match insert_or_update_address(
         name.clone(),
-        SupportedNamespaces::Eip155,
+        namespace,
         format!("{}", payload.coin_type),
         payload.address,
         &state.postgres.clone(),
     )
     .await
     {
-        Ok(response) => Ok(Json(response).into_response()),
+        Ok(response) => {
+            // Both the replaced and the new addresses are resolving to the name
+            invalidate_name_identities(
+                &state,
+                name_addresses
+                    .addresses
+                    .values()
+                    .map(|address| address.address.as_str())
+                    .chain([new_address.as_str()]),
+            )
+            .await;
+            Ok(Json(response).into_response())
+        }
         Err(e) => {
             error!("Failed to update address: {}", e);
             Ok((
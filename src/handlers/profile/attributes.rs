@@ -1,19 +1,17 @@
 use {
     super::{
-        super::HANDLER_TASK_METRICS, RegisterRequest, UpdateAttributesPayload,
-        UNIXTIMESTAMP_SYNC_THRESHOLD,
+        super::HANDLER_TASK_METRICS, check_name_ownership, verify_request_signature,
+        RegisterRequest, UpdateAttributesPayload, UNIXTIMESTAMP_SYNC_THRESHOLD,
     },
     crate::{
         analytics::MessageSource,
         database::helpers::{get_name_and_addresses_by_name, update_name_attributes},
         error::RpcError,
+        handlers::identity::invalidate_name_identities,
         names::utils::{check_attributes, is_timestamp_within_interval},
         names::{ATTRIBUTES_VALUE_MAX_LENGTH, SUPPORTED_ATTRIBUTES},
         state::AppState,
-        utils::crypto::{
-            constant_time_eq, convert_coin_type_to_evm_chain_id, is_coin_type_supported,
-            verify_message_signature,
-        },
+        utils::crypto::is_coin_type_supported,
     },
     axum::{
         extract::{Path, State},
@@ -21,7 +19,7 @@ use {
         Json,
     },
     hyper::StatusCode,
-    std::{str::FromStr, sync::Arc},
+    std::sync::Arc,
     tracing::log::error,
     wc::future::FutureExt,
 };
@@ -65,67 +63,16 @@ pub async fn handler_internal(
         return Err(RpcError::ExpiredTimestamp(payload.timestamp));
     }
 
-    let payload_owner = match ethers::types::H160::from_str(&request_payload.address) {
-        Ok(owner) => owner,
-        Err(_) => return Err(RpcError::InvalidAddress),
-    };
-
     // Check the signature
-    let chain_id_caip2 = format!(
-        "eip155:{}",
-        convert_coin_type_to_evm_chain_id(request_payload.coin_type) as u64
-    );
-    let rpc_project_id = state
-        .config
-        .server
-        .testing_project_id
-        .as_ref()
-        .ok_or_else(|| {
-            RpcError::InvalidConfiguration(
-                "Missing testing project id in the configuration for eip1271 lookups".to_string(),
-            )
-        })?;
-    let sinature_check = match verify_message_signature(
-        raw_payload,
-        &request_payload.signature,
-        &request_payload.address,
-        &chain_id_caip2,
-        rpc_project_id,
+    verify_request_signature(
+        &state,
+        &request_payload,
         MessageSource::ProfileAttributesSigValidate,
     )
-    .await
-    {
-        Ok(sinature_check) => sinature_check,
-        Err(_) => {
-            return Err(RpcError::SignatureValidationError(
-                "Invalid signature".into(),
-            ))
-        }
-    };
-    if !sinature_check {
-        return Err(RpcError::SignatureValidationError(
-            "Signature verification error".into(),
-        ));
-    }
+    .await?;
 
     // Check for the name address ownership and address from the signed payload
-    let mut address_is_authorized = false;
-    for (coint_type, address) in name_addresses.addresses.iter() {
-        if coint_type == &request_payload.coin_type {
-            let name_owner = match ethers::types::H160::from_str(&address.address) {
-                Ok(owner) => owner,
-                Err(_) => return Err(RpcError::InvalidAddress),
-            };
-            if !constant_time_eq(payload_owner, name_owner) {
-                return Err(RpcError::NameOwnerValidationError);
-            } else {
-                address_is_authorized = true;
-            }
-        }
-    }
-    if !address_is_authorized {
-        return Err(RpcError::NameOwnerValidationError);
-    }
+    check_name_ownership(&name_addresses, &request_payload)?;
 
     // Check for supported attributes
     if !check_attributes(
@@ -145,6 +92,16 @@ pub async fn handler_internal(
             )
                 .into_response())
         }
-        Ok(attributes) => Ok(Json(attributes).into_response()),
+        Ok(attributes) => {
+            invalidate_name_identities(
+                &state,
+                name_addresses
+                    .addresses
+                    .values()
+                    .map(|address| address.address.as_str()),
+            )
+            .await;
+            Ok(Json(attributes).into_response())
+        }
     }
 }
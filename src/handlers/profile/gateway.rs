@@ -0,0 +1,107 @@
+use {
+    super::super::HANDLER_TASK_METRICS,
+    crate::{
+        database::helpers::get_name_and_addresses_by_name,
+        error::RpcError,
+        names::{
+            gateway::{self, ResolveRequest},
+            utils::is_name_in_allowed_zones,
+        },
+        state::AppState,
+    },
+    axum::{
+        extract::{Path, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    ethers::types::Address,
+    serde::{Deserialize, Serialize},
+    sqlx::Error as SqlxError,
+    std::{
+        sync::Arc,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    wc::future::FutureExt,
+};
+
+/// EIP-3668 gateway request, the `GET` requests are passing the same fields
+/// in the path
+#[derive(Debug, Deserialize, Clone)]
+pub struct GatewayRequest {
+    /// Address of the resolver contract that reverted with `OffchainLookup`
+    pub sender: String,
+    /// Hex-encoded `resolve(bytes,bytes)` calldata
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GatewayResponse {
+    /// Hex-encoded signed `(bytes result, uint64 expires, bytes signature)`
+    pub data: String,
+}
+
+pub async fn get_handler(
+    state: State<Arc<AppState>>,
+    Path((sender, data)): Path<(String, String)>,
+) -> Result<Response, RpcError> {
+    // Gateway URL templates are commonly ending with the `{data}.json`
+    let data = data.trim_end_matches(".json").to_owned();
+    handler_internal(state, GatewayRequest { sender, data })
+        .with_metrics(HANDLER_TASK_METRICS.with_name("profile_gateway"))
+        .await
+}
+
+pub async fn post_handler(
+    state: State<Arc<AppState>>,
+    Json(request): Json<GatewayRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, request)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("profile_gateway"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    request: GatewayRequest,
+) -> Result<Response, RpcError> {
+    let names_config = &state.config.names;
+    let signer = state
+        .names_gateway_signer
+        .as_ref()
+        .ok_or(RpcError::NamesGatewayDisabled)?;
+    let allowed_zones = names_config.allowed_zones.as_ref().ok_or_else(|| {
+        RpcError::InvalidConfiguration("Names allowed zones are not defined".to_string())
+    })?;
+
+    let sender = request
+        .sender
+        .parse::<Address>()
+        .map_err(|_| RpcError::InvalidAddress)?;
+    let calldata = hex::decode(request.data.trim_start_matches("0x"))
+        .map_err(|e| RpcError::InvalidGatewayRequest(format!("Invalid calldata hex: {e}")))?;
+    let resolve_request = ResolveRequest::decode(&calldata)?;
+    if !is_name_in_allowed_zones(&resolve_request.name, allowed_zones.clone()) {
+        return Err(RpcError::InvalidNameZone(resolve_request.name));
+    }
+
+    // Unregistered names are answered with the empty records
+    let name = match get_name_and_addresses_by_name(resolve_request.name, &state.postgres).await {
+        Ok(name) => Some(name),
+        Err(SqlxError::RowNotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let result = resolve_request.query.answer(name.as_ref());
+
+    let expires = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time should't go backwards")
+        .as_secs()
+        + names_config.gateway_response_ttl();
+    let response = gateway::sign_response(signer, sender, &calldata, result, expires)?;
+
+    Ok(Json(GatewayResponse {
+        data: format!("0x{}", hex::encode(response)),
+    })
+    .into_response())
+}
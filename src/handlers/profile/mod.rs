@@ -1,14 +1,27 @@
 use {
+    crate::{
+        analytics::MessageSource,
+        database::types::{NameAndAddresses, SupportedNamespaces},
+        error::RpcError,
+        state::AppState,
+        utils::crypto::{
+            constant_time_eq, convert_coin_type_to_caip2, verify_ed25519_signature,
+            verify_message_signature,
+        },
+    },
+    ethers::types::H160,
     serde::{Deserialize, Serialize},
-    std::collections::HashMap,
+    std::{collections::HashMap, str::FromStr},
 };
 
 pub mod address;
 pub mod attributes;
+pub mod gateway;
 pub mod lookup;
 pub mod register;
 pub mod reverse;
 pub mod suggestions;
+pub mod transfer;
 
 pub const UNIXTIMESTAMP_SYNC_THRESHOLD: u64 = 10;
 
@@ -49,6 +62,21 @@ pub struct UpdateAddressPayload {
     pub timestamp: u64,
 }
 
+/// Payload to transfer the name to the new owner address that should be
+/// serialized to JSON and signed by the current owner and optionally by the
+/// new owner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferNamePayload {
+    /// Name to transfer
+    pub name: String,
+    /// Coin type ENSIP-11 of the new owner address
+    pub coin_type: u32,
+    /// New owner address
+    pub address: String,
+    /// Unixtime
+    pub timestamp: u64,
+}
+
 /// Data structure representing a request to register a name
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RegisterRequest {
@@ -62,6 +90,16 @@ pub struct RegisterRequest {
     pub address: String,
 }
 
+/// Data structure representing a request to transfer a name signed by the
+/// current owner
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TransferRequest {
+    #[serde(flatten)]
+    pub owner_request: RegisterRequest,
+    /// Optional new owner signature of the same message to accept the transfer
+    pub recipient_signature: Option<String>,
+}
+
 /// Forward and reverse lookup query parameters
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -79,3 +117,89 @@ pub struct SuggestionsParams {
     /// Optional zone to use for name suggestions
     pub zone: Option<String>,
 }
+
+/// Verifies the signed request payload by the namespace of the request coin
+/// type, EVM signatures are verified including the smart accounts signatures
+/// and Solana signatures are ed25519 signatures of the payload
+pub async fn verify_request_signature(
+    state: &AppState,
+    request: &RegisterRequest,
+    source: MessageSource,
+) -> Result<(), RpcError> {
+    match SupportedNamespaces::from_slip44(request.coin_type) {
+        Some(SupportedNamespaces::Eip155) => {
+            verify_evm_request_signature(state, request, source).await
+        }
+        Some(SupportedNamespaces::Solana) => {
+            verify_ed25519_signature(&request.message, &request.signature, &request.address)
+        }
+        // Bitcoin message signatures are not supported
+        _ => Err(RpcError::UnsupportedCoinType(request.coin_type)),
+    }
+}
+
+async fn verify_evm_request_signature(
+    state: &AppState,
+    request: &RegisterRequest,
+    source: MessageSource,
+) -> Result<(), RpcError> {
+    let rpc_project_id = state
+        .config
+        .server
+        .testing_project_id
+        .as_ref()
+        .ok_or_else(|| {
+            RpcError::InvalidConfiguration(
+                "Missing testing project id in the configuration for eip1271 lookups".to_string(),
+            )
+        })?;
+    let signature_check = match verify_message_signature(
+        &request.message,
+        &request.signature,
+        &request.address,
+        &convert_coin_type_to_caip2(request.coin_type),
+        rpc_project_id,
+        source,
+    )
+    .await
+    {
+        Ok(signature_check) => signature_check,
+        Err(_) => {
+            return Err(RpcError::SignatureValidationError(
+                "Invalid signature".into(),
+            ))
+        }
+    };
+    if !signature_check {
+        return Err(RpcError::SignatureValidationError(
+            "Signature verification error".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the request signer is the name address of the request coin type
+pub fn check_name_ownership(
+    name_addresses: &NameAndAddresses,
+    request: &RegisterRequest,
+) -> Result<(), RpcError> {
+    let name_owner = name_addresses
+        .addresses
+        .get(&request.coin_type)
+        .ok_or(RpcError::NameOwnerValidationError)?;
+    let is_owner = match SupportedNamespaces::from_slip44(request.coin_type) {
+        Some(SupportedNamespaces::Eip155) => {
+            let payload_owner =
+                H160::from_str(&request.address).map_err(|_| RpcError::InvalidAddress)?;
+            let name_owner =
+                H160::from_str(&name_owner.address).map_err(|_| RpcError::InvalidAddress)?;
+            constant_time_eq(payload_owner, name_owner)
+        }
+        // Non-EVM addresses are case-sensitive
+        _ => constant_time_eq(&request.address, &name_owner.address),
+    };
+    if !is_owner {
+        return Err(RpcError::NameOwnerValidationError);
+    }
+    Ok(())
+}
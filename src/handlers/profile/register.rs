@@ -1,6 +1,7 @@
 use {
     super::{
-        super::HANDLER_TASK_METRICS, RegisterPayload, RegisterRequest, UNIXTIMESTAMP_SYNC_THRESHOLD,
+        super::HANDLER_TASK_METRICS, verify_request_signature, RegisterPayload, RegisterRequest,
+        UNIXTIMESTAMP_SYNC_THRESHOLD,
     },
     crate::{
         analytics::{AccountNameRegistration, MessageSource},
@@ -9,6 +10,7 @@ use {
             types::{Address, ENSIP11AddressesMap, SupportedNamespaces},
         },
         error::RpcError,
+        handlers::identity::invalidate_name_identities,
         names::{
             utils::{
                 check_attributes, is_name_format_correct, is_name_in_allowed_zones,
@@ -18,9 +20,7 @@ use {
         },
         state::AppState,
         utils::{
-            crypto::{
-                convert_coin_type_to_evm_chain_id, is_coin_type_supported, verify_message_signature,
-            },
+            crypto::{convert_coin_type_to_caip2, is_address_valid, is_coin_type_supported},
             network,
         },
     },
@@ -82,6 +82,13 @@ pub async fn handler_internal(
     if !is_coin_type_supported(register_request.coin_type) {
         return Err(RpcError::UnsupportedCoinType(register_request.coin_type));
     }
+    let namespace = SupportedNamespaces::from_slip44(register_request.coin_type)
+        .ok_or(RpcError::UnsupportedCoinType(register_request.coin_type))?;
+
+    // Check the address format of the coin type namespace
+    if !is_address_valid(&register_request.address, &namespace.to_caip_namespace()) {
+        return Err(RpcError::InvalidAddress);
+    }
 
     // Check is name already registered
     if get_name_and_addresses_by_name(payload.name.clone(), &state.postgres.clone())
@@ -108,42 +115,12 @@ pub async fn handler_internal(
     }
 
     // Check the signature
-    let chain_id_caip2 = format!(
-        "eip155:{}",
-        convert_coin_type_to_evm_chain_id(register_request.coin_type) as u64
-    );
-    let rpc_project_id = state
-        .config
-        .server
-        .testing_project_id
-        .as_ref()
-        .ok_or_else(|| {
-            RpcError::InvalidConfiguration(
-                "Missing testing project id in the configuration for eip1271 lookups".to_string(),
-            )
-        })?;
-    let sinature_check = match verify_message_signature(
-        raw_payload,
-        &register_request.signature,
-        &register_request.address,
-        &chain_id_caip2,
-        rpc_project_id,
+    verify_request_signature(
+        &state,
+        &register_request,
         MessageSource::ProfileRegisterSigValidate,
     )
-    .await
-    {
-        Ok(sinature_check) => sinature_check,
-        Err(_) => {
-            return Err(RpcError::SignatureValidationError(
-                "Invalid signature".into(),
-            ))
-        }
-    };
-    if !sinature_check {
-        return Err(RpcError::SignatureValidationError(
-            "Signature verification error".into(),
-        ));
-    }
+    .await?;
 
     // Register (insert) a new domain with address
     let mut addresses: ENSIP11AddressesMap = HashMap::from([(
@@ -155,18 +132,20 @@ pub async fn handler_internal(
     )]);
 
     // Adding address with cointype 60 (Mainnet) by default
-    // if it was not provided during the registration
-    if let std::collections::hash_map::Entry::Vacant(e) = addresses.entry(60) {
-        e.insert(Address {
-            address: register_request.address.clone(),
-            created_at: None,
-        });
+    // if it was not provided during the registration by the EVM address
+    if namespace == SupportedNamespaces::Eip155 {
+        if let std::collections::hash_map::Entry::Vacant(e) = addresses.entry(60) {
+            e.insert(Address {
+                address: register_request.address.clone(),
+                created_at: None,
+            });
+        }
     }
 
     let insert_result = insert_name(
         payload.name.clone(),
         payload.attributes.unwrap_or(HashMap::new()),
-        SupportedNamespaces::Eip155,
+        namespace,
         addresses,
         &state.postgres,
     )
@@ -175,6 +154,7 @@ pub async fn handler_internal(
         error!("Failed to insert new name: {}", e);
         return Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response());
     }
+    invalidate_name_identities(&state, [register_request.address.as_str()]).await;
 
     // Name registration analytics
     {
@@ -193,7 +173,7 @@ pub async fn handler_internal(
             .name_registration(AccountNameRegistration::new(
                 payload.name.clone(),
                 register_request.address.clone(),
-                chain_id_caip2,
+                convert_coin_type_to_caip2(register_request.coin_type),
                 origin,
                 region,
                 country,
@@ -0,0 +1,199 @@
+use {
+    super::{
+        super::HANDLER_TASK_METRICS, check_name_ownership, verify_request_signature,
+        RegisterRequest, TransferNamePayload, TransferRequest, UNIXTIMESTAMP_SYNC_THRESHOLD,
+    },
+    crate::{
+        analytics::MessageSource,
+        database::{
+            error::DatabaseError,
+            helpers::{get_name_and_addresses_by_name, transfer_name},
+            types::{Address, ENSIP11AddressesMap, NameTransfer, SupportedNamespaces},
+        },
+        error::RpcError,
+        handlers::identity::invalidate_name_identities,
+        names::utils::is_timestamp_within_interval,
+        state::AppState,
+        utils::crypto::{is_address_valid, is_coin_type_supported},
+    },
+    axum::{
+        extract::{Path, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    hyper::StatusCode,
+    sqlx::Error as SqlxError,
+    std::{collections::HashMap, sync::Arc},
+    tracing::log::error,
+    wc::future::FutureExt,
+};
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    name: Path<String>,
+    Json(request_payload): Json<TransferRequest>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, name, request_payload)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("profile_transfer"))
+        .await
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn handler_internal(
+    state: State<Arc<AppState>>,
+    Path(name): Path<String>,
+    request_payload: TransferRequest,
+) -> Result<Response, RpcError> {
+    let owner_request = &request_payload.owner_request;
+    let raw_payload = &owner_request.message;
+    let payload = match serde_json::from_str::<TransferNamePayload>(raw_payload) {
+        Ok(payload) => payload,
+        Err(e) => return Err(RpcError::SerdeJson(e)),
+    };
+
+    // Check the signed payload is for the requested name
+    if payload.name != name {
+        return Err(RpcError::NameTransferError(format!(
+            "Signed payload is for the different name: {}",
+            payload.name
+        )));
+    }
+
+    // Check for the supported ENSIP-11 coin type
+    if !is_coin_type_supported(owner_request.coin_type) {
+        return Err(RpcError::UnsupportedCoinType(owner_request.coin_type));
+    }
+
+    // Check for supported chain id and address format of the new owner
+    if !is_coin_type_supported(payload.coin_type) {
+        return Err(RpcError::UnsupportedCoinType(payload.coin_type));
+    }
+    let namespace = SupportedNamespaces::from_slip44(payload.coin_type)
+        .ok_or(RpcError::UnsupportedCoinType(payload.coin_type))?;
+    if !is_address_valid(&payload.address, &namespace.to_caip_namespace()) {
+        return Err(RpcError::InvalidAddress);
+    }
+
+    // Check is name registered
+    let name_addresses =
+        match get_name_and_addresses_by_name(name.clone(), &state.postgres.clone()).await {
+            Ok(result) => result,
+            Err(e) => match e {
+                SqlxError::RowNotFound => return Err(RpcError::NameNotRegistered(name)),
+                _ => {
+                    error!("Failed to lookup name in the database: {}", e);
+                    return Ok((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Name lookup database error",
+                    )
+                        .into_response());
+                }
+            },
+        };
+
+    // Check the timestamp is within the sync threshold interval
+    if !is_timestamp_within_interval(payload.timestamp, UNIXTIMESTAMP_SYNC_THRESHOLD) {
+        return Err(RpcError::ExpiredTimestamp(payload.timestamp));
+    }
+
+    // Check the current owner signature
+    verify_request_signature(
+        &state,
+        owner_request,
+        MessageSource::ProfileTransferSigValidate,
+    )
+    .await?;
+
+    // Check for the name address ownership and address from the signed payload
+    check_name_ownership(&name_addresses, owner_request)?;
+
+    // Check the new owner signature of the same payload if the transfer is
+    // accepted by the new owner
+    if let Some(recipient_signature) = &request_payload.recipient_signature {
+        let recipient_request = RegisterRequest {
+            message: raw_payload.clone(),
+            signature: recipient_signature.clone(),
+            coin_type: payload.coin_type,
+            address: payload.address.clone(),
+        };
+        verify_request_signature(
+            &state,
+            &recipient_request,
+            MessageSource::ProfileTransferSigValidate,
+        )
+        .await?;
+    }
+
+    // Replace the name addresses with the new owner address
+    let mut addresses: ENSIP11AddressesMap = HashMap::from([(
+        payload.coin_type,
+        Address {
+            address: payload.address.clone(),
+            created_at: None,
+        },
+    )]);
+
+    // Adding address with cointype 60 (Mainnet) by default
+    // if the new owner is the EVM address same as for the registration
+    if namespace == SupportedNamespaces::Eip155 {
+        if let std::collections::hash_map::Entry::Vacant(e) = addresses.entry(60) {
+            e.insert(Address {
+                address: payload.address.clone(),
+                created_at: None,
+            });
+        }
+    }
+
+    // Owner address is taken from the database for the exact match in the
+    // transfer transaction
+    let owner_address = name_addresses
+        .addresses
+        .get(&owner_request.coin_type)
+        .map(|address| address.address.clone())
+        .ok_or(RpcError::NameOwnerValidationError)?;
+    let transfer = NameTransfer {
+        name: name.clone(),
+        from_address: owner_address,
+        to_address: payload.address.clone(),
+        accepted: request_payload.recipient_signature.is_some(),
+    };
+    let transfer_result = transfer_name(
+        transfer,
+        owner_request.coin_type,
+        namespace,
+        addresses,
+        &state.postgres,
+    )
+    .await;
+    match transfer_result {
+        Ok(()) => {}
+        // Owner address was updated concurrently with the transfer
+        Err(DatabaseError::OwnerChanged(_)) => return Err(RpcError::NameOwnerValidationError),
+        Err(e) => {
+            error!("Failed to transfer name: {}", e);
+            return Ok(
+                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to transfer name").into_response(),
+            );
+        }
+    }
+
+    // Both the previous and the new owner addresses are resolving differently
+    invalidate_name_identities(
+        &state,
+        name_addresses
+            .addresses
+            .values()
+            .map(|address| address.address.as_str())
+            .chain([payload.address.as_str()]),
+    )
+    .await;
+
+    // Return the transferred name and addresses
+    match get_name_and_addresses_by_name(name, &state.postgres.clone()).await {
+        Ok(response) => Ok(Json(response).into_response()),
+        Err(e) => {
+            error!("Failed to lookup name after the transfer: {}", e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, "").into_response())
+        }
+    }
+}
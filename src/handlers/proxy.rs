@@ -3,32 +3,42 @@ use {
     crate::{
         analytics::MessageInfo,
         error::RpcError,
+        json_rpc::RequestEnvelope,
+        providers::{shadow, with_body_deadline, RpcProvider},
         state::AppState,
         utils::{crypto, network},
     },
     axum::{
-        body::Bytes,
+        body::{self, BoxBody, Bytes},
         extract::{ConnectInfo, MatchedPath, Query, State},
         response::Response,
     },
-    hyper::{http, HeaderMap},
+    bytes::BytesMut,
+    futures_util::stream,
+    hyper::{body::HttpBody, http, Body, HeaderMap},
+    rand::Rng,
     std::{
         borrow::Borrow,
         net::SocketAddr,
         sync::Arc,
-        time::{Duration, SystemTime},
+        time::{Duration, Instant, SystemTime},
     },
     tap::TapFallible,
-    tokio::time::timeout,
+    tokio::{sync::oneshot, time::timeout},
     tracing::{
         log::{debug, error, warn},
         Span,
     },
-    wc::future::FutureExt,
+    wc::{future::FutureExt, geoip},
 };
 
-const PROVIDER_PROXY_MAX_CALLS: usize = 3;
-const PROVIDER_PROXY_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+/// Share of the shadow responses mismatches written to the logs
+const SHADOW_DIFF_LOG_SAMPLE_RATE: f64 = 0.1;
+/// Maximum length of the responses written to the shadow diff logs
+const SHADOW_DIFF_LOG_MAX_LENGTH: usize = 1024;
+/// Primary responses larger than this are streamed to the client without
+/// being compared with the shadow responses
+const SHADOW_COMPARED_BODY_MAX_LENGTH: usize = 512 * 1024;
 
 pub async fn handler(
     state: State<Arc<AppState>>,
@@ -55,7 +65,187 @@ async fn handler_internal(
     state
         .validate_project_access_and_quota(&query_params.project_id.clone())
         .await?;
-    rpc_call(state, addr, query_params, headers, body).await
+
+    let request = RequestEnvelope::parse(body);
+    // Exact provider requests of the testing suite and batches are not mirrored
+    let shadow_call = if query_params.provider_id.is_none()
+        && request.method().is_some_and(shadow::is_read_method)
+    {
+        state
+            .providers
+            .get_shadow_call_for_chain_id(&query_params.chain_id)
+    } else {
+        None
+    };
+    let Some(shadow_call) = shadow_call else {
+        return rpc_call(state, addr, query_params, headers, request).await;
+    };
+
+    let chain_id = query_params.chain_id.clone();
+    let call_timeout = state
+        .providers
+        .call_policies
+        .resolve(&chain_id, request.method())
+        .timeout;
+    let primary_call_start = SystemTime::now();
+    let response = rpc_call(state.clone(), addr, query_params, headers, request.clone()).await?;
+    let primary_latency = primary_call_start.elapsed().unwrap_or_default();
+    mirror_to_shadow(
+        state,
+        chain_id,
+        request,
+        response,
+        primary_latency,
+        shadow_call,
+        call_timeout,
+    )
+    .await
+}
+
+/// Compares the primary provider response with the shadow provider response
+/// in the background, the primary response is streamed to the client as is
+/// while its copy is collected for the comparison
+async fn mirror_to_shadow(
+    state: Arc<AppState>,
+    chain_id: String,
+    request: RequestEnvelope,
+    response: Response,
+    primary_latency: Duration,
+    shadow_call: shadow::ShadowCall,
+    call_timeout: Duration,
+) -> Result<Response, RpcError> {
+    let (parts, primary_body) = response.into_parts();
+    let primary_status = parts.status.as_u16();
+    let (primary_body_sender, primary_body_receiver) = oneshot::channel();
+    let response = Response::from_parts(
+        parts,
+        body::boxed(tee_body(primary_body, primary_body_sender)),
+    );
+
+    tokio::spawn(async move {
+        let shadow_provider = &shadow_call.provider;
+        let shadow_kind = shadow_provider.provider_kind();
+        let shadow_call_start = SystemTime::now();
+        let shadow_response = timeout(
+            call_timeout,
+            shadow_provider.proxy(&chain_id, request.body().clone()),
+        )
+        .await;
+        let shadow_latency = shadow_call_start.elapsed().unwrap_or_default();
+
+        // Primary response is not compared when the client disconnected
+        // before it was fully streamed or it's too large
+        let Ok(primary_body) = primary_body_receiver.await else {
+            return;
+        };
+
+        let outcome = match shadow_response {
+            Ok(Ok(shadow_response)) => {
+                let shadow_status = shadow_response.status().as_u16();
+                match hyper::body::to_bytes(shadow_response.into_body()).await {
+                    Ok(shadow_body) => {
+                        let outcome = shadow::compare_responses(
+                            primary_status,
+                            &primary_body,
+                            shadow_status,
+                            &shadow_body,
+                        );
+                        if outcome.is_mismatch()
+                            && rand::thread_rng().gen_bool(SHADOW_DIFF_LOG_SAMPLE_RATE)
+                        {
+                            let (id, method, params) = request
+                                .single()
+                                .map(|single| {
+                                    (
+                                        single.id.as_ref().map(ToString::to_string),
+                                        single.method.as_ref(),
+                                        request.params(single).map(truncate_body),
+                                    )
+                                })
+                                .unwrap_or_default();
+                            warn!(
+                                "Shadow provider {} response mismatch ({}) for {} request \
+                                 {:?} {} {:?}: primary {} {}, shadow {} {}",
+                                shadow_kind,
+                                outcome.as_str(),
+                                chain_id,
+                                id,
+                                method,
+                                params,
+                                primary_status,
+                                truncate_body(&primary_body),
+                                shadow_status,
+                                truncate_body(&shadow_body),
+                            );
+                        }
+                        outcome
+                    }
+                    Err(e) => {
+                        debug!("Failed to read the shadow provider {shadow_kind} response: {e}");
+                        shadow::ShadowOutcome::ShadowError
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                debug!("Failed call to the shadow provider {shadow_kind}: {e}");
+                shadow::ShadowOutcome::ShadowError
+            }
+            Err(e) => {
+                debug!("Timeout calling the shadow provider {shadow_kind}: {e}");
+                shadow::ShadowOutcome::ShadowError
+            }
+        };
+
+        state
+            .metrics
+            .add_shadow_call(&shadow_kind, chain_id.clone(), outcome.as_str());
+        state.metrics.add_shadow_call_latency(
+            &shadow_kind,
+            chain_id.clone(),
+            "primary",
+            primary_latency,
+        );
+        state
+            .metrics
+            .add_shadow_call_latency(&shadow_kind, chain_id, "shadow", shadow_latency);
+    });
+
+    Ok(response)
+}
+
+/// Streams the body and sends its copy once it's fully streamed, the copy is
+/// not sent for the bodies larger than the `SHADOW_COMPARED_BODY_MAX_LENGTH`
+fn tee_body(body: BoxBody, sender: oneshot::Sender<Bytes>) -> Body {
+    let chunks = stream::unfold(
+        (body, Some(sender), BytesMut::new()),
+        |(mut body, mut sender, mut copy)| async move {
+            match body.data().await {
+                Some(Ok(chunk)) => {
+                    if copy.len() + chunk.len() > SHADOW_COMPARED_BODY_MAX_LENGTH {
+                        sender = None;
+                    } else if sender.is_some() {
+                        copy.extend_from_slice(&chunk);
+                    }
+                    Some((Ok(chunk), (body, sender, copy)))
+                }
+                Some(Err(e)) => Some((Err(e), (body, None, copy))),
+                None => {
+                    if let Some(sender) = sender {
+                        let _ = sender.send(copy.freeze());
+                    }
+                    None
+                }
+            }
+        },
+    );
+    Body::wrap_stream(chunks)
+}
+
+fn truncate_body(body: &[u8]) -> String {
+    String::from_utf8_lossy(body)
+        .chars()
+        .take(SHADOW_DIFF_LOG_MAX_LENGTH)
+        .collect()
 }
 
 #[tracing::instrument(skip(state), level = "debug")]
@@ -64,9 +254,18 @@ pub async fn rpc_call(
     addr: SocketAddr,
     query_params: RpcQueryParams,
     headers: HeaderMap,
-    body: Bytes,
+    request: RequestEnvelope,
 ) -> Result<Response, RpcError> {
     let chain_id = query_params.chain_id.clone();
+    let policy = state
+        .providers
+        .call_policies
+        .resolve(&chain_id, request.method());
+    // Client location is resolved once for the routing, the providers
+    // regional endpoints and the analytics
+    let geo = state
+        .analytics
+        .lookup_geo_data(network::get_forwarded_ip(headers.clone()).unwrap_or_else(|| addr.ip()));
     // Exact provider proxy request for testing suite
     // This request is allowed only for the RPC_PROXY_TESTING_PROJECT_ID
     let providers = match query_params.provider_id.clone() {
@@ -92,30 +291,56 @@ pub async fn rpc_call(
 
             provider
         }
-        None => state
-            .providers
-            .get_provider_for_chain_id(&chain_id, PROVIDER_PROXY_MAX_CALLS)?,
+        None => {
+            // Providers closer to the client are preferred
+            state.providers.get_provider_for_chain_id(
+                &chain_id,
+                policy.max_providers,
+                geo.as_ref().and_then(|geo| geo.continent.as_deref()),
+            )?
+        }
     };
 
+    let deadline = Instant::now() + policy.deadline;
     for (i, provider) in providers.iter().enumerate() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            debug!(
+                "Request deadline of the '{}' policy exceeded for chain_id: {}",
+                policy.name, chain_id
+            );
+            state.metrics.add_rpc_call_policy_outcome(
+                chain_id.clone(),
+                policy.name.clone(),
+                "deadline_exceeded",
+            );
+            return Err(RpcError::ChainTemporarilyUnavailable(chain_id));
+        }
+
         let response = rpc_provider_call(
             state.clone(),
-            addr,
             query_params.clone(),
             headers.clone(),
-            body.clone(),
+            geo.as_ref(),
+            &request,
             provider.clone(),
+            policy.timeout.min(remaining),
         )
         .await;
 
         match response {
             Ok(response) if !response.status().is_server_error() => {
+                state.metrics.add_rpc_call_policy_outcome(
+                    chain_id.clone(),
+                    policy.name.clone(),
+                    "success",
+                );
                 return Ok(response);
             }
             e => {
                 state
                     .metrics
-                    .add_rpc_call_retries(i as u64, chain_id.clone());
+                    .add_rpc_call_retries(i as u64, chain_id.clone(), policy.name.clone());
                 debug!(
                     "Provider '{}' returned an error {e:?}, trying the next provider",
                     provider.provider_kind()
@@ -125,17 +350,21 @@ pub async fn rpc_call(
     }
 
     debug!("All providers failed for chain_id: {}", chain_id);
+    state
+        .metrics
+        .add_rpc_call_policy_outcome(chain_id.clone(), policy.name.clone(), "failed");
     Err(RpcError::ChainTemporarilyUnavailable(chain_id))
 }
 
-#[tracing::instrument(skip(state), level = "debug")]
+#[tracing::instrument(skip(state, geo), level = "debug")]
 pub async fn rpc_provider_call(
     state: Arc<AppState>,
-    addr: SocketAddr,
     query_params: RpcQueryParams,
     headers: HeaderMap,
-    body: Bytes,
-    provider: Arc<dyn crate::providers::RpcProvider>,
+    geo: Option<&geoip::Data>,
+    request: &RequestEnvelope,
+    provider: Arc<dyn RpcProvider>,
+    call_timeout: Duration,
 ) -> Result<Response, RpcError> {
     Span::current().record("provider", provider.provider_kind().to_string());
     let chain_id = query_params.chain_id.clone();
@@ -144,16 +373,20 @@ pub async fn rpc_provider_call(
         .map(|v| v.to_str().unwrap_or("invalid_header").to_string());
 
     state.metrics.add_rpc_call(chain_id.clone());
-    if let Ok(rpc_request) = serde_json::from_slice(&body) {
-        let (country, continent, region) = state
-            .analytics
-            .lookup_geo_data(network::get_forwarded_ip(headers).unwrap_or_else(|| addr.ip()))
-            .map(|geo| (geo.country, geo.continent, geo.region))
+    if let Some(rpc_request) = request.single() {
+        let (country, continent, region) = geo
+            .map(|geo| {
+                (
+                    geo.country.clone(),
+                    geo.continent.clone(),
+                    geo.region.clone(),
+                )
+            })
             .unwrap_or((None, None, None));
 
         state.analytics.message(MessageInfo::new(
             &query_params,
-            &rpc_request,
+            rpc_request.method.clone(),
             region,
             country,
             continent,
@@ -166,10 +399,16 @@ pub async fn rpc_provider_call(
 
     // Start timing external provider added time
     let external_call_start = SystemTime::now();
+    // Streamed response bodies are bounded by the same call timeout
+    let call_deadline = tokio::time::Instant::now() + call_timeout;
 
-    let proxy_fut = provider.proxy(&chain_id, body);
-    let timeout_fut = timeout(PROVIDER_PROXY_CALL_TIMEOUT, proxy_fut);
-    let mut response = timeout_fut
+    let proxy_fut = provider.proxy_regional(
+        &chain_id,
+        request.body().clone(),
+        geo.and_then(|geo| geo.continent.as_deref()),
+    );
+    let timeout_fut = timeout(call_timeout, proxy_fut);
+    let proxy_result = timeout_fut
         .await
         .tap_err(|e| {
             warn!(
@@ -178,22 +417,43 @@ pub async fn rpc_provider_call(
                 e
             );
         })
-        .map_err(RpcError::ProxyTimeoutError)?
-        .tap_err(|e| {
-            warn!(
-                "Failed call to provider: {} with {}",
-                provider.provider_kind(),
-                e
-            );
-        })?;
+        .map_err(RpcError::ProxyTimeoutError)
+        .and_then(|response| {
+            response.tap_err(|e| {
+                warn!(
+                    "Failed call to provider: {} with {}",
+                    provider.provider_kind(),
+                    e
+                );
+            })
+        });
+    if proxy_result.is_err() {
+        state.providers.call_stats.record(
+            provider.provider_kind(),
+            &chain_id,
+            false,
+            external_call_start.elapsed().unwrap_or_default(),
+        );
+    }
+    let mut response = with_body_deadline(proxy_result?, call_deadline);
 
     state.metrics.add_status_code_for_provider(
         provider.provider_kind(),
         response.status().as_u16(),
-        Some(chain_id),
+        Some(chain_id.clone()),
         None,
     );
 
+    // Requests are billed by the provider once it responded
+    let request_cost = state
+        .providers
+        .get_request_cost(&chain_id, provider.provider_kind());
+    if request_cost > 0 {
+        state
+            .metrics
+            .add_provider_cost(&provider.provider_kind(), chain_id.clone(), request_cost);
+    }
+
     if provider.is_rate_limited(&mut response).await {
         state
             .metrics
@@ -201,6 +461,15 @@ pub async fn rpc_provider_call(
         *response.status_mut() = http::StatusCode::SERVICE_UNAVAILABLE;
     }
 
+    state.providers.call_stats.record(
+        provider.provider_kind(),
+        &chain_id,
+        matches!(
+            response.status(),
+            http::StatusCode::OK | http::StatusCode::BAD_REQUEST
+        ),
+        external_call_start.elapsed().unwrap_or_default(),
+    );
     state
         .metrics
         .add_external_http_latency(provider.provider_kind(), external_call_start, None);
@@ -211,10 +480,9 @@ pub async fn rpc_provider_call(
         }
         _ => {
             error!(
-                "Call to provider '{}' failed with status '{}' and body '{:?}'",
+                "Call to provider '{}' failed with status '{}'",
                 provider.provider_kind(),
-                response.status(),
-                response.body()
+                response.status()
             );
             state.metrics.add_failed_provider_call(provider.borrow());
             *response.status_mut() = http::StatusCode::SERVICE_UNAVAILABLE;
@@ -0,0 +1,510 @@
+use {
+    super::{
+        identity::{
+            handle_contract_error, self_provider, EnsRegistry, MulticoinAddrResolver, SelfProvider,
+            ETHEREUM_MAINNET, L2_PRIMARY_NAME_CHAINS,
+        },
+        HANDLER_TASK_METRICS,
+    },
+    crate::{
+        analytics::MessageSource,
+        database::{helpers::get_name_and_addresses_by_name, types::SupportedNamespaces},
+        error::RpcError,
+        names::{ccip, utils::is_name_in_allowed_zones},
+        state::AppState,
+        utils::crypto,
+    },
+    axum::{
+        extract::{ConnectInfo, Path, Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    ens_normalize_rs::EnsNameNormalizer,
+    ethers::{
+        abi::{self, Address, ParamType},
+        contract::abigen,
+        providers::{ens, Provider},
+        types::{Bytes, H256},
+        utils::to_checksum,
+    },
+    futures_util::future,
+    hyper::HeaderMap,
+    once_cell::sync::Lazy,
+    serde::{Deserialize, Serialize},
+    sqlx::Error as SqlxError,
+    std::{collections::BTreeMap, iter, net::SocketAddr, sync::Arc},
+    tracing::debug,
+    wc::future::FutureExt,
+};
+
+/// ENSIP-15 names normalizer, the names are resolved in the normalized form
+static ENS_NAME_NORMALIZER: Lazy<EnsNameNormalizer> = Lazy::new(EnsNameNormalizer::default);
+
+/// ENSIP-10 `resolve(bytes,bytes)` interface id
+const ENSIP10_INTERFACE_ID: [u8; 4] = [0x90, 0x61, 0xb9, 0x23];
+/// ENSIP-19 coin type of the address for all of the EVM chains that have no
+/// chain-specific address record
+const DEFAULT_EVM_COIN_TYPE: u32 = 0x80000000;
+/// Non-EVM chains resolved by their ENSIP-11 coin types
+const NON_EVM_CHAINS: [(&str, u32); 2] = [
+    (
+        crypto::BITCOIN_MAINNET_CAIP2,
+        crypto::SLIP44_BITCOIN_COIN_TYPE,
+    ),
+    (
+        crypto::SOLANA_MAINNET_CAIP2,
+        crypto::SLIP44_SOLANA_COIN_TYPE,
+    ),
+];
+/// Maximum number of the name labels, the resolver of each parent name is
+/// looked up with a separate call
+const MAX_NAME_LABELS: usize = 10;
+
+abigen!(
+    ExtendedResolver,
+    r#"[
+        function supportsInterface(bytes4 interfaceId) external view returns (bool)
+        function resolve(bytes name, bytes data) external view returns (bytes)
+    ]"#,
+);
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveQueryParams {
+    pub project_id: String,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NameSource {
+    /// ENS registry on mainnet including the wildcard resolvers
+    Ens,
+    /// Names registered with the profile API
+    Internal,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveResponse {
+    pub name: String,
+    pub source: NameSource,
+    /// Addresses by the CAIP-2 chain id
+    pub addresses: BTreeMap<String, String>,
+    /// Resolver contract address of the ENS name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolver: Option<String>,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    connect_info: ConnectInfo<SocketAddr>,
+    query: Query<ResolveQueryParams>,
+    headers: HeaderMap,
+    name: Path<String>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, connect_info, query, headers, name)
+        .with_metrics(HANDLER_TASK_METRICS.with_name("resolve"))
+        .await
+}
+
+#[tracing::instrument(skip_all, level = "debug")]
+async fn handler_internal(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(connect_info): ConnectInfo<SocketAddr>,
+    Query(query): Query<ResolveQueryParams>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<Response, RpcError> {
+    state
+        .validate_project_access_and_quota(&query.project_id)
+        .await?;
+
+    let name = ENS_NAME_NORMALIZER
+        .normalize(&name)
+        .map_err(|_| RpcError::InvalidNameFormat(name))?;
+    let Some(dns_name) = dns_encode(&name).filter(|_| name.contains('.')) else {
+        return Err(RpcError::InvalidNameFormat(name));
+    };
+
+    let is_internal_name = state
+        .config
+        .names
+        .allowed_zones
+        .as_ref()
+        .is_some_and(|zones| is_name_in_allowed_zones(&name, zones.clone()));
+    let response = if is_internal_name {
+        resolve_internal_name(&state, &name).await?
+    } else {
+        let provider = self_provider(
+            state.clone(),
+            connect_info,
+            query.project_id,
+            ETHEREUM_MAINNET,
+            MessageSource::NameResolve,
+            headers,
+        );
+        resolve_ens_name(&provider, &name, dns_name.into()).await?
+    };
+
+    match response {
+        Some(response) => Ok(Json(response).into_response()),
+        None => Err(RpcError::NameNotFound(name)),
+    }
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+async fn resolve_internal_name(
+    state: &AppState,
+    name: &str,
+) -> Result<Option<ResolveResponse>, RpcError> {
+    let lookup_result = get_name_and_addresses_by_name(name.to_owned(), &state.postgres).await;
+    let name_addresses = match lookup_result {
+        Ok(name_addresses) => name_addresses,
+        Err(SqlxError::RowNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let addresses = name_addresses
+        .addresses
+        .into_iter()
+        .map(|(coin_type, address)| {
+            let chain_id = crypto::convert_coin_type_to_caip2(coin_type);
+            (chain_id, address.address)
+        })
+        .collect();
+    Ok(Some(ResolveResponse {
+        name: name_addresses.name,
+        source: NameSource::Internal,
+        addresses,
+        resolver: None,
+    }))
+}
+
+/// Forward resolution of the mainnet, the ENSIP-19 L2 chains and the non-EVM
+/// chains addresses including the wildcard resolvers and their offchain
+/// (CCIP-Read) lookups
+#[tracing::instrument(skip(provider, dns_name), level = "debug")]
+async fn resolve_ens_name(
+    provider: &Arc<Provider<SelfProvider>>,
+    name: &str,
+    dns_name: Bytes,
+) -> Result<Option<ResolveResponse>, RpcError> {
+    let Some((resolver_address, is_wildcard)) = find_resolver(provider, name).await? else {
+        return Ok(None);
+    };
+
+    let chains = iter::once(ETHEREUM_MAINNET)
+        .chain(L2_PRIMARY_NAME_CHAINS)
+        .filter_map(|chain_id| Some((chain_id, evm_chain_coin_type(chain_id)?)))
+        .chain(NON_EVM_CHAINS)
+        .collect::<Vec<_>>();
+    let node = ens::namehash(name);
+    let mut resolved = future::join_all(
+        chains
+            .iter()
+            .map(|(_, coin_type)| *coin_type)
+            .chain([DEFAULT_EVM_COIN_TYPE])
+            .map(|coin_type| {
+                resolve_address(
+                    provider,
+                    resolver_address,
+                    node,
+                    &dns_name,
+                    is_wildcard,
+                    coin_type,
+                )
+            }),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+    let default_address = resolved
+        .pop()
+        .flatten()
+        .and_then(|address| format_address(DEFAULT_EVM_COIN_TYPE, &address));
+
+    let addresses = chains
+        .iter()
+        .zip(resolved)
+        .filter_map(|((chain_id, coin_type), address)| {
+            let address = address
+                .and_then(|address| format_address(*coin_type, &address))
+                .or_else(|| {
+                    default_address
+                        .clone()
+                        .filter(|_| has_default_evm_address(*coin_type))
+                })?;
+            Some((chain_id.to_string(), address))
+        })
+        .collect::<BTreeMap<_, _>>();
+    if addresses.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ResolveResponse {
+        name: name.to_owned(),
+        source: NameSource::Ens,
+        addresses,
+        resolver: Some(to_checksum(&resolver_address, None)),
+    }))
+}
+
+/// Address of the name for the ENSIP-11 coin type, the address is resolved
+/// from the wildcard resolvers and their offchain lookups as well. The L2
+/// chains without the address record fall back to the default EVM address.
+#[tracing::instrument(skip(provider), level = "debug")]
+pub async fn resolve_ens_address(
+    provider: &Arc<Provider<SelfProvider>>,
+    name: &str,
+    coin_type: u32,
+) -> Result<Option<Address>, RpcError> {
+    let Some(dns_name) = dns_encode(name) else {
+        return Ok(None);
+    };
+    let Some((resolver_address, is_wildcard)) = find_resolver(provider, name).await? else {
+        return Ok(None);
+    };
+    let dns_name = dns_name.into();
+    let node = ens::namehash(name);
+    let address = resolve_address(
+        provider,
+        resolver_address,
+        node,
+        &dns_name,
+        is_wildcard,
+        coin_type,
+    )
+    .await?
+    .and_then(|address| evm_address(&address));
+    if address.is_some() || !has_default_evm_address(coin_type) {
+        return Ok(address);
+    }
+    Ok(resolve_address(
+        provider,
+        resolver_address,
+        node,
+        &dns_name,
+        is_wildcard,
+        DEFAULT_EVM_COIN_TYPE,
+    )
+    .await?
+    .and_then(|address| evm_address(&address)))
+}
+
+/// ENSIP-10 resolver lookup, the resolver of the closest parent name is used
+/// if the name has no resolver and the parent resolver supports the wildcard
+/// resolution. Returns the resolver and whether it is the wildcard resolver.
+async fn find_resolver(
+    provider: &Arc<Provider<SelfProvider>>,
+    name: &str,
+) -> Result<Option<(Address, bool)>, RpcError> {
+    if name.split('.').count() > MAX_NAME_LABELS {
+        debug!("Name {name} has too many labels to look up the resolver");
+        return Ok(None);
+    }
+    let registry = EnsRegistry::new(ens::ENS_ADDRESS, provider.clone());
+    let mut resolver_lookup = None;
+    for (index, name) in name_and_parents(name).enumerate() {
+        match registry.resolver(ens::namehash(name).0).call().await {
+            Ok(resolver) if !resolver.is_zero() => {
+                resolver_lookup = Some((resolver, index == 0));
+                break;
+            }
+            Ok(_) => {}
+            Err(error) => {
+                handle_contract_error(error)?;
+                return Ok(None);
+            }
+        }
+    }
+    let Some((resolver_address, is_exact)) = resolver_lookup else {
+        return Ok(None);
+    };
+
+    let resolver = ExtendedResolver::new(resolver_address, provider.clone());
+    let is_wildcard = match resolver
+        .supports_interface(ENSIP10_INTERFACE_ID)
+        .call()
+        .await
+    {
+        Ok(is_wildcard) => is_wildcard,
+        Err(error) => {
+            handle_contract_error(error)?;
+            false
+        }
+    };
+    // Resolvers of the parent names are used only if they support the
+    // wildcard resolution
+    if !is_exact && !is_wildcard {
+        debug!("Resolver of the {name} parent name is not the wildcard resolver");
+        return Ok(None);
+    }
+    Ok(Some((resolver_address, is_wildcard)))
+}
+
+/// ENSIP-9 binary address record of the coin type, the wildcard resolvers are
+/// called with the ENSIP-10 `resolve` wrapping the `addr` call
+async fn resolve_address(
+    provider: &Arc<Provider<SelfProvider>>,
+    resolver_address: Address,
+    node: H256,
+    dns_name: &Bytes,
+    is_wildcard: bool,
+    coin_type: u32,
+) -> Result<Option<Vec<u8>>, RpcError> {
+    let addr_call = MulticoinAddrResolver::new(resolver_address, provider.clone())
+        .addr(node.0, coin_type.into())
+        .calldata()
+        .expect("Address call should have the calldata");
+    let calldata = if is_wildcard {
+        ExtendedResolver::new(resolver_address, provider.clone())
+            .resolve(dns_name.clone(), addr_call)
+            .calldata()
+            .expect("Resolve call should have the calldata")
+    } else {
+        addr_call
+    };
+    let Some(result) = ccip::call(provider, resolver_address, calldata).await? else {
+        return Ok(None);
+    };
+
+    // Result of the `resolve` call is the ABI encoded result of the wrapped call
+    let result = if is_wildcard {
+        decode_bytes(&result)
+    } else {
+        Some(result.to_vec())
+    };
+    Ok(result
+        .and_then(|result| decode_bytes(&result))
+        .filter(|address| !address.is_empty()))
+}
+
+/// Text form of the ENSIP-9 binary address by the coin type namespace, the
+/// inverse of the gateway addresses encoding
+fn format_address(coin_type: u32, address: &[u8]) -> Option<String> {
+    match SupportedNamespaces::from_slip44(coin_type)? {
+        SupportedNamespaces::Eip155 => {
+            evm_address(address).map(|address| to_checksum(&address, None))
+        }
+        SupportedNamespaces::Solana => {
+            (address.len() == 32).then(|| bs58::encode(address).into_string())
+        }
+        SupportedNamespaces::Bip122 => crypto::bitcoin_address(address),
+    }
+}
+
+fn evm_address(address: &[u8]) -> Option<Address> {
+    Some(address)
+        .filter(|address| address.len() == Address::len_bytes())
+        .map(Address::from_slice)
+        .filter(|address| !address.is_zero())
+}
+
+fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+    abi::decode(&[ParamType::Bytes], data)
+        .ok()?
+        .into_iter()
+        .next()?
+        .into_bytes()
+}
+
+/// ENSIP-11 coin type of the EVM chain
+fn evm_chain_coin_type(chain_id: &str) -> Option<u32> {
+    let (_, reference) = crypto::disassemble_caip2(chain_id).ok()?;
+    let evm_chain_id = reference.parse::<u32>().ok()?;
+    Some(crypto::convert_evm_chain_id_to_coin_type(evm_chain_id))
+}
+
+/// Whether the ENSIP-19 default EVM address is used for the coin type without
+/// the address record, the mainnet address is never taken from it
+fn has_default_evm_address(coin_type: u32) -> bool {
+    coin_type > DEFAULT_EVM_COIN_TYPE
+}
+
+/// The name followed by all of its parent names
+fn name_and_parents(name: &str) -> impl Iterator<Item = &str> {
+    iter::successors(Some(name), |name| {
+        name.split_once('.').map(|(_, parent)| parent)
+    })
+}
+
+/// DNS wire format of the name for the ENSIP-10 `resolve` call, names with
+/// the empty or too long labels can't be encoded
+fn dns_encode(name: &str) -> Option<Vec<u8>> {
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.') {
+        let length = u8::try_from(label.len())
+            .ok()
+            .filter(|length| *length > 0)?;
+        encoded.push(length);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    Some(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dns_encode_name() {
+        assert_eq!(
+            dns_encode("vitalik.eth"),
+            Some(b"\x07vitalik\x03eth\x00".to_vec())
+        );
+        assert_eq!(dns_encode("vitalik..eth"), None);
+        assert_eq!(dns_encode(&format!("{}.eth", "a".repeat(256))), None);
+    }
+
+    #[test]
+    fn normalizes_names() {
+        assert_eq!(
+            ENS_NAME_NORMALIZER.normalize("Nick.ETH").unwrap(),
+            "nick.eth"
+        );
+        assert!(ENS_NAME_NORMALIZER.normalize("nick..eth").is_err());
+    }
+
+    #[test]
+    fn formats_addresses() {
+        let evm_address = hex::decode("d8da6bf26964af9d7eed9e03e53415d37aa96045").unwrap();
+        assert_eq!(
+            format_address(60, &evm_address).as_deref(),
+            Some("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045")
+        );
+        assert_eq!(format_address(60, &[0; 20]), None);
+        assert_eq!(format_address(0x80000000 | 10, &evm_address[1..]), None);
+
+        let script = hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert_eq!(
+            format_address(crypto::SLIP44_BITCOIN_COIN_TYPE, &script).as_deref(),
+            Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa")
+        );
+        assert_eq!(
+            format_address(crypto::SLIP44_SOLANA_COIN_TYPE, &[1; 32]),
+            Some(bs58::encode([1; 32]).into_string())
+        );
+        assert_eq!(
+            format_address(crypto::SLIP44_SOLANA_COIN_TYPE, &[1; 20]),
+            None
+        );
+    }
+
+    #[test]
+    fn parent_names() {
+        assert_eq!(
+            name_and_parents("sub.name.eth").collect::<Vec<_>>(),
+            vec!["sub.name.eth", "name.eth", "eth"]
+        );
+    }
+
+    #[test]
+    fn coin_types_to_chains() {
+        assert_eq!(evm_chain_coin_type("eip155:1"), Some(60));
+        assert_eq!(evm_chain_coin_type("eip155:10"), Some(0x80000000 | 10));
+        assert!(has_default_evm_address(0x80000000 | 10));
+        assert!(!has_default_evm_address(60));
+        assert!(!has_default_evm_address(DEFAULT_EVM_COIN_TYPE));
+    }
+}
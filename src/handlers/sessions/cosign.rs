@@ -1,13 +1,13 @@
 use {
     super::{super::HANDLER_TASK_METRICS, CoSignRequest, StoragePermissionsItem},
     crate::{
+        chains,
         error::RpcError,
         state::AppState,
         storage::irn::OperationType,
         utils::crypto::{
             abi_encode_two_bytes_arrays, call_get_user_op_hash, disassemble_caip10,
-            is_address_valid, pack_signature, to_eip191_message, CaipNamespaces, ChainId,
-            UserOperation,
+            is_address_valid, pack_signature, to_eip191_message, CaipNamespaces, UserOperation,
         },
     },
     axum::{
@@ -92,7 +92,7 @@ async fn handler_internal(
     let chain_id_uint = chain_id
         .parse::<u64>()
         .map_err(|_| RpcError::InvalidChainIdFormat(chain_id.clone()))?;
-    if !ChainId::is_supported(chain_id_uint) {
+    if !chains::is_evm_chain_supported(chain_id_uint) {
         return Err(RpcError::UnsupportedChain(chain_id.clone()));
     }
 
@@ -1,13 +1,78 @@
 use {
     super::HANDLER_TASK_METRICS,
-    crate::{error::RpcError, providers::SupportedChains, state::AppState},
-    axum::{extract::State, Json},
-    std::sync::Arc,
+    crate::{
+        chains::{self, NativeCurrency},
+        error::RpcError,
+        providers::SupportedChains,
+        state::AppState,
+        utils::crypto::CaipNamespaces,
+    },
+    axum::{
+        extract::{Query, State},
+        response::{IntoResponse, Response},
+        Json,
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{BTreeSet, HashMap},
+        str::FromStr,
+        sync::Arc,
+    },
     wc::future::FutureExt,
 };
 
-pub async fn handler(state: State<Arc<AppState>>) -> Result<Json<SupportedChains>, RpcError> {
-    handler_internal(state)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedChainsQueryParams {
+    /// Optional version parameter, the version `2` responds with the chains
+    /// metadata
+    pub api_version: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupportedChainsResponse {
+    #[serde(flatten)]
+    pub chains: SupportedChains,
+    /// Human-readable aliases by the supported CAIP-2 chain id
+    pub aliases: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupportedChainsResponseV2 {
+    pub chains: Vec<ChainMetadata>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainMetadata {
+    pub chain_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_currency: Option<NativeCurrency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_explorer_url: Option<&'static str>,
+    pub testnet: bool,
+    pub aliases: Vec<&'static str>,
+    pub capabilities: ChainCapabilities,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainCapabilities {
+    pub http: bool,
+    pub ws: bool,
+    pub archive: bool,
+    pub bundler: bool,
+    pub balance: bool,
+    pub history: bool,
+    pub convert: bool,
+}
+
+pub async fn handler(
+    state: State<Arc<AppState>>,
+    query: Query<SupportedChainsQueryParams>,
+) -> Result<Response, RpcError> {
+    handler_internal(state, query)
         .with_metrics(HANDLER_TASK_METRICS.with_name("supported_chains"))
         .await
 }
@@ -15,6 +80,80 @@ pub async fn handler(state: State<Arc<AppState>>) -> Result<Json<SupportedChains
 #[tracing::instrument(skip_all, level = "debug")]
 async fn handler_internal(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<SupportedChains>, RpcError> {
-    Ok(Json(state.providers.supported_chains.clone()))
+    Query(query): Query<SupportedChainsQueryParams>,
+) -> Result<Response, RpcError> {
+    let supported_chains = &state.providers.supported_chains;
+    if query.api_version == Some(2) {
+        return Ok(Json(SupportedChainsResponseV2 {
+            chains: chains_metadata(&state),
+        })
+        .into_response());
+    }
+
+    let aliases = supported_chains
+        .http
+        .iter()
+        .chain(supported_chains.ws.iter())
+        .filter_map(|chain_id| {
+            let chain = chains::get_chain_info(chain_id)?;
+            Some((
+                chain_id.clone(),
+                chain
+                    .aliases
+                    .iter()
+                    .map(|alias| alias.to_string())
+                    .collect(),
+            ))
+        })
+        .collect();
+    Ok(Json(SupportedChainsResponse {
+        chains: supported_chains.clone(),
+        aliases,
+    })
+    .into_response())
+}
+
+/// Aggregates the registry metadata with the capabilities of the providers
+/// serving each of the supported chains
+fn chains_metadata(state: &AppState) -> Vec<ChainMetadata> {
+    let providers = &state.providers;
+    let supported_chains = &providers.supported_chains;
+    let chain_ids = supported_chains
+        .http
+        .iter()
+        .chain(supported_chains.ws.iter())
+        .collect::<BTreeSet<_>>();
+
+    chain_ids
+        .into_iter()
+        .map(|chain_id| {
+            let chain = chains::get_chain_info(chain_id);
+            let namespace = chain_id
+                .split_once(':')
+                .and_then(|(namespace, _)| CaipNamespaces::from_str(namespace).ok());
+            ChainMetadata {
+                chain_id: chain_id.clone(),
+                name: chain.map(|chain| chain.name),
+                native_currency: chain.map(|chain| chain.native_currency),
+                block_explorer_url: chain.map(|chain| chain.explorer_url),
+                testnet: chain.is_some_and(|chain| chain.testnet),
+                aliases: chain
+                    .map(|chain| chain.aliases.to_vec())
+                    .unwrap_or_default(),
+                capabilities: ChainCapabilities {
+                    http: supported_chains.http.contains(chain_id),
+                    ws: supported_chains.is_ws_supported(chain_id),
+                    archive: supported_chains.archive.contains(chain_id),
+                    bundler: providers.bundler_ops_provider.is_chain_supported(chain_id),
+                    balance: namespace.is_some_and(|namespace| {
+                        providers.balance_providers.contains_key(&namespace)
+                    }),
+                    history: namespace.is_some_and(|namespace| {
+                        providers.history_providers.contains_key(&namespace)
+                    }),
+                    convert: providers.conversion_provider.is_chain_supported(chain_id),
+                },
+            }
+        })
+        .collect()
 }
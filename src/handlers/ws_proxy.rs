@@ -1,21 +1,33 @@
 use {
     super::{RpcQueryParams, HANDLER_TASK_METRICS},
-    crate::{error::RpcError, state::AppState},
+    crate::{
+        error::RpcError,
+        providers::WS_PROXY_TASK_METRICS,
+        state::AppState,
+        ws::{self, inspector::FrameInspector},
+    },
     axum::{
-        extract::{Query, State},
+        extract::{ConnectInfo, Query, State},
         response::Response,
     },
     axum_tungstenite::WebSocketUpgrade,
-    std::sync::Arc,
+    hyper::HeaderMap,
+    std::{net::SocketAddr, sync::Arc},
+    tracing::warn,
     wc::future::FutureExt,
 };
 
+/// Maximum number of the WebSocket providers to try connecting to
+const WS_PROVIDER_MAX_CONNECTS: usize = 3;
+
 pub async fn handler(
     state: State<Arc<AppState>>,
+    addr: ConnectInfo<SocketAddr>,
     query_params: Query<RpcQueryParams>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Result<Response, RpcError> {
-    handler_internal(state, query_params, ws)
+    handler_internal(state, addr, query_params, headers, ws)
         .with_metrics(HANDLER_TASK_METRICS.with_name("ws_proxy"))
         .await
 }
@@ -23,20 +35,70 @@ pub async fn handler(
 #[tracing::instrument(skip_all, level = "debug")]
 async fn handler_internal(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Query(query_params): Query<RpcQueryParams>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Result<Response, RpcError> {
     state
         .validate_project_access_and_quota(&query_params.project_id)
         .await?;
 
-    let chain_id = query_params.chain_id.to_lowercase();
-    let provider = state
+    let chain_id = query_params.chain_id.clone();
+    let providers = state
         .providers
-        .get_ws_provider_for_chain_id(&chain_id)
-        .ok_or(RpcError::UnsupportedChain(chain_id.clone()))?;
+        .get_ws_provider_for_chain_id(&chain_id, WS_PROVIDER_MAX_CONNECTS);
+    for provider in providers.iter() {
+        let upstream = match provider.connect(&chain_id, state.metrics.clone()).await {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                warn!(
+                    "Failed to connect to the WebSocket provider '{}' for chain {chain_id}: {e}, \
+                     trying the next provider",
+                    provider.provider_kind()
+                );
+                continue;
+            }
+        };
+        state.metrics.add_websocket_connection(chain_id);
 
-    state.metrics.add_websocket_connection(chain_id);
+        let project_id = query_params.project_id.clone();
+        let inspector = FrameInspector::new(
+            state,
+            query_params,
+            Some(provider.provider_kind()),
+            addr,
+            &headers,
+        );
+        return Ok(ws.on_upgrade(move |socket| {
+            ws::proxy(project_id, socket, upstream, inspector)
+                .with_metrics(WS_PROXY_TASK_METRICS.with_name("pooled"))
+        }));
+    }
+
+    // Emulate the WebSocket interface over the HTTP providers for the EVM
+    // chains that have no native WebSocket provider or when all of the
+    // WebSocket providers failed to connect
+    if !state
+        .providers
+        .supported_chains
+        .is_ws_emulation_supported(&chain_id)
+    {
+        if !providers.is_empty() {
+            return Err(RpcError::ChainTemporarilyUnavailable(chain_id));
+        }
+        return Err(RpcError::UnsupportedChain(chain_id));
+    }
+    state.metrics.add_websocket_connection(chain_id.clone());
+    let query_params = RpcQueryParams {
+        chain_id,
+        ..query_params
+    };
 
-    provider.proxy(ws, query_params).await
+    // Requests are recorded in the analytics by the HTTP proxy path
+    let inspector = FrameInspector::new(state.clone(), query_params.clone(), None, addr, &headers);
+    Ok(ws.on_upgrade(move |socket| {
+        ws::emulated::proxy(state, addr, query_params, headers, socket, inspector)
+            .with_metrics(WS_PROXY_TASK_METRICS.with_name("emulated"))
+    }))
 }
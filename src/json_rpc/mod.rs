@@ -5,9 +5,11 @@
 
 use {
     derive_more::{Display, From, Into},
+    hyper::body::Bytes,
     serde::{Deserialize, Serialize},
     serde_aux::prelude::deserialize_string_from_number,
-    std::sync::Arc,
+    serde_json::value::RawValue,
+    std::{ops::Range, sync::Arc},
 };
 
 #[cfg(test)]
@@ -131,3 +133,97 @@ pub struct ErrorResponse<T: Serialize> {
     /// Error data, if any.
     pub data: T,
 }
+
+/// Lightweight view of the JSON-RPC request body that is parsed once per
+/// proxied request and shared by the routing and analytics. The params are
+/// not deserialized, only their span in the raw body is kept, and the raw
+/// body is forwarded to the providers as is.
+#[derive(Debug, Clone)]
+pub struct RequestEnvelope {
+    body: Bytes,
+    requests: Vec<EnvelopeRequest>,
+    is_batch: bool,
+}
+
+/// Single JSON-RPC request of the envelope.
+#[derive(Debug, Clone)]
+pub struct EnvelopeRequest {
+    /// ID of the request, notifications have no ID.
+    pub id: Option<MessageId>,
+    /// The RPC method.
+    pub method: Arc<str>,
+    /// Span of the raw params in the request body.
+    pub params: Option<Range<usize>>,
+}
+
+#[derive(Deserialize)]
+struct RawRequest<'a> {
+    #[serde(default)]
+    id: Option<MessageId>,
+    method: Arc<str>,
+    #[serde(borrow, default)]
+    params: Option<&'a RawValue>,
+}
+
+impl RequestEnvelope {
+    /// Parses the request body, bodies that are not valid JSON-RPC requests
+    /// are kept to be forwarded with no parsed requests.
+    pub fn parse(body: Bytes) -> Self {
+        let is_batch = body
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .is_some_and(|byte| *byte == b'[');
+        let raw_requests = if is_batch {
+            serde_json::from_slice::<Vec<RawRequest>>(&body).ok()
+        } else {
+            serde_json::from_slice::<RawRequest>(&body)
+                .ok()
+                .map(|request| vec![request])
+        };
+        let requests = raw_requests
+            .unwrap_or_default()
+            .into_iter()
+            .map(|request| EnvelopeRequest {
+                id: request.id,
+                method: request.method,
+                params: request.params.map(|params| {
+                    // Raw params are borrowed from the body
+                    let start = params.get().as_ptr() as usize - body.as_ptr() as usize;
+                    start..start + params.get().len()
+                }),
+            })
+            .collect();
+
+        Self {
+            body,
+            requests,
+            is_batch,
+        }
+    }
+
+    /// Raw request body.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The single request, batches have no single request.
+    pub fn single(&self) -> Option<&EnvelopeRequest> {
+        if self.is_batch {
+            return None;
+        }
+        self.requests.first()
+    }
+
+    /// Method of the single request.
+    pub fn method(&self) -> Option<&str> {
+        self.single().map(|request| request.method.as_ref())
+    }
+
+    /// Raw params of the request.
+    pub fn params(&self, request: &EnvelopeRequest) -> Option<&[u8]> {
+        request
+            .params
+            .as_ref()
+            .and_then(|params| self.body.get(params.clone()))
+    }
+}
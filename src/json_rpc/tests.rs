@@ -82,3 +82,31 @@ fn test_deserialize_iridium_method() {
                       test_message\"}}}";
     assert!(serde_json::from_str::<'_, JsonRpcPayload>(serialized).is_ok());
 }
+
+#[test]
+fn test_request_envelope() {
+    let envelope = RequestEnvelope::parse(Bytes::from_static(
+        br#"{"id":1,"jsonrpc":"2.0","method":"eth_getBalance","params":["0x1", "latest"]}"#,
+    ));
+    let request = envelope.single().unwrap();
+    assert_eq!(request.id, Some(MessageId("1".into())));
+    assert_eq!(envelope.method(), Some("eth_getBalance"));
+    assert_eq!(
+        envelope.params(request),
+        Some(br#"["0x1", "latest"]"#.as_slice())
+    );
+
+    let batch = RequestEnvelope::parse(Bytes::from_static(
+        br#" [{"id":1,"jsonrpc":"2.0","method":"eth_chainId"},
+              {"id":"a","jsonrpc":"2.0","method":"eth_blockNumber","params":[]}]"#,
+    ));
+    assert!(batch.single().is_none());
+    assert_eq!(batch.method(), None);
+    assert_eq!(batch.requests.len(), 2);
+    assert_eq!(batch.params(&batch.requests[0]), None);
+    assert_eq!(batch.params(&batch.requests[1]), Some(b"[]".as_slice()));
+
+    let invalid = RequestEnvelope::parse(Bytes::from_static(b"not a json"));
+    assert!(invalid.requests.is_empty());
+    assert_eq!(invalid.body().as_ref(), b"not a json");
+}
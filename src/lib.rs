@@ -3,6 +3,7 @@ use {
         env::Config,
         handlers::{identity::IdentityResponse, rate_limit_middleware},
         metrics::Metrics,
+        names::sources::IdentitySources,
         project::Registry,
         providers::ProvidersConfig,
         storage::{irn, redis, KeyValueStorage},
@@ -14,7 +15,7 @@ use {
         extract::connect_info::IntoMakeServiceWithConnectInfo,
         middleware,
         response::Response,
-        routing::{get, post},
+        routing::{delete, get, post},
         Router,
     },
     env::{
@@ -27,9 +28,9 @@ use {
     hyper::{header::HeaderName, http, server::conn::AddrIncoming, Body, Server},
     providers::{
         AuroraProvider, BaseProvider, BerachainProvider, BinanceProvider, GetBlockProvider,
-        InfuraProvider, InfuraWsProvider, MantleProvider, NearProvider, PoktProvider,
-        ProviderRepository, PublicnodeProvider, QuicknodeProvider, ZKSyncProvider, ZoraProvider,
-        ZoraWsProvider,
+        GetBlockWsProvider, InfuraProvider, InfuraWsProvider, MantleProvider, NearProvider,
+        PoktProvider, ProviderRepository, PublicnodeProvider, PublicnodeWsProvider,
+        QuicknodeProvider, QuicknodeWsProvider, ZKSyncProvider, ZoraProvider, ZoraWsProvider,
     },
     sqlx::postgres::PgPoolOptions,
     std::{
@@ -58,6 +59,7 @@ use {
 const DB_STATS_POLLING_INTERVAL: Duration = Duration::from_secs(3600);
 
 mod analytics;
+pub mod chains;
 pub mod database;
 pub mod env;
 pub mod error;
@@ -79,6 +81,11 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
     let s3_client = get_s3_client(&config).await;
     let geoip_resolver = get_geoip_resolver(&config, &s3_client).await;
 
+    let names_gateway_signer = config.names.gateway_signer()?;
+    let identity_sources = IdentitySources::new(
+        config.names.identity_sources.as_deref(),
+        config.names.identity_sources_priority.as_deref(),
+    )?;
     let metrics = Arc::new(Metrics::new());
     let registry = Registry::new(&config.registry, &config.storage)?;
 
@@ -127,7 +134,7 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .transpose()?
         .map(|r| Arc::new(r) as Arc<dyn KeyValueStorage<IdentityResponse> + 'static>);
 
-    let providers = init_providers(&config.providers);
+    let providers = init_providers(&config.providers, metrics.clone());
 
     let external_ip = config
         .server
@@ -158,7 +165,7 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .await?;
     sqlx::migrate!("./migrations").run(&postgres).await?;
 
-    let http_client = reqwest::Client::new();
+    let http_client = providers.api_http_client.clone();
     let irn_client =
         if let (Some(node_addr), Some(key_base64), Some(namespace), Some(namespace_secret)) = (
             config.irn.node.clone(),
@@ -188,6 +195,8 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         http_client,
         rate_limiting,
         irn_client,
+        identity_sources,
+        names_gateway_signer,
     );
 
     let port = state.config.server.port;
@@ -248,6 +257,11 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
         .route("/v1/supported-chains", get(handlers::supported_chains::handler))
         .route("/ws", get(handlers::ws_proxy::handler))
         .route("/v1/identity/:address", get(handlers::identity::handler))
+        .route(
+            "/v1/identity/batch",
+            post(handlers::identity::batch_handler),
+        )
+        .route("/v1/avatar/:address", get(handlers::avatar::handler))
         .route(
             "/v1/account/:address/identity",
             get(handlers::identity::handler),
@@ -279,6 +293,11 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
             "/v1/profile/account/:name/address",
             post(handlers::profile::address::handler),
         )
+        // Transfer account name to the new owner
+        .route(
+            "/v1/profile/account/:name/transfer",
+            post(handlers::profile::transfer::handler),
+        )
         // Forward address lookup
         .route(
             "/v1/profile/account/:name",
@@ -294,6 +313,17 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
             "/v1/profile/suggestions/:name",
             get(handlers::profile::suggestions::handler),
         )
+        // CCIP-Read gateway of the names offchain resolver
+        .route(
+            "/v1/profile/gateway",
+            post(handlers::profile::gateway::post_handler),
+        )
+        .route(
+            "/v1/profile/gateway/:sender/:data",
+            get(handlers::profile::gateway::get_handler),
+        )
+        // Forward name resolution across ENS and the internal names
+        .route("/v1/resolve/:name", get(handlers::resolve::handler))
         // Generators
         .route(
             "/v1/generators/onrampurl",
@@ -383,6 +413,23 @@ pub async fn bootstrap(config: Config) -> RpcResult<()> {
 
     let private_app = Router::new()
         .route("/metrics", get(handlers::metrics::handler))
+        .route(
+            "/admin/providers",
+            get(handlers::admin::providers::status_handler),
+        )
+        .route(
+            "/admin/providers/dry-run",
+            get(handlers::admin::providers::dry_run_handler),
+        )
+        .route(
+            "/admin/providers/overrides",
+            post(handlers::admin::providers::override_handler)
+                .delete(handlers::admin::providers::reset_overrides_handler),
+        )
+        .route(
+            "/admin/identity/:address/cache",
+            delete(handlers::admin::identity::invalidate_handler),
+        )
         .with_state(state_arc.clone());
 
     let public_server = create_server(app, &addr);
@@ -457,8 +504,8 @@ fn create_server(
     axum::Server::bind(addr).serve(app.into_make_service_with_connect_info::<SocketAddr>())
 }
 
-fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
-    let mut providers = ProviderRepository::new(config);
+fn init_providers(config: &ProvidersConfig, metrics: Arc<Metrics>) -> ProviderRepository {
+    let mut providers = ProviderRepository::new(config, metrics);
 
     // Keep in-sync with SUPPORTED_CHAINS.md
 
@@ -482,15 +529,27 @@ fn init_providers(config: &ProvidersConfig) -> ProviderRepository {
     providers.add_provider::<BerachainProvider, BerachainConfig>(BerachainConfig::default());
 
     if let Some(getblock_access_tokens) = &config.getblock_access_tokens {
-        providers.add_provider::<GetBlockProvider, GetBlockConfig>(GetBlockConfig::new(
-            getblock_access_tokens.clone(),
-        ));
+        providers.add_provider::<GetBlockProvider, GetBlockConfig>(
+            GetBlockConfig::new(getblock_access_tokens.clone())
+                .with_regional_access_tokens(config.getblock_regional_access_tokens.as_deref()),
+        );
     };
 
     providers.add_ws_provider::<InfuraWsProvider, InfuraConfig>(InfuraConfig::new(
         config.infura_project_id.clone(),
     ));
     providers.add_ws_provider::<ZoraWsProvider, ZoraConfig>(ZoraConfig::default());
+    providers
+        .add_ws_provider::<PublicnodeWsProvider, PublicnodeConfig>(PublicnodeConfig::default());
+    providers.add_ws_provider::<QuicknodeWsProvider, QuicknodeConfig>(QuicknodeConfig::new(
+        config.quicknode_api_tokens.clone(),
+    ));
+
+    if let Some(getblock_ws_access_tokens) = &config.getblock_ws_access_tokens {
+        providers.add_ws_provider::<GetBlockWsProvider, GetBlockConfig>(GetBlockConfig::new_ws(
+            getblock_ws_access_tokens.clone(),
+        ));
+    };
 
     providers
 }
@@ -24,6 +24,7 @@ use {
 pub struct Metrics {
     pub rpc_call_counter: Counter<u64>,
     pub rpc_call_retries: Histogram<u64>,
+    pub rpc_call_policy_counter: Counter<u64>,
     pub http_call_counter: Counter<u64>,
     pub provider_finished_call_counter: Counter<u64>,
     pub provider_failed_call_counter: Counter<u64>,
@@ -46,7 +47,22 @@ pub struct Metrics {
     pub identity_lookup_avatar_latency_tracker: Histogram<f64>,
     pub identity_lookup_avatar_present_counter: Counter<u64>,
     pub identity_lookup_name_present_counter: Counter<u64>,
+    pub identity_cache_refresh_counter: Counter<u64>,
+    pub identity_source_lookup_counter: Counter<u64>,
+    pub identity_source_lookup_latency_tracker: Histogram<f64>,
+    pub identity_batch_size: Histogram<u64>,
+    pub identity_batch_item_counter: Counter<u64>,
+    pub avatar_proxy_counter: Counter<u64>,
     pub websocket_connection_counter: Counter<u64>,
+    pub ws_upstream_pool_size: Histogram<u64>,
+    pub ws_client_subscriptions: Histogram<u64>,
+    pub ws_message_counter: Counter<u64>,
+    pub ws_limited_message_counter: Counter<u64>,
+    pub shadow_call_counter: Counter<u64>,
+    pub shadow_call_latency_tracker: Histogram<f64>,
+    pub provider_cost_counter: Counter<u64>,
+    pub upstream_connection_counter: Counter<u64>,
+    pub upstream_connection_latency_tracker: Histogram<f64>,
     pub history_lookup_counter: Counter<u64>,
     pub history_lookup_success_counter: Counter<u64>,
     pub history_lookup_latency_tracker: Histogram<f64>,
@@ -86,6 +102,11 @@ impl Metrics {
             .with_description("Retries per RPC call")
             .init();
 
+        let rpc_call_policy_counter = meter
+            .u64_counter("rpc_call_policy_counter")
+            .with_description("The number of RPC calls by the applied call policy and outcome")
+            .init();
+
         let http_call_counter = meter
             .u64_counter("http_call_counter")
             .with_description("The number of http calls served")
@@ -196,11 +217,86 @@ impl Metrics {
             .with_description("The number of identity lookups that returned an avatar")
             .init();
 
+        let identity_cache_refresh_counter = meter
+            .u64_counter("identity_cache_refresh_counter")
+            .with_description("The number of background refreshes of the stale cached identities")
+            .init();
+
+        let identity_source_lookup_counter = meter
+            .u64_counter("identity_source_lookup_counter")
+            .with_description("The number of identity lookups by the identity source")
+            .init();
+
+        let identity_source_lookup_latency_tracker = meter
+            .f64_histogram("identity_source_lookup_latency_tracker")
+            .with_description("The latency of identity lookups by the identity source")
+            .init();
+
+        let identity_batch_size = meter
+            .u64_histogram("identity_batch_size")
+            .with_description("The number of addresses per identity batch lookup")
+            .init();
+
+        let identity_batch_item_counter = meter
+            .u64_counter("identity_batch_item_counter")
+            .with_description("The number of identity batch lookup addresses by result")
+            .init();
+
+        let avatar_proxy_counter = meter
+            .u64_counter("avatar_proxy_counter")
+            .with_description("The number of avatar proxy requests by success")
+            .init();
+
         let websocket_connection_counter = meter
             .u64_counter("websocket_connection_counter")
             .with_description("The number of websocket connections")
             .init();
 
+        let ws_upstream_pool_size = meter
+            .u64_histogram("ws_upstream_pool_size")
+            .with_description("The number of pooled upstream websocket connections")
+            .init();
+
+        let ws_client_subscriptions = meter
+            .u64_histogram("ws_client_subscriptions")
+            .with_description("The number of subscriptions per client websocket session")
+            .init();
+
+        let ws_message_counter = meter
+            .u64_counter("ws_message_counter")
+            .with_description("The number of websocket JSON-RPC requests by method")
+            .init();
+
+        let ws_limited_message_counter = meter
+            .u64_counter("ws_limited_message_counter")
+            .with_description("The number of websocket messages rejected by the limits")
+            .init();
+
+        let shadow_call_counter = meter
+            .u64_counter("shadow_call_counter")
+            .with_description("The number of mirrored calls to the shadow providers by outcome")
+            .init();
+
+        let shadow_call_latency_tracker = meter
+            .f64_histogram("shadow_call_latency_tracker")
+            .with_description("The primary and shadow providers latency of the mirrored calls")
+            .init();
+
+        let provider_cost_counter = meter
+            .u64_counter("provider_cost_counter")
+            .with_description("The estimated providers spend in micro-USD")
+            .init();
+
+        let upstream_connection_counter = meter
+            .u64_counter("upstream_connection_counter")
+            .with_description("The number of the new connections to the upstream hosts")
+            .init();
+
+        let upstream_connection_latency_tracker = meter
+            .f64_histogram("upstream_connection_latency_tracker")
+            .with_description("The latency of establishing the upstream connections")
+            .init();
+
         let history_lookup_counter = meter
             .u64_counter("history_lookup_counter")
             .with_description("The number of transaction history lookups")
@@ -263,6 +359,7 @@ impl Metrics {
         Metrics {
             rpc_call_counter,
             rpc_call_retries,
+            rpc_call_policy_counter,
             http_call_counter,
             http_external_latency_tracker,
             http_latency_tracker,
@@ -285,7 +382,22 @@ impl Metrics {
             identity_lookup_avatar_latency_tracker,
             identity_lookup_name_present_counter,
             identity_lookup_avatar_present_counter,
+            identity_cache_refresh_counter,
+            identity_source_lookup_counter,
+            identity_source_lookup_latency_tracker,
+            identity_batch_size,
+            identity_batch_item_counter,
+            avatar_proxy_counter,
             websocket_connection_counter,
+            ws_upstream_pool_size,
+            ws_client_subscriptions,
+            ws_message_counter,
+            ws_limited_message_counter,
+            shadow_call_counter,
+            shadow_call_latency_tracker,
+            provider_cost_counter,
+            upstream_connection_counter,
+            upstream_connection_latency_tracker,
             history_lookup_counter,
             history_lookup_success_counter,
             history_lookup_latency_tracker,
@@ -311,11 +423,32 @@ impl Metrics {
         );
     }
 
-    pub fn add_rpc_call_retries(&self, retires_count: u64, chain_id: String) {
+    pub fn add_rpc_call_retries(&self, retires_count: u64, chain_id: String, policy: String) {
         self.rpc_call_retries.record(
             &otel::Context::new(),
             retires_count,
-            &[otel::KeyValue::new("chain_id", chain_id)],
+            &[
+                otel::KeyValue::new("chain_id", chain_id),
+                otel::KeyValue::new("policy", policy),
+            ],
+        )
+    }
+
+    /// Records the outcome of the RPC call under the applied call policy
+    pub fn add_rpc_call_policy_outcome(
+        &self,
+        chain_id: String,
+        policy: String,
+        outcome: &'static str,
+    ) {
+        self.rpc_call_policy_counter.add(
+            &otel::Context::new(),
+            1,
+            &[
+                otel::KeyValue::new("chain_id", chain_id),
+                otel::KeyValue::new("policy", policy),
+                otel::KeyValue::new("outcome", outcome),
+            ],
         )
     }
 
@@ -536,6 +669,58 @@ impl Metrics {
             .add(&otel::Context::new(), 1, &[]);
     }
 
+    pub fn add_identity_cache_refresh(&self, success: bool) {
+        self.identity_cache_refresh_counter.add(
+            &otel::Context::new(),
+            1,
+            &[otel::KeyValue::new("success", success)],
+        );
+    }
+
+    /// Records the identity source lookup, the result is `found`, `not_found`
+    /// or `error`
+    pub fn add_identity_source_lookup(
+        &self,
+        source: &'static str,
+        result: &'static str,
+        latency: Duration,
+    ) {
+        let attributes = [
+            otel::KeyValue::new("source", source),
+            otel::KeyValue::new("result", result),
+        ];
+        self.identity_source_lookup_counter
+            .add(&otel::Context::new(), 1, &attributes);
+        self.identity_source_lookup_latency_tracker.record(
+            &otel::Context::new(),
+            latency.as_secs_f64(),
+            &attributes,
+        );
+    }
+
+    pub fn add_identity_batch_lookup(&self, size: usize) {
+        self.identity_batch_size
+            .record(&otel::Context::new(), size as u64, &[]);
+    }
+
+    /// Records the batch lookup address result, the result is either
+    /// `cache_hit`, `cache_miss` or `error`
+    pub fn add_identity_batch_item(&self, result: &'static str) {
+        self.identity_batch_item_counter.add(
+            &otel::Context::new(),
+            1,
+            &[otel::KeyValue::new("result", result)],
+        );
+    }
+
+    pub fn add_avatar_proxy_request(&self, success: bool) {
+        self.avatar_proxy_counter.add(
+            &otel::Context::new(),
+            1,
+            &[otel::KeyValue::new("success", success)],
+        );
+    }
+
     pub fn add_websocket_connection(&self, chain_id: String) {
         self.websocket_connection_counter.add(
             &otel::Context::new(),
@@ -544,6 +729,114 @@ impl Metrics {
         );
     }
 
+    pub fn record_ws_upstream_pool_size(
+        &self,
+        provider: &ProviderKind,
+        chain_id: String,
+        size: usize,
+    ) {
+        self.ws_upstream_pool_size.record(
+            &otel::Context::new(),
+            size as u64,
+            &[
+                otel::KeyValue::new("provider", provider.to_string()),
+                otel::KeyValue::new("chain_id", chain_id),
+            ],
+        );
+    }
+
+    pub fn record_ws_client_subscriptions(&self, chain_id: String, subscriptions: usize) {
+        self.ws_client_subscriptions.record(
+            &otel::Context::new(),
+            subscriptions as u64,
+            &[otel::KeyValue::new("chain_id", chain_id)],
+        );
+    }
+
+    pub fn add_ws_message(&self, chain_id: String, method: &str) {
+        self.ws_message_counter.add(
+            &otel::Context::new(),
+            1,
+            &[
+                otel::KeyValue::new("chain_id", chain_id),
+                otel::KeyValue::new("method", method.to_owned()),
+            ],
+        );
+    }
+
+    pub fn add_ws_limited_message(&self, chain_id: String, reason: &'static str) {
+        self.ws_limited_message_counter.add(
+            &otel::Context::new(),
+            1,
+            &[
+                otel::KeyValue::new("chain_id", chain_id),
+                otel::KeyValue::new("reason", reason),
+            ],
+        );
+    }
+
+    pub fn add_shadow_call(
+        &self,
+        provider: &ProviderKind,
+        chain_id: String,
+        outcome: &'static str,
+    ) {
+        self.shadow_call_counter.add(
+            &otel::Context::new(),
+            1,
+            &[
+                otel::KeyValue::new("provider", provider.to_string()),
+                otel::KeyValue::new("chain_id", chain_id),
+                otel::KeyValue::new("outcome", outcome),
+            ],
+        );
+    }
+
+    /// Records the latency of the mirrored call, the target is either
+    /// `primary` or `shadow`
+    pub fn add_shadow_call_latency(
+        &self,
+        provider: &ProviderKind,
+        chain_id: String,
+        target: &'static str,
+        latency: Duration,
+    ) {
+        self.shadow_call_latency_tracker.record(
+            &otel::Context::new(),
+            latency.as_secs_f64(),
+            &[
+                otel::KeyValue::new("provider", provider.to_string()),
+                otel::KeyValue::new("chain_id", chain_id),
+                otel::KeyValue::new("target", target),
+            ],
+        );
+    }
+
+    pub fn add_upstream_connection(&self, host: String, success: bool, latency: Duration) {
+        let attributes = [
+            otel::KeyValue::new("host", host),
+            otel::KeyValue::new("success", success),
+        ];
+        self.upstream_connection_counter
+            .add(&otel::Context::new(), 1, &attributes);
+        self.upstream_connection_latency_tracker.record(
+            &otel::Context::new(),
+            latency.as_secs_f64(),
+            &attributes,
+        );
+    }
+
+    pub fn add_provider_cost(&self, provider: &ProviderKind, chain_id: String, cost: u64) {
+        self.provider_cost_counter.add(
+            &otel::Context::new(),
+            cost,
+            &[
+                otel::KeyValue::new("provider", provider.to_string()),
+                otel::KeyValue::new("chain_id", chain_id),
+            ],
+        );
+    }
+
     pub fn add_history_lookup(&self, provider: &ProviderKind) {
         self.history_lookup_counter.add(
             &otel::Context::new(),
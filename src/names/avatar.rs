@@ -0,0 +1,428 @@
+//! Avatar URIs normalization and the avatar images proxy.
+//!
+//! ENS avatar records can point to the `ipfs://`, `ar://` and `data:` URIs
+//! that clients can't render, the URIs are rewritten to the configured
+//! gateways. The proxy fetches the avatar images from the public hosts only
+//! and serves the cached resized thumbnails.
+
+use {
+    super::Config,
+    base64::prelude::*,
+    bytes::{Bytes, BytesMut},
+    image::{ImageFormat, ImageReader, Limits},
+    moka::future::Cache,
+    reqwest::{
+        dns::{Addrs, Name, Resolve, Resolving},
+        header::CONTENT_TYPE,
+        redirect, StatusCode,
+    },
+    std::{
+        io::Cursor,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        sync::Arc,
+        time::Duration,
+    },
+    tokio::sync::Semaphore,
+    url::{Host, Url},
+};
+
+/// Gateway of the IPFS URLs resolved by the ethers NFT avatars resolution
+const ETHERS_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+/// Image types that are served by the proxy, SVG is not allowed since it can
+/// carry scripts
+const ALLOWED_CONTENT_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Maximum size of the fetched avatar image
+const AVATAR_MAX_SIZE: usize = 5 * 1024 * 1024;
+/// Maximum width and height of the decoded avatar image
+const AVATAR_MAX_DIMENSION: u32 = 4096;
+const AVATAR_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const AVATAR_MAX_REDIRECTS: usize = 3;
+
+const SOURCES_CACHE_CAPACITY: u64 = 64 * 1024 * 1024;
+const SOURCES_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const THUMBNAILS_CACHE_CAPACITY: u64 = 64 * 1024 * 1024;
+const THUMBNAILS_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+/// Maximum number of the avatar images being decoded and resized at once
+const MAX_CONCURRENT_DECODES: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AvatarError {
+    #[error("Unsupported avatar URI")]
+    UnsupportedUri,
+
+    #[error("Avatar URL host is not allowed")]
+    DisallowedUrl,
+
+    #[error("Avatar request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Avatar host responded with {0}")]
+    Status(StatusCode),
+
+    #[error("Unsupported avatar content type: {0}")]
+    ContentType(String),
+
+    #[error("Avatar image exceeds the size limit")]
+    TooLarge,
+
+    #[error("Avatar image decoding error: {0}")]
+    Decode(String),
+}
+
+/// Rewrites the `ipfs://` and `ar://` avatar URIs to the configured gateways
+/// URLs, the rest of the URIs are kept as is and only filtered by the proxy
+pub fn normalize_avatar_uri(uri: &str, config: &Config) -> Option<String> {
+    let uri = uri.trim();
+    if uri.is_empty() {
+        return None;
+    }
+    if let Some(path) = uri
+        .strip_prefix("ipfs://")
+        .or_else(|| uri.strip_prefix(ETHERS_IPFS_GATEWAY))
+    {
+        let path = path.trim_start_matches("ipfs/");
+        return Some(format!("{}/ipfs/{}", config.ipfs_gateway(), path));
+    }
+    if let Some(path) = uri.strip_prefix("ar://") {
+        return Some(format!("{}/{}", config.arweave_gateway(), path));
+    }
+    Some(uri.to_owned())
+}
+
+/// Content type of the base64 encoded image data URI
+fn data_uri_content_type(data: &str) -> Option<&str> {
+    let (media_type, _) = data.split_once(',')?;
+    let content_type = media_type.strip_suffix(";base64")?;
+    ALLOWED_CONTENT_TYPES
+        .iter()
+        .find(|allowed| content_type.eq_ignore_ascii_case(allowed))
+        .copied()
+}
+
+/// Avatar images proxy serving the resized thumbnails
+#[derive(Clone)]
+pub struct AvatarProxy {
+    client: reqwest::Client,
+    /// Fetched avatar images by the avatar URI hash, shared by the thumbnails
+    /// of all of the sizes
+    sources: Cache<String, Bytes>,
+    /// Encoded PNG thumbnails by the size and the avatar URI hash
+    thumbnails: Cache<(u32, String), Bytes>,
+    decodes: Arc<Semaphore>,
+}
+
+impl Default for AvatarProxy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AvatarProxy {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .dns_resolver(Arc::new(PublicHostsResolver))
+            .redirect(redirect::Policy::custom(|attempt| {
+                if attempt.previous().len() >= AVATAR_MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if is_allowed_url(attempt.url()) {
+                    attempt.follow()
+                } else {
+                    attempt.error("redirect to the not allowed URL")
+                }
+            }))
+            .no_proxy()
+            .timeout(AVATAR_FETCH_TIMEOUT)
+            .build()
+            .expect("Failed to build the avatar proxy HTTP client");
+        let sources = Cache::builder()
+            .max_capacity(SOURCES_CACHE_CAPACITY)
+            .weigher(|_, image: &Bytes| image.len().try_into().unwrap_or(u32::MAX))
+            .time_to_live(SOURCES_CACHE_TTL)
+            .build();
+        let thumbnails = Cache::builder()
+            .max_capacity(THUMBNAILS_CACHE_CAPACITY)
+            .weigher(|_, thumbnail: &Bytes| thumbnail.len().try_into().unwrap_or(u32::MAX))
+            .time_to_live(THUMBNAILS_CACHE_TTL)
+            .build();
+
+        Self {
+            client,
+            sources,
+            thumbnails,
+            decodes: Arc::new(Semaphore::new(MAX_CONCURRENT_DECODES)),
+        }
+    }
+
+    /// PNG thumbnail of the avatar, concurrent requests of the same avatar
+    /// are coalesced into a single fetch and of the same thumbnail into a
+    /// single decode
+    pub async fn thumbnail(&self, avatar: &str, size: u32) -> Result<Bytes, Arc<AvatarError>> {
+        // Avatar URIs can be large data URIs, so the caches are keyed by the
+        // hash of the URI
+        let avatar_hash = sha256::digest(avatar);
+        let thumbnail_key = (size, avatar_hash.clone());
+        if let Some(thumbnail) = self.thumbnails.get(&thumbnail_key).await {
+            return Ok(thumbnail);
+        }
+
+        let image = self
+            .sources
+            .try_get_with(avatar_hash, self.fetch(avatar))
+            .await?;
+        self.thumbnails
+            .try_get_with(thumbnail_key, async {
+                let _permit = self
+                    .decodes
+                    .acquire()
+                    .await
+                    .map_err(|e| AvatarError::Decode(e.to_string()))?;
+                tokio::task::spawn_blocking(move || resize(&image, size))
+                    .await
+                    .map_err(|e| AvatarError::Decode(e.to_string()))?
+            })
+            .await
+    }
+
+    async fn fetch(&self, avatar: &str) -> Result<Bytes, AvatarError> {
+        if let Some(data) = avatar.strip_prefix("data:") {
+            return decode_data_uri(data);
+        }
+
+        let url = Url::parse(avatar).map_err(|_| AvatarError::UnsupportedUri)?;
+        if !is_allowed_url(&url) {
+            return Err(AvatarError::DisallowedUrl);
+        }
+        let mut response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(AvatarError::Status(response.status()));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        if !ALLOWED_CONTENT_TYPES
+            .iter()
+            .any(|allowed| media_type.eq_ignore_ascii_case(allowed))
+        {
+            return Err(AvatarError::ContentType(content_type.to_owned()));
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| length > AVATAR_MAX_SIZE as u64)
+        {
+            return Err(AvatarError::TooLarge);
+        }
+
+        // Content length can be missing or wrong, so the limit is enforced
+        // while reading the body
+        let mut body = BytesMut::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > AVATAR_MAX_SIZE {
+                return Err(AvatarError::TooLarge);
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body.freeze())
+    }
+}
+
+fn decode_data_uri(data: &str) -> Result<Bytes, AvatarError> {
+    data_uri_content_type(data).ok_or(AvatarError::UnsupportedUri)?;
+    let (_, encoded) = data.split_once(',').ok_or(AvatarError::UnsupportedUri)?;
+    if encoded.len() > AVATAR_MAX_SIZE * 4 / 3 + 4 {
+        return Err(AvatarError::TooLarge);
+    }
+    BASE64_STANDARD
+        .decode(encoded)
+        .map(Bytes::from)
+        .map_err(|e| AvatarError::Decode(e.to_string()))
+}
+
+/// Resizes the image to fit the square of the size keeping the aspect ratio
+fn resize(image: &[u8], size: u32) -> Result<Bytes, AvatarError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(AVATAR_MAX_DIMENSION);
+    limits.max_image_height = Some(AVATAR_MAX_DIMENSION);
+
+    let mut reader = ImageReader::new(Cursor::new(image))
+        .with_guessed_format()
+        .map_err(|e| AvatarError::Decode(e.to_string()))?;
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|e| AvatarError::Decode(e.to_string()))?;
+
+    let mut thumbnail = Vec::new();
+    image
+        .thumbnail(size, size)
+        .write_to(&mut Cursor::new(&mut thumbnail), ImageFormat::Png)
+        .map_err(|e| AvatarError::Decode(e.to_string()))?;
+    Ok(thumbnail.into())
+}
+
+/// Only the HTTPS URLs of the public hosts on the default port are fetched,
+/// the domain names are checked by the `PublicHostsResolver`
+pub fn is_allowed_url(url: &Url) -> bool {
+    if url.scheme() != "https" || url.port().is_some_and(|port| port != 443) {
+        return false;
+    }
+    match url.host() {
+        Some(Host::Domain(_)) => true,
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(ip);
+            }
+            let segments = ip.segments();
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // IPv4-compatible ::/96
+                || segments[..6] == [0; 6]
+                // NAT64 64:ff9b::/96
+                || segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+                // 6to4 2002::/16
+                || segments[0] == 0x2002
+                // Teredo 2001::/32
+                || (segments[0] == 0x2001 && segments[1] == 0)
+                // Unique local fc00::/7
+                || (segments[0] & 0xfe00) == 0xfc00
+                // Link-local fe80::/10
+                || (segments[0] & 0xffc0) == 0xfe80
+                // Documentation 2001:db8::/32
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Shared address space 100.64.0.0/10
+        || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        // IETF protocol assignments 192.0.0.0/24
+        || (octets[0] == 192 && octets[1] == 0 && octets[2] == 0)
+        // Benchmarking 198.18.0.0/15
+        || (octets[0] == 198 && (octets[1] & 0xfe) == 18)
+        // Reserved 240.0.0.0/4
+        || octets[0] >= 240
+        // "This network" 0.0.0.0/8
+        || octets[0] == 0)
+}
+
+/// Resolves the domain names to the public addresses only, so the proxy can't
+/// be pointed to the internal hosts including the DNS rebinding and redirects
+pub struct PublicHostsResolver;
+
+impl Resolve for PublicHostsResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} is not resolving to a public address", name.as_str()).into(),
+                );
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            allowed_zones: None,
+            ipfs_gateway: Some("https://gateway.example.com/".to_owned()),
+            arweave_gateway: None,
+            avatar_proxy_enabled: None,
+            gateway_signing_key: None,
+            gateway_response_ttl: None,
+            identity_sources: None,
+            identity_sources_priority: None,
+        }
+    }
+
+    #[test]
+    fn normalizes_avatar_uris() {
+        let config = config();
+        assert_eq!(
+            normalize_avatar_uri("ipfs://QmHash/avatar.png", &config).as_deref(),
+            Some("https://gateway.example.com/ipfs/QmHash/avatar.png")
+        );
+        assert_eq!(
+            normalize_avatar_uri("ipfs://ipfs/QmHash", &config).as_deref(),
+            Some("https://gateway.example.com/ipfs/QmHash")
+        );
+        assert_eq!(
+            normalize_avatar_uri("https://ipfs.io/ipfs/QmHash", &config).as_deref(),
+            Some("https://gateway.example.com/ipfs/QmHash")
+        );
+        assert_eq!(
+            normalize_avatar_uri("ar://TxId", &config).as_deref(),
+            Some("https://arweave.net/TxId")
+        );
+        assert_eq!(
+            normalize_avatar_uri("https://example.com/avatar.png", &config).as_deref(),
+            Some("https://example.com/avatar.png")
+        );
+        assert_eq!(
+            normalize_avatar_uri("data:image/png;base64,iVBORw0KGgo=", &config).as_deref(),
+            Some("data:image/png;base64,iVBORw0KGgo=")
+        );
+        assert_eq!(
+            normalize_avatar_uri("data:image/svg+xml;base64,PHN2Zz4=", &config).as_deref(),
+            Some("data:image/svg+xml;base64,PHN2Zz4=")
+        );
+        assert_eq!(
+            normalize_avatar_uri("http://example.com/avatar.png", &config).as_deref(),
+            Some("http://example.com/avatar.png")
+        );
+        assert_eq!(normalize_avatar_uri(" ", &config), None);
+    }
+
+    #[test]
+    fn allows_only_public_hosts() {
+        let allowed = |url: &str| is_allowed_url(&Url::parse(url).unwrap());
+        assert!(allowed("https://example.com/avatar.png"));
+        assert!(allowed("https://8.8.8.8/avatar.png"));
+        assert!(!allowed("http://example.com/avatar.png"));
+        assert!(!allowed("https://example.com:8080/avatar.png"));
+        assert!(!allowed("https://127.0.0.1/avatar.png"));
+        assert!(!allowed("https://10.0.0.1/avatar.png"));
+        assert!(!allowed("https://169.254.169.254/latest/meta-data"));
+        assert!(!allowed("https://100.64.0.1/avatar.png"));
+        assert!(!allowed("https://[::1]/avatar.png"));
+        assert!(!allowed("https://[fd00::1]/avatar.png"));
+        assert!(!allowed("https://[::ffff:127.0.0.1]/avatar.png"));
+        assert!(!allowed("https://[::7f00:1]/avatar.png"));
+        assert!(!allowed("https://[64:ff9b::a9fe:a9fe]/avatar.png"));
+        assert!(!allowed("https://[2002:7f00:1::]/avatar.png"));
+        assert!(!allowed("https://[2001:0:4136:e378::1]/avatar.png"));
+        assert!(!allowed("https://198.18.0.1/avatar.png"));
+        assert!(!allowed("https://192.0.0.8/avatar.png"));
+        assert!(allowed("https://[2606:4700::1111]/avatar.png"));
+    }
+}
@@ -0,0 +1,245 @@
+//! EIP-3668 (CCIP-Read) client of the offchain resolvers.
+//!
+//! The resolver reverts the call with the `OffchainLookup` error listing the
+//! gateway URLs, the gateway answer is passed back to the resolver callback
+//! function that verifies it and returns the call result. Gateways are only
+//! requested on the public hosts.
+
+use {
+    super::avatar::{is_allowed_url, PublicHostsResolver},
+    crate::{
+        error::RpcError,
+        handlers::identity::{handle_rpc_error, SelfProvider, SelfProviderError},
+    },
+    ethers::{
+        abi::{self, ParamType, Token},
+        providers::{JsonRpcClient, Provider},
+        types::{Address, Bytes},
+    },
+    once_cell::sync::Lazy,
+    reqwest::{redirect, StatusCode},
+    serde::{Deserialize, Serialize},
+    serde_json::json,
+    std::{sync::Arc, time::Duration},
+    tracing::debug,
+    url::Url,
+};
+
+/// `OffchainLookup(address,string[],bytes,bytes4,bytes)` error selector
+const OFFCHAIN_LOOKUP_SELECTOR: [u8; 4] = [0x55, 0x6f, 0x18, 0x30];
+/// Maximum number of the offchain lookups followed by a single call
+const MAX_OFFCHAIN_LOOKUPS: usize = 4;
+const GATEWAY_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+static GATEWAY_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .dns_resolver(Arc::new(PublicHostsResolver))
+        .redirect(redirect::Policy::none())
+        .no_proxy()
+        .timeout(GATEWAY_REQUEST_TIMEOUT)
+        .build()
+        .expect("Failed to build the CCIP-Read gateways HTTP client")
+});
+
+/// Gateway request of the `POST` gateways, the `GET` gateways are passing the
+/// same fields in the URL template
+#[derive(Debug, Serialize)]
+struct GatewayRequest {
+    sender: String,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GatewayResponse {
+    data: Bytes,
+}
+
+/// Decoded `OffchainLookup` revert
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OffchainLookup {
+    sender: Address,
+    urls: Vec<String>,
+    call_data: Bytes,
+    callback_function: [u8; 4],
+    extra_data: Bytes,
+}
+
+impl OffchainLookup {
+    fn decode(data: &[u8]) -> Option<Self> {
+        let params = data.strip_prefix(&OFFCHAIN_LOOKUP_SELECTOR)?;
+        let mut tokens = abi::decode(
+            &[
+                ParamType::Address,
+                ParamType::Array(Box::new(ParamType::String)),
+                ParamType::Bytes,
+                ParamType::FixedBytes(4),
+                ParamType::Bytes,
+            ],
+            params,
+        )
+        .ok()?
+        .into_iter();
+
+        Some(Self {
+            sender: tokens.next()?.into_address()?,
+            urls: tokens
+                .next()?
+                .into_array()?
+                .into_iter()
+                .map(Token::into_string)
+                .collect::<Option<_>>()?,
+            call_data: tokens.next()?.into_bytes()?.into(),
+            callback_function: tokens.next()?.into_fixed_bytes()?.try_into().ok()?,
+            extra_data: tokens.next()?.into_bytes()?.into(),
+        })
+    }
+
+    /// Calldata of the callback function with the gateway response
+    fn callback_calldata(&self, response: &Bytes) -> Bytes {
+        let mut calldata = self.callback_function.to_vec();
+        calldata.extend(abi::encode(&[
+            Token::Bytes(response.to_vec()),
+            Token::Bytes(self.extra_data.to_vec()),
+        ]));
+        calldata.into()
+    }
+
+    /// Response of the first gateway that answered, the gateways are tried in
+    /// order until one of them answers or fails with the client error
+    async fn request_gateways(&self) -> Option<Bytes> {
+        let sender = format!("{:#x}", self.sender);
+        let data = format!("0x{}", hex::encode(&self.call_data));
+        for url in &self.urls {
+            let request = if url.contains("{data}") {
+                let url = url.replace("{sender}", &sender).replace("{data}", &data);
+                Url::parse(&url)
+                    .ok()
+                    .filter(is_allowed_url)
+                    .map(|url| GATEWAY_CLIENT.get(url))
+            } else {
+                Url::parse(&url.replace("{sender}", &sender))
+                    .ok()
+                    .filter(is_allowed_url)
+                    .map(|url| {
+                        GATEWAY_CLIENT.post(url).json(&GatewayRequest {
+                            sender: sender.clone(),
+                            data: data.clone(),
+                        })
+                    })
+            };
+            let Some(request) = request else {
+                debug!("Skipping the not allowed CCIP-Read gateway URL {url}");
+                continue;
+            };
+
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    debug!("CCIP-Read gateway request error: {e}");
+                    continue;
+                }
+            };
+            let status = response.status();
+            if status.is_client_error() {
+                debug!("CCIP-Read gateway responded with {status}");
+                return None;
+            }
+            if status != StatusCode::OK {
+                debug!("CCIP-Read gateway responded with {status}");
+                continue;
+            }
+            match response.json::<GatewayResponse>().await {
+                Ok(response) => return Some(response.data),
+                Err(e) => debug!("Invalid CCIP-Read gateway response: {e}"),
+            }
+        }
+        None
+    }
+}
+
+/// `eth_call` of the contract following its offchain lookups, the reverted
+/// calls are resolved to `None`
+#[tracing::instrument(skip(provider, calldata), level = "debug")]
+pub async fn call(
+    provider: &Provider<SelfProvider>,
+    to: Address,
+    calldata: Bytes,
+) -> Result<Option<Bytes>, RpcError> {
+    let mut calldata = calldata;
+    for _ in 0..=MAX_OFFCHAIN_LOOKUPS {
+        let params = json!([{ "to": to, "data": calldata }, "latest"]);
+        let error = match provider
+            .as_ref()
+            .request::<_, Bytes>("eth_call", params)
+            .await
+        {
+            Ok(result) => return Ok(Some(result)),
+            Err(error) => error,
+        };
+        let Some(lookup) = offchain_lookup(&error) else {
+            handle_rpc_error(error.into())?;
+            return Ok(None);
+        };
+        // Lookups of the contracts called by the resolver are not followed
+        if lookup.sender != to {
+            debug!(
+                "OffchainLookup sender {:#x} is not the {to:#x}",
+                lookup.sender
+            );
+            return Ok(None);
+        }
+        let Some(response) = lookup.request_gateways().await else {
+            return Ok(None);
+        };
+        calldata = lookup.callback_calldata(&response);
+    }
+    debug!("Too many offchain lookups of the {to:#x} call");
+    Ok(None)
+}
+
+/// `OffchainLookup` revert of the call
+fn offchain_lookup(error: &SelfProviderError) -> Option<OffchainLookup> {
+    let SelfProviderError::JsonRpcError(error) = error else {
+        return None;
+    };
+    let data = error.error.data.as_deref()?;
+    let data = hex::decode(data.trim_start_matches("0x")).ok()?;
+    OffchainLookup::decode(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_offchain_lookup() {
+        let lookup = OffchainLookup {
+            sender: Address::repeat_byte(0x11),
+            urls: vec!["https://gateway.example/{sender}/{data}.json".to_owned()],
+            call_data: vec![0x90, 0x61, 0xb9, 0x23].into(),
+            callback_function: [0xf4, 0xd4, 0xd2, 0xf8],
+            extra_data: vec![0x01, 0x02].into(),
+        };
+        let mut data = OFFCHAIN_LOOKUP_SELECTOR.to_vec();
+        data.extend(abi::encode(&[
+            Token::Address(lookup.sender),
+            Token::Array(vec![Token::String(lookup.urls[0].clone())]),
+            Token::Bytes(lookup.call_data.to_vec()),
+            Token::FixedBytes(lookup.callback_function.to_vec()),
+            Token::Bytes(lookup.extra_data.to_vec()),
+        ]));
+        assert_eq!(OffchainLookup::decode(&data), Some(lookup.clone()));
+        assert_eq!(OffchainLookup::decode(&data[4..]), None);
+
+        let response = Bytes::from(vec![0xaa]);
+        let calldata = lookup.callback_calldata(&response);
+        assert_eq!(calldata[..4], lookup.callback_function);
+        assert_eq!(
+            abi::decode(&[ParamType::Bytes, ParamType::Bytes], &calldata[4..]).unwrap(),
+            vec![
+                Token::Bytes(response.to_vec()),
+                Token::Bytes(lookup.extra_data.to_vec())
+            ]
+        );
+    }
+}
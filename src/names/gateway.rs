@@ -0,0 +1,401 @@
+//! EIP-3668 (CCIP-Read) offchain lookup gateway of the ENS `OffchainResolver`.
+//!
+//! The resolver reverts the `resolve(name, data)` calls with the
+//! `OffchainLookup` error pointing to the gateway, the gateway answers the
+//! record query from the names registry and signs the answer, the signature
+//! is verified by the resolver `resolveWithProof` callback.
+
+use {
+    super::LOCAL_ATTRIBUTES_RECORDS,
+    crate::{
+        database::types::{NameAndAddresses, SupportedNamespaces},
+        error::RpcError,
+        utils::crypto,
+    },
+    ethers::{
+        abi::{self, ParamType, Token},
+        providers::ens,
+        signers::LocalWallet,
+        types::{Address, H256, U256},
+        utils::keccak256,
+    },
+};
+
+/// `resolve(bytes,bytes)` selector of the ENSIP-10 extended resolver
+const RESOLVE_SELECTOR: [u8; 4] = [0x90, 0x61, 0xb9, 0x23];
+/// `addr(bytes32)` selector
+const ADDR_SELECTOR: [u8; 4] = [0x3b, 0x3b, 0x57, 0xde];
+/// `addr(bytes32,uint256)` selector
+const ADDR_COIN_TYPE_SELECTOR: [u8; 4] = [0xf1, 0xcb, 0x7e, 0x06];
+/// `text(bytes32,string)` selector
+const TEXT_SELECTOR: [u8; 4] = [0x59, 0xd1, 0xd4, 0x3c];
+/// ENSIP-11 coin type of the `addr(bytes32)` record
+const MAINNET_COIN_TYPE: u32 = 60;
+
+/// Record query wrapped into the `resolve` call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordQuery {
+    Addr,
+    AddrCoinType(u32),
+    Text(String),
+}
+
+/// Decoded `resolve(bytes name, bytes data)` call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveRequest {
+    pub name: String,
+    pub query: RecordQuery,
+}
+
+impl ResolveRequest {
+    pub fn decode(calldata: &[u8]) -> Result<Self, RpcError> {
+        let (selector, params) = split_selector(calldata)?;
+        if selector != RESOLVE_SELECTOR {
+            return Err(invalid_request("Only the resolve calls are supported"));
+        }
+        let mut tokens = abi::decode(&[ParamType::Bytes, ParamType::Bytes], params)
+            .map_err(|e| invalid_request(&format!("Invalid resolve call: {e}")))?
+            .into_iter()
+            .filter_map(Token::into_bytes);
+        let (Some(dns_name), Some(data)) = (tokens.next(), tokens.next()) else {
+            return Err(invalid_request("Invalid resolve call"));
+        };
+        let name = dns_decode(&dns_name).ok_or_else(|| invalid_request("Invalid DNS name"))?;
+
+        let (selector, params) = split_selector(&data)?;
+        let (node, query) = match selector {
+            ADDR_SELECTOR => {
+                let tokens = decode_params(&[ParamType::FixedBytes(32)], params)?;
+                (tokens[0].clone(), RecordQuery::Addr)
+            }
+            ADDR_COIN_TYPE_SELECTOR => {
+                let tokens =
+                    decode_params(&[ParamType::FixedBytes(32), ParamType::Uint(256)], params)?;
+                let coin_type = tokens[1]
+                    .clone()
+                    .into_uint()
+                    .filter(|coin_type| *coin_type <= U256::from(u32::MAX))
+                    .ok_or_else(|| invalid_request("Unsupported coin type"))?;
+                (
+                    tokens[0].clone(),
+                    RecordQuery::AddrCoinType(coin_type.as_u32()),
+                )
+            }
+            TEXT_SELECTOR => {
+                let tokens =
+                    decode_params(&[ParamType::FixedBytes(32), ParamType::String], params)?;
+                let key = tokens[1]
+                    .clone()
+                    .into_string()
+                    .ok_or_else(|| invalid_request("Invalid text record key"))?;
+                (tokens[0].clone(), RecordQuery::Text(key))
+            }
+            _ => return Err(invalid_request("Unsupported record query")),
+        };
+        if node.into_fixed_bytes() != Some(ens::namehash(&name).as_bytes().to_vec()) {
+            return Err(invalid_request("Record node doesn't match the name"));
+        }
+
+        Ok(Self { name, query })
+    }
+}
+
+impl RecordQuery {
+    /// ABI encoded answer of the record query, unset records are answered
+    /// with the empty values
+    pub fn answer(&self, name: Option<&NameAndAddresses>) -> Vec<u8> {
+        match self {
+            Self::Addr => {
+                let address = name
+                    .and_then(|name| evm_address(name, MAINNET_COIN_TYPE))
+                    .unwrap_or_default();
+                abi::encode(&[Token::Address(address)])
+            }
+            Self::AddrCoinType(coin_type) => {
+                let address = name
+                    .and_then(|name| address_bytes(name, *coin_type))
+                    .unwrap_or_default();
+                abi::encode(&[Token::Bytes(address)])
+            }
+            Self::Text(key) => {
+                let value = name
+                    .and_then(|name| text_record(name, key))
+                    .unwrap_or_default();
+                abi::encode(&[Token::String(value)])
+            }
+        }
+    }
+}
+
+/// Signs the answer for the `OffchainResolver` verification and encodes it as
+/// the `(bytes result, uint64 expires, bytes signature)` gateway response
+pub fn sign_response(
+    signer: &LocalWallet,
+    sender: Address,
+    request: &[u8],
+    result: Vec<u8>,
+    expires: u64,
+) -> Result<Vec<u8>, RpcError> {
+    let signature = signer
+        .sign_hash(signature_hash(sender, expires, request, &result))
+        .map_err(|e| {
+            RpcError::InvalidConfiguration(format!("Failed to sign the gateway response: {e}"))
+        })?;
+    Ok(abi::encode(&[
+        Token::Bytes(result),
+        Token::Uint(expires.into()),
+        Token::Bytes(crypto::pack_signature(&signature).to_vec()),
+    ]))
+}
+
+/// `SignatureVerifier.makeSignatureHash` of the ENS offchain resolver
+fn signature_hash(sender: Address, expires: u64, request: &[u8], result: &[u8]) -> H256 {
+    let mut message = Vec::with_capacity(2 + 20 + 8 + 32 + 32);
+    message.extend_from_slice(&[0x19, 0x00]);
+    message.extend_from_slice(sender.as_bytes());
+    message.extend_from_slice(&expires.to_be_bytes());
+    message.extend_from_slice(&keccak256(request));
+    message.extend_from_slice(&keccak256(result));
+    H256(keccak256(message))
+}
+
+fn evm_address(name: &NameAndAddresses, coin_type: u32) -> Option<Address> {
+    name.addresses
+        .get(&coin_type)?
+        .address
+        .parse::<Address>()
+        .ok()
+}
+
+/// ENSIP-9 binary format of the name address by the coin type namespace
+fn address_bytes(name: &NameAndAddresses, coin_type: u32) -> Option<Vec<u8>> {
+    let address = &name.addresses.get(&coin_type)?.address;
+    match SupportedNamespaces::from_slip44(coin_type)? {
+        SupportedNamespaces::Eip155 => {
+            evm_address(name, coin_type).map(|address| address.as_bytes().to_vec())
+        }
+        SupportedNamespaces::Solana => bs58::decode(address).into_vec().ok(),
+        SupportedNamespaces::Bip122 => crypto::bitcoin_script_pubkey(address),
+    }
+}
+
+/// Text record of the name attribute, attributes that are named differently
+/// than the text records are resolved by the text record key
+fn text_record(name: &NameAndAddresses, key: &str) -> Option<String> {
+    let attribute = LOCAL_ATTRIBUTES_RECORDS
+        .iter()
+        .find(|(_, record)| *record == key)
+        .map_or(key, |(attribute, _)| *attribute);
+    name.attributes.as_ref()?.get(attribute).cloned()
+}
+
+/// Decodes the name from the DNS wire format of the `resolve` call
+fn dns_decode(encoded: &[u8]) -> Option<String> {
+    let mut labels = Vec::new();
+    let mut rest = encoded;
+    loop {
+        let (&length, tail) = rest.split_first()?;
+        if length == 0 {
+            break;
+        }
+        let label = tail.get(..length as usize)?;
+        labels.push(std::str::from_utf8(label).ok()?);
+        rest = &tail[length as usize..];
+    }
+    (!labels.is_empty()).then(|| labels.join("."))
+}
+
+fn split_selector(calldata: &[u8]) -> Result<([u8; 4], &[u8]), RpcError> {
+    if calldata.len() < 4 {
+        return Err(invalid_request("Calldata is too short"));
+    }
+    let (selector, params) = calldata.split_at(4);
+    let selector = selector
+        .try_into()
+        .expect("Selector should be 4 bytes long");
+    Ok((selector, params))
+}
+
+fn decode_params(types: &[ParamType], params: &[u8]) -> Result<Vec<Token>, RpcError> {
+    abi::decode(types, params).map_err(|e| invalid_request(&format!("Invalid record query: {e}")))
+}
+
+fn invalid_request(message: &str) -> RpcError {
+    RpcError::InvalidGatewayRequest(message.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::database::types,
+        chrono::Utc,
+        ethers::{signers::Signer, types::Signature},
+        rand_core::OsRng,
+        std::collections::HashMap,
+    };
+
+    const NAME: &str = "name.reown.id";
+    const ADDRESS: &str = "0x2e234DAe75C793f67A35089C9d99245E1C58470b";
+    const SOLANA_ADDRESS: &str = "CKfatsPMUf8SkiURsDXs7eK6GWb4Jsd6UDbs7twMCWxo";
+    const BITCOIN_ADDRESS: &str = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+
+    fn resolve_calldata(name: &str, data: Vec<u8>) -> Vec<u8> {
+        let mut dns_name = Vec::new();
+        for label in name.split('.') {
+            dns_name.push(label.len() as u8);
+            dns_name.extend_from_slice(label.as_bytes());
+        }
+        dns_name.push(0);
+        let mut calldata = RESOLVE_SELECTOR.to_vec();
+        calldata.extend(abi::encode(&[Token::Bytes(dns_name), Token::Bytes(data)]));
+        calldata
+    }
+
+    fn record_call(selector: [u8; 4], tokens: &[Token]) -> Vec<u8> {
+        let mut data = selector.to_vec();
+        data.extend(abi::encode(tokens));
+        data
+    }
+
+    fn name_and_addresses() -> NameAndAddresses {
+        NameAndAddresses {
+            name: NAME.to_owned(),
+            registered_at: Utc::now(),
+            updated_at: Utc::now(),
+            attributes: Some(sqlx::types::Json(HashMap::from([(
+                "bio".to_owned(),
+                "Bio".to_owned(),
+            )]))),
+            addresses: HashMap::from([
+                (
+                    MAINNET_COIN_TYPE,
+                    types::Address {
+                        address: ADDRESS.to_owned(),
+                        created_at: None,
+                    },
+                ),
+                (
+                    crypto::SLIP44_SOLANA_COIN_TYPE,
+                    types::Address {
+                        address: SOLANA_ADDRESS.to_owned(),
+                        created_at: None,
+                    },
+                ),
+                (
+                    crypto::SLIP44_BITCOIN_COIN_TYPE,
+                    types::Address {
+                        address: BITCOIN_ADDRESS.to_owned(),
+                        created_at: None,
+                    },
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn decodes_resolve_requests() {
+        let node = Token::FixedBytes(ens::namehash(NAME).as_bytes().to_vec());
+        let request = |data| ResolveRequest::decode(&resolve_calldata(NAME, data));
+
+        assert_eq!(
+            request(record_call(ADDR_SELECTOR, &[node.clone()])).unwrap(),
+            ResolveRequest {
+                name: NAME.to_owned(),
+                query: RecordQuery::Addr,
+            }
+        );
+        assert_eq!(
+            request(record_call(
+                ADDR_COIN_TYPE_SELECTOR,
+                &[node.clone(), Token::Uint(10.into())]
+            ))
+            .unwrap()
+            .query,
+            RecordQuery::AddrCoinType(10)
+        );
+        assert_eq!(
+            request(record_call(
+                TEXT_SELECTOR,
+                &[node, Token::String("description".to_owned())]
+            ))
+            .unwrap()
+            .query,
+            RecordQuery::Text("description".to_owned())
+        );
+
+        // Node of the other name
+        let other_node = Token::FixedBytes(ens::namehash("other.reown.id").as_bytes().to_vec());
+        assert!(request(record_call(ADDR_SELECTOR, &[other_node])).is_err());
+        assert!(ResolveRequest::decode(&[0x90, 0x61]).is_err());
+    }
+
+    #[test]
+    fn answers_record_queries() {
+        let name = name_and_addresses();
+        let address = ADDRESS.parse::<Address>().unwrap();
+        let solana_address = bs58::decode(SOLANA_ADDRESS).into_vec().unwrap();
+        let bitcoin_script =
+            hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+
+        assert_eq!(
+            RecordQuery::Addr.answer(Some(&name)),
+            abi::encode(&[Token::Address(address)])
+        );
+        assert_eq!(
+            RecordQuery::AddrCoinType(MAINNET_COIN_TYPE).answer(Some(&name)),
+            abi::encode(&[Token::Bytes(address.as_bytes().to_vec())])
+        );
+        assert_eq!(
+            RecordQuery::AddrCoinType(crypto::SLIP44_SOLANA_COIN_TYPE).answer(Some(&name)),
+            abi::encode(&[Token::Bytes(solana_address)])
+        );
+        assert_eq!(
+            RecordQuery::AddrCoinType(crypto::SLIP44_BITCOIN_COIN_TYPE).answer(Some(&name)),
+            abi::encode(&[Token::Bytes(bitcoin_script)])
+        );
+        assert_eq!(
+            RecordQuery::Text("description".to_owned()).answer(Some(&name)),
+            abi::encode(&[Token::String("Bio".to_owned())])
+        );
+        assert_eq!(
+            RecordQuery::Addr.answer(None),
+            abi::encode(&[Token::Address(Address::zero())])
+        );
+    }
+
+    #[test]
+    fn signs_gateway_responses() {
+        let signer = LocalWallet::new(&mut OsRng);
+        let sender = Address::random();
+        let request = resolve_calldata(NAME, vec![]);
+        let result = RecordQuery::Addr.answer(None);
+        let response = sign_response(&signer, sender, &request, result.clone(), 1000).unwrap();
+
+        let tokens = abi::decode(
+            &[ParamType::Bytes, ParamType::Uint(64), ParamType::Bytes],
+            &response,
+        )
+        .unwrap();
+        assert_eq!(tokens[0], Token::Bytes(result.clone()));
+        assert_eq!(tokens[1], Token::Uint(1000.into()));
+        let signature = tokens[2].clone().into_bytes().unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert_eq!(
+            signature
+                .recover(signature_hash(sender, 1000, &request, &result))
+                .unwrap(),
+            signer.address()
+        );
+    }
+
+    #[test]
+    fn decodes_dns_names() {
+        assert_eq!(
+            dns_decode(b"\x04name\x05reown\x02id\x00"),
+            Some(NAME.to_owned())
+        );
+        assert_eq!(dns_decode(b"\x04name\x05reown\x02id"), None);
+        assert_eq!(dns_decode(b"\x00"), None);
+    }
+}
@@ -1,8 +1,23 @@
-use {once_cell::sync::Lazy, regex::Regex, serde::Deserialize, std::collections::HashMap};
+use {
+    crate::error::RpcError,
+    ethers::signers::LocalWallet,
+    once_cell::sync::Lazy,
+    regex::Regex,
+    serde::Deserialize,
+    std::{collections::HashMap, fmt},
+};
 
+pub mod avatar;
+pub mod ccip;
+pub mod gateway;
+pub mod sources;
 pub mod suggestions;
 pub mod utils;
 
+const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
+const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net";
+const DEFAULT_GATEWAY_RESPONSE_TTL: u64 = 300;
+
 /// Attributes value max length
 pub const ATTRIBUTES_VALUE_MAX_LENGTH: usize = 255;
 
@@ -16,7 +31,117 @@ pub static SUPPORTED_ATTRIBUTES: Lazy<HashMap<String, Regex>> = Lazy::new(|| {
     map
 });
 
-#[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
+/// Local name attributes that are named differently than the text records
+pub const LOCAL_ATTRIBUTES_RECORDS: [(&str, &str); 1] = [("bio", "description")];
+
+#[derive(Clone, Deserialize, Eq, PartialEq)]
 pub struct Config {
     pub allowed_zones: Option<Vec<String>>,
+    /// Gateway the `ipfs://` avatar URIs are rewritten to
+    pub ipfs_gateway: Option<String>,
+    /// Gateway the `ar://` avatar URIs are rewritten to
+    pub arweave_gateway: Option<String>,
+    /// Enables the `/v1/avatar/:address` avatar images proxy
+    pub avatar_proxy_enabled: Option<bool>,
+    /// Hex-encoded private key signing the CCIP-Read gateway responses, the
+    /// gateway is disabled if not set
+    pub gateway_signing_key: Option<String>,
+    /// Validity period of the signed gateway responses in seconds
+    pub gateway_response_ttl: Option<u64>,
+    /// JSON of the HTTP identity sources endpoints
+    pub identity_sources: Option<String>,
+    /// JSON of the identity sources priority order by the project ID
+    pub identity_sources_priority: Option<String>,
+}
+
+impl Config {
+    pub fn ipfs_gateway(&self) -> &str {
+        self.ipfs_gateway
+            .as_deref()
+            .unwrap_or(DEFAULT_IPFS_GATEWAY)
+            .trim_end_matches('/')
+    }
+
+    pub fn arweave_gateway(&self) -> &str {
+        self.arweave_gateway
+            .as_deref()
+            .unwrap_or(DEFAULT_ARWEAVE_GATEWAY)
+            .trim_end_matches('/')
+    }
+
+    pub fn avatar_proxy_enabled(&self) -> bool {
+        self.avatar_proxy_enabled.unwrap_or(false)
+    }
+
+    pub fn gateway_response_ttl(&self) -> u64 {
+        self.gateway_response_ttl
+            .unwrap_or(DEFAULT_GATEWAY_RESPONSE_TTL)
+    }
+
+    /// Signer of the gateway responses, the invalid signing key is failing
+    /// the startup
+    pub fn gateway_signer(&self) -> Result<Option<LocalWallet>, RpcError> {
+        self.gateway_signing_key
+            .as_deref()
+            .map(|signing_key| {
+                signing_key.parse::<LocalWallet>().map_err(|e| {
+                    RpcError::InvalidConfiguration(format!(
+                        "Invalid names gateway signing key: {e}"
+                    ))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// The gateway signing key is redacted
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("allowed_zones", &self.allowed_zones)
+            .field("ipfs_gateway", &self.ipfs_gateway)
+            .field("arweave_gateway", &self.arweave_gateway)
+            .field("avatar_proxy_enabled", &self.avatar_proxy_enabled)
+            .field(
+                "gateway_signing_key",
+                &self.gateway_signing_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("gateway_response_ttl", &self.gateway_response_ttl)
+            .field("identity_sources", &self.identity_sources)
+            .field("identity_sources_priority", &self.identity_sources_priority)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(gateway_signing_key: Option<&str>) -> Config {
+        Config {
+            allowed_zones: None,
+            ipfs_gateway: None,
+            arweave_gateway: None,
+            avatar_proxy_enabled: None,
+            gateway_signing_key: gateway_signing_key.map(ToOwned::to_owned),
+            gateway_response_ttl: None,
+            identity_sources: None,
+            identity_sources_priority: None,
+        }
+    }
+
+    #[test]
+    fn parses_gateway_signer() {
+        let signing_key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+        assert!(config(Some(signing_key))
+            .gateway_signer()
+            .unwrap()
+            .is_some());
+        assert!(config(None).gateway_signer().unwrap().is_none());
+        assert!(config(Some("signing_key")).gateway_signer().is_err());
+
+        let debug = format!("{:?}", config(Some(signing_key)));
+        assert!(!debug.contains(signing_key.trim_start_matches("0x")));
+        assert!(debug.contains("<redacted>"));
+    }
 }
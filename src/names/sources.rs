@@ -0,0 +1,281 @@
+//! Identity sources consulted by the identity lookup in the priority order.
+//!
+//! ENS and the names registry are always available, the social graph sources
+//! are queried through the configured HTTP endpoints responding with the
+//! `{"name": "...", "avatar": "..."}` identity of the address.
+
+use {
+    crate::error::RpcError,
+    ethers::{types::H160, utils::to_checksum},
+    reqwest::StatusCode,
+    serde::{de::DeserializeOwned, Deserialize},
+    std::{collections::HashMap, iter, time::Duration},
+    tracing::warn,
+};
+
+const DEFAULT_HTTP_SOURCE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Priority key of the projects without their own sources priority
+const DEFAULT_PRIORITY_KEY: &str = "*";
+const DEFAULT_PRIORITY: [IdentitySource; 2] = [IdentitySource::Ens, IdentitySource::Local];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentitySource {
+    /// ENS primary names
+    Ens,
+    /// Names registry
+    Local,
+    /// Farcaster usernames
+    Farcaster,
+    /// Lens handles
+    Lens,
+}
+
+impl IdentitySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ens => "ens",
+            Self::Local => "local",
+            Self::Farcaster => "farcaster",
+            Self::Lens => "lens",
+        }
+    }
+
+    /// Whether the source is queried through the configured HTTP endpoint
+    pub fn is_http(&self) -> bool {
+        matches!(self, Self::Farcaster | Self::Lens)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpSourceConfig {
+    /// Endpoint URL with the `{address}` placeholder
+    url: String,
+    /// Request headers, e.g. the API key of the source
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    timeout_ms: Option<u64>,
+}
+
+/// Identity responded by the HTTP source
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpIdentity {
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HttpSourceError {
+    #[error("Identity source is not configured")]
+    NotConfigured,
+
+    #[error("Identity source request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Identity source responded with the status code {0}")]
+    Status(StatusCode),
+}
+
+#[derive(Debug)]
+pub struct IdentitySources {
+    http: HashMap<IdentitySource, HttpSourceConfig>,
+    default_priority: Vec<IdentitySource>,
+    project_priorities: HashMap<String, Vec<IdentitySource>>,
+}
+
+impl Default for IdentitySources {
+    fn default() -> Self {
+        Self {
+            http: HashMap::new(),
+            default_priority: DEFAULT_PRIORITY.to_vec(),
+            project_priorities: HashMap::new(),
+        }
+    }
+}
+
+impl IdentitySources {
+    /// Sources JSON maps the HTTP sources to their endpoints, e.g.
+    /// `{"farcaster": {"url": "https://example.com/farcaster/{address}"}}`.
+    /// Priority JSON maps the project IDs to the sources priority order, the
+    /// `*` key overrides the default order, e.g. `{"*": ["ens", "local",
+    /// "farcaster"], "project_id": ["lens", "ens"]}`. The invalid JSON is
+    /// failing the startup.
+    pub fn new(sources_json: Option<&str>, priority_json: Option<&str>) -> Result<Self, RpcError> {
+        let http = parse_json::<HashMap<IdentitySource, HttpSourceConfig>>(
+            sources_json,
+            "identity sources",
+        )?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(source, _)| {
+            if !source.is_http() {
+                warn!("Identity source {} has no HTTP endpoint", source.as_str());
+            }
+            source.is_http()
+        })
+        .collect::<HashMap<_, _>>();
+
+        let mut priorities = parse_json::<HashMap<String, Vec<IdentitySource>>>(
+            priority_json,
+            "identity sources priority",
+        )?
+        .unwrap_or_default();
+        // HTTP sources without the configured endpoint are skipped
+        for (key, sources) in priorities.iter_mut() {
+            sources.retain(|source| {
+                let is_available = !source.is_http() || http.contains_key(source);
+                if !is_available {
+                    warn!(
+                        "Identity source {} of {key} is not configured",
+                        source.as_str()
+                    );
+                }
+                is_available
+            });
+        }
+        let default_priority = priorities
+            .remove(DEFAULT_PRIORITY_KEY)
+            .unwrap_or_else(|| DEFAULT_PRIORITY.to_vec());
+
+        Ok(Self {
+            http,
+            default_priority,
+            project_priorities: priorities,
+        })
+    }
+
+    /// Sources priority order of the project
+    pub fn priority(&self, project_id: &str) -> &[IdentitySource] {
+        self.project_priorities
+            .get(project_id)
+            .unwrap_or(&self.default_priority)
+    }
+
+    pub fn default_priority(&self) -> &[IdentitySource] {
+        &self.default_priority
+    }
+
+    /// Priority orders of the projects that differ from the default order
+    pub fn project_priorities(&self) -> impl Iterator<Item = &[IdentitySource]> {
+        self.project_priorities
+            .values()
+            .map(Vec::as_slice)
+            .filter(|priority| *priority != self.default_priority.as_slice())
+    }
+
+    /// Identity of the address from the HTTP source, the not found responses
+    /// and the identities without the name are resolved to `None`
+    pub async fn lookup_http(
+        &self,
+        http_client: &reqwest::Client,
+        source: IdentitySource,
+        address: H160,
+    ) -> Result<Option<HttpIdentity>, HttpSourceError> {
+        let config = self
+            .http
+            .get(&source)
+            .ok_or(HttpSourceError::NotConfigured)?;
+        let url = config
+            .url
+            .replace("{address}", &to_checksum(&address, None));
+        let timeout = config
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_HTTP_SOURCE_TIMEOUT);
+
+        let mut request = http_client.get(url).timeout(timeout);
+        for (name, value) in &config.headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(HttpSourceError::Status(response.status()));
+        }
+
+        let identity = response.json::<HttpIdentity>().await?;
+        Ok(identity
+            .name
+            .as_deref()
+            .is_some_and(|name| !name.is_empty())
+            .then_some(identity))
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(
+    json: Option<&str>,
+    config_name: &str,
+) -> Result<Option<T>, RpcError> {
+    json.map(serde_json::from_str)
+        .transpose()
+        .map_err(|e| RpcError::InvalidConfiguration(format!("Invalid {config_name} JSON: {e}")))
+}
+
+/// Cache key segment of the sources priority order
+pub fn priority_key(priority: &[IdentitySource]) -> String {
+    iter::once("sources")
+        .chain(priority.iter().map(IdentitySource::as_str))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_priority() {
+        let sources = IdentitySources::new(None, None).unwrap();
+        assert_eq!(sources.priority("project_id"), DEFAULT_PRIORITY);
+        assert_eq!(sources.project_priorities().count(), 0);
+    }
+
+    #[test]
+    fn project_priorities() {
+        let sources = IdentitySources::new(
+            Some(r#"{"farcaster": {"url": "https://example.com/{address}"}}"#),
+            Some(
+                r#"{
+                    "*": ["ens", "local", "farcaster"],
+                    "project_id": ["lens", "farcaster", "ens"],
+                    "same_project_id": ["ens", "local", "farcaster"]
+                }"#,
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            sources.priority("other_project_id"),
+            [
+                IdentitySource::Ens,
+                IdentitySource::Local,
+                IdentitySource::Farcaster
+            ]
+        );
+        // Lens is skipped since its endpoint is not configured
+        assert_eq!(
+            sources.priority("project_id"),
+            [IdentitySource::Farcaster, IdentitySource::Ens]
+        );
+        assert_eq!(
+            sources.project_priorities().collect::<Vec<_>>(),
+            vec![[IdentitySource::Farcaster, IdentitySource::Ens].as_slice()]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_configs() {
+        assert!(IdentitySources::new(Some("invalid"), None).is_err());
+        assert!(IdentitySources::new(None, Some(r#"{"*": ["unknown"]}"#)).is_err());
+    }
+
+    #[test]
+    fn priority_cache_key() {
+        assert_eq!(
+            priority_key(&[IdentitySource::Lens, IdentitySource::Ens]),
+            "sources.lens.ens"
+        );
+    }
+}
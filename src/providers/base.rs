@@ -1,23 +1,22 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        HyperClient, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
+        UpstreamResponse,
+    },
     crate::{
         env::BaseConfig,
         error::{RpcError, RpcResult},
     },
     async_trait::async_trait,
-    axum::{
-        http::HeaderValue,
-        response::{IntoResponse, Response},
-    },
-    hyper::{client::HttpConnector, http, Client, Method},
-    hyper_tls::HttpsConnector,
+    axum::response::{IntoResponse, Response},
+    hyper::{http, Method},
     std::collections::HashMap,
     tracing::debug,
 };
 
 #[derive(Debug)]
 pub struct BaseProvider {
-    pub client: Client<HttpsConnector<HttpConnector>>,
+    pub client: HyperClient,
     pub supported_chains: HashMap<String, String>,
 }
 
@@ -60,31 +59,26 @@ impl RpcProvider for BaseProvider {
             .header("Content-Type", "application/json")
             .body(hyper::body::Body::from(body))?;
 
-        let response = self.client.request(hyper_request).await?;
+        let response = UpstreamResponse::read(self.client.request(hyper_request).await?).await?;
         let status = response.status();
-        let body = hyper::body::to_bytes(response.into_body()).await?;
 
-        if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
-            if response.error.is_some() && status.is_success() {
+        if let Some(error) = response.json_rpc_error() {
+            if status.is_success() {
                 debug!(
                     "Strange: provider returned JSON RPC error, but status {status} is success: \
-                     Base: {response:?}"
+                     Base: {error}"
                 );
             }
         }
 
-        let mut response = (status, body).into_response();
-        response
-            .headers_mut()
-            .insert("Content-Type", HeaderValue::from_static("application/json"));
-        Ok(response)
+        Ok(response.into_response())
     }
 }
 
 impl RpcProviderFactory<BaseConfig> for BaseProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &BaseConfig) -> Self {
-        let forward_proxy_client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+    fn new(provider_config: &BaseConfig, http_client: &HyperClient) -> Self {
+        let forward_proxy_client = http_client.clone();
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -1,6 +1,7 @@
 use {
     super::{HistoryProvider, OnRampProvider},
     crate::{
+        chains,
         error::{RpcError, RpcResult},
         handlers::{
             history::{
@@ -14,7 +15,6 @@ use {
             },
         },
         providers::ProviderKind,
-        utils::crypto::ChainId,
         Metrics,
     },
     async_trait::async_trait,
@@ -34,13 +34,18 @@ pub struct CoinbaseProvider {
 }
 
 impl CoinbaseProvider {
-    pub fn new(api_key: String, app_id: String, base_api_url: String) -> Self {
+    pub fn new(
+        api_key: String,
+        app_id: String,
+        base_api_url: String,
+        http_client: reqwest::Client,
+    ) -> Self {
         Self {
             provider_kind: ProviderKind::Coinbase,
             api_key,
             app_id,
             base_api_url,
-            http_client: reqwest::Client::new(),
+            http_client,
         }
     }
 
@@ -146,7 +151,7 @@ impl HistoryProvider for CoinbaseProvider {
                     sent_to: address.clone(),
                     status: f.status,
                     application: None,
-                    chain: ChainId::to_caip2(&f.purchase_network),
+                    chain: chains::to_caip2(&f.purchase_network),
                 },
                 transfers: Some(vec![HistoryTransactionTransfer {
                     fungible_info: Some(HistoryTransactionFungibleInfo {
@@ -0,0 +1,149 @@
+use {
+    super::{CallStatsSummary, ProviderKind},
+    rand::Rng,
+    std::collections::HashMap,
+    tracing::error,
+};
+
+const DEFAULT_SLO_SUCCESS_PERCENTAGE: u8 = 99;
+const DEFAULT_SLO_P95_LATENCY_MS: u64 = 1_000;
+/// Minimum number of the recent calls to evaluate the SLO, providers with
+/// less calls are considered meeting the SLO
+const SLO_MIN_CALLS: usize = 20;
+/// Share of the requests routed to the providers missing the SLO as if they
+/// were meeting it, so their recent calls stats are refreshed and they can
+/// recover from the demotion
+const SLO_PROBE_PROBABILITY: f64 = 0.05;
+/// Sampling weight multiplier of the free providers, the sampling weight is
+/// divided by the request cost increased by one
+const FREE_REQUEST_WEIGHT_MULTIPLIER: u64 = 10;
+
+/// Estimated provider price per request in micro-USD for the paid plans,
+/// public endpoints are free. Can be overridden per chain by the
+/// configuration
+pub fn default_request_cost(provider_kind: ProviderKind) -> u64 {
+    match provider_kind {
+        ProviderKind::Infura => 5,
+        ProviderKind::Quicknode => 5,
+        ProviderKind::GetBlock => 3,
+        ProviderKind::Pokt => 2,
+        _ => 0,
+    }
+}
+
+/// Scales down the provider sampling weight by its request cost, so the
+/// cheaper providers are sampled more often. Non-zero weights are kept
+/// non-zero
+pub fn cost_weighted(weight: u64, request_cost: u64) -> u64 {
+    if weight == 0 {
+        return 0;
+    }
+    (weight.saturating_mul(FREE_REQUEST_WEIGHT_MULTIPLIER) / request_cost.saturating_add(1)).max(1)
+}
+
+/// Parses the request costs overrides in JSON format keyed by
+/// `<chain_id>/<provider>`, e.g. `{"eip155:1/Infura": 10}`
+pub fn parse_request_costs(costs_json: &str) -> HashMap<(String, ProviderKind), u64> {
+    let costs: HashMap<String, u64> = match serde_json::from_str(costs_json) {
+        Ok(costs) => costs,
+        Err(e) => {
+            error!("Failed to parse the providers request costs: {}", e);
+            return HashMap::new();
+        }
+    };
+    costs
+        .into_iter()
+        .filter_map(|(field, cost)| {
+            let key = field.rsplit_once('/').and_then(|(chain_id, provider)| {
+                Some((chain_id.to_owned(), ProviderKind::from_str(provider)?))
+            });
+            if key.is_none() {
+                error!("Invalid provider request cost key: {}", field);
+            }
+            Some((key?, cost))
+        })
+        .collect()
+}
+
+/// Success rate and latency objective the cheaper providers must meet to be
+/// preferred over the pricier ones
+#[derive(Debug, Clone, Copy)]
+pub struct RoutingSlo {
+    success_rate: f64,
+    p95_latency_ms: u64,
+}
+
+impl RoutingSlo {
+    pub fn new(success_percentage: Option<u8>, p95_latency_ms: Option<u64>) -> Self {
+        Self {
+            success_rate: success_percentage
+                .unwrap_or(DEFAULT_SLO_SUCCESS_PERCENTAGE)
+                .min(100) as f64
+                / 100.0,
+            p95_latency_ms: p95_latency_ms.unwrap_or(DEFAULT_SLO_P95_LATENCY_MS),
+        }
+    }
+
+    pub fn is_met(&self, stats: &CallStatsSummary) -> bool {
+        if stats.calls < SLO_MIN_CALLS {
+            return true;
+        }
+        stats
+            .success_rate
+            .is_some_and(|success_rate| success_rate >= self.success_rate)
+            && stats
+                .p95_latency_ms
+                .is_some_and(|latency| latency <= self.p95_latency_ms)
+    }
+
+    /// Whether the provider missing the SLO is probed with the request
+    pub fn is_probe(&self) -> bool {
+        rand::thread_rng().gen_bool(SLO_PROBE_PROBABILITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(calls: usize, failures: usize, p95_latency_ms: u64) -> CallStatsSummary {
+        CallStatsSummary {
+            calls,
+            failures,
+            success_rate: Some((calls - failures) as f64 / calls as f64),
+            avg_latency_ms: Some(p95_latency_ms),
+            p95_latency_ms: Some(p95_latency_ms),
+        }
+    }
+
+    #[test]
+    fn evaluates_slo() {
+        let slo = RoutingSlo::new(Some(95), Some(500));
+        assert!(slo.is_met(&stats(100, 5, 500)));
+        assert!(!slo.is_met(&stats(100, 6, 500)));
+        assert!(!slo.is_met(&stats(100, 0, 501)));
+        // Not enough calls to evaluate
+        assert!(slo.is_met(&stats(10, 10, 5_000)));
+    }
+
+    #[test]
+    fn weights_by_cost() {
+        assert_eq!(cost_weighted(0, 0), 0);
+        assert_eq!(cost_weighted(100, 0), 1_000);
+        assert_eq!(cost_weighted(100, 4), 200);
+        assert!(cost_weighted(100, 0) > cost_weighted(100, 5));
+        // Expensive providers are still sampled
+        assert_eq!(cost_weighted(1, 1_000), 1);
+    }
+
+    #[test]
+    fn parses_request_costs() {
+        let costs = parse_request_costs(r#"{"eip155:1/Infura": 10, "eip155:1/Unknown": 1}"#);
+        assert_eq!(costs.len(), 1);
+        assert_eq!(
+            costs.get(&("eip155:1".to_owned(), ProviderKind::Infura)),
+            Some(&10)
+        );
+        assert!(parse_request_costs("not a json").is_empty());
+    }
+}
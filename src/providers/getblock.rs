@@ -1,25 +1,59 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        HyperClient, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
+        RpcWsProvider, UpstreamResponse,
+    },
     crate::{
         env::GetBlockConfig,
         error::{RpcError, RpcResult},
+        ws::pool::{UpstreamPool, UpstreamSession},
+        Metrics,
     },
     async_trait::async_trait,
-    axum::{
-        http::HeaderValue,
-        response::{IntoResponse, Response},
-    },
-    hyper::{client::HttpConnector, http, Client, Method},
-    hyper_tls::HttpsConnector,
-    std::collections::HashMap,
+    axum::response::{IntoResponse, Response},
+    hyper::{http, Method},
+    std::{collections::HashMap, sync::Arc},
     tracing::debug,
 };
 
 #[derive(Debug)]
 pub struct GetBlockProvider {
     base_api_url: String,
-    client: Client<HttpsConnector<HttpConnector>>,
+    client: HyperClient,
     supported_chains: HashMap<String, String>,
+    /// Regional endpoints API URLs and the access tokens by the continent
+    regional_chains: HashMap<String, (String, HashMap<String, String>)>,
+}
+
+impl GetBlockProvider {
+    async fn proxy_to(
+        &self,
+        api_url: &str,
+        access_token: &str,
+        body: hyper::body::Bytes,
+    ) -> RpcResult<Response> {
+        let uri = format!("{}/{}", api_url, access_token);
+
+        let hyper_request = hyper::http::Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(hyper::body::Body::from(body))?;
+
+        let response = UpstreamResponse::read(self.client.request(hyper_request).await?).await?;
+        let status = response.status();
+
+        if let Some(error) = response.json_rpc_error() {
+            if status.is_success() {
+                debug!(
+                    "Strange: provider returned JSON RPC error, but status {status} is success: \
+                     GetBlock RPC: {error}"
+                );
+            }
+        }
+
+        Ok(response.into_response())
+    }
 }
 
 impl Provider for GetBlockProvider {
@@ -52,52 +86,106 @@ impl RpcProvider for GetBlockProvider {
             .supported_chains
             .get(chain_id)
             .ok_or(RpcError::ChainNotFound)?;
+        self.proxy_to(&self.base_api_url, access_token_api, body)
+            .await
+    }
 
-        let uri = format!("{}/{}", self.base_api_url, access_token_api);
-
-        let hyper_request = hyper::http::Request::builder()
-            .method(Method::POST)
-            .uri(uri)
-            .header("Content-Type", "application/json")
-            .body(hyper::body::Body::from(body))?;
-
-        let response = self.client.request(hyper_request).await?;
-
-        let status = response.status();
-        let body = hyper::body::to_bytes(response.into_body()).await?;
-
-        if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
-            if response.error.is_some() && status.is_success() {
-                debug!(
-                    "Strange: provider returned JSON RPC error, but status {status} is success: \
-                     GetBlock RPC: {response:?}"
-                );
-            }
+    /// Regional endpoint is used when the access token for the chain is
+    /// issued for the client continent
+    #[tracing::instrument(skip(self, body), fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy_regional(
+        &self,
+        chain_id: &str,
+        body: hyper::body::Bytes,
+        continent: Option<&str>,
+    ) -> RpcResult<Response> {
+        let regional = continent
+            .and_then(|continent| self.regional_chains.get(continent))
+            .and_then(|(api_url, access_tokens)| Some((api_url, access_tokens.get(chain_id)?)));
+        match regional {
+            Some((api_url, access_token)) => self.proxy_to(api_url, access_token, body).await,
+            None => self.proxy(chain_id, body).await,
         }
-
-        let mut response = (status, body).into_response();
-        response
-            .headers_mut()
-            .insert("Content-Type", HeaderValue::from_static("application/json"));
-        Ok(response)
     }
 }
 
 impl RpcProviderFactory<GetBlockConfig> for GetBlockProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &GetBlockConfig) -> Self {
-        let client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+    fn new(provider_config: &GetBlockConfig, http_client: &HyperClient) -> Self {
+        let client = http_client.clone();
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
             .map(|(k, v)| (k.clone(), v.0.clone()))
             .collect();
-        let base_api_url = "https://go.getblock.io".to_string();
+        let base_api_url = GetBlockConfig::default_api_url().to_string();
 
         GetBlockProvider {
             base_api_url,
             client,
             supported_chains,
+            regional_chains: provider_config.regional_chains.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GetBlockWsProvider {
+    base_ws_url: String,
+    supported_chains: HashMap<String, String>,
+    pool: UpstreamPool,
+}
+
+impl Provider for GetBlockWsProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.supported_chains.contains_key(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.supported_chains.keys().cloned().collect()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::GetBlock
+    }
+}
+
+#[async_trait]
+impl RateLimited for GetBlockWsProvider {
+    async fn is_rate_limited(&self, response: &mut Response) -> bool {
+        response.status() == http::StatusCode::TOO_MANY_REQUESTS
+            || response.status() == http::StatusCode::PAYMENT_REQUIRED
+    }
+}
+
+#[async_trait]
+impl RpcWsProvider for GetBlockWsProvider {
+    #[tracing::instrument(skip_all, fields(provider = %self.provider_kind()), level = "debug")]
+    async fn connect(&self, chain_id: &str, metrics: Arc<Metrics>) -> RpcResult<UpstreamSession> {
+        let access_token = self
+            .supported_chains
+            .get(chain_id)
+            .ok_or(RpcError::ChainNotFound)?;
+
+        let uri = format!("{}/{}", self.base_ws_url, access_token);
+        self.pool.session(chain_id, &uri, metrics).await
+    }
+}
+
+impl RpcProviderFactory<GetBlockConfig> for GetBlockWsProvider {
+    #[tracing::instrument(level = "debug")]
+    fn new(provider_config: &GetBlockConfig, _http_client: &HyperClient) -> Self {
+        let supported_chains: HashMap<String, String> = provider_config
+            .supported_ws_chains
+            .iter()
+            .map(|(k, v)| (k.clone(), v.0.clone()))
+            .collect();
+        let base_ws_url = "wss://go.getblock.io".to_string();
+
+        GetBlockWsProvider {
+            base_ws_url,
+            supported_chains,
+            pool: UpstreamPool::new(ProviderKind::GetBlock),
         }
     }
 }
@@ -0,0 +1,141 @@
+//! Upstream HTTP clients shared by all of the providers, so the connections
+//! to the same upstream hosts are pooled and reused across the providers.
+//!
+//! The JSON-RPC providers client speaks HTTP/1.1 only, since the `hyper-tls`
+//! connector doesn't report the HTTP/2 negotiated over ALPN to `hyper`. The
+//! REST API providers client uses HTTP/2 with the hosts negotiating it.
+
+use {
+    crate::metrics::Metrics,
+    hyper::{client::HttpConnector, service::Service, Client, Uri},
+    hyper_tls::HttpsConnector,
+    std::{
+        future::Future,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll},
+        time::{Duration, SystemTime},
+    },
+};
+
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 64;
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_HTTP2_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Client of the JSON-RPC providers
+pub type HyperClient = Client<MeteredConnector, hyper::Body>;
+
+/// Upstream connections pooling and keep-alive settings
+#[derive(Debug, Clone, Copy)]
+pub struct HttpClientConfig {
+    /// Maximum number of the idle connections kept in the pool per upstream
+    /// host, it doesn't limit the number of the open connections to the host
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub tcp_keepalive: Duration,
+    pub connect_timeout: Duration,
+    /// Keep-alive pings interval of the REST API providers HTTP/2
+    /// connections
+    pub http2_keepalive_interval: Duration,
+}
+
+impl HttpClientConfig {
+    pub fn new(
+        pool_max_idle_per_host: Option<usize>,
+        pool_idle_timeout_secs: Option<u64>,
+        tcp_keepalive_secs: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+        http2_keepalive_interval_secs: Option<u64>,
+    ) -> Self {
+        Self {
+            pool_max_idle_per_host: pool_max_idle_per_host
+                .unwrap_or(DEFAULT_POOL_MAX_IDLE_PER_HOST),
+            pool_idle_timeout: pool_idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT),
+            tcp_keepalive: tcp_keepalive_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_TCP_KEEPALIVE),
+            connect_timeout: connect_timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            http2_keepalive_interval: http2_keepalive_interval_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_HTTP2_KEEPALIVE_INTERVAL),
+        }
+    }
+}
+
+pub fn build_hyper_client(config: &HttpClientConfig, metrics: Arc<Metrics>) -> HyperClient {
+    let mut http_connector = HttpConnector::new();
+    http_connector.enforce_http(false);
+    http_connector.set_nodelay(true);
+    http_connector.set_keepalive(Some(config.tcp_keepalive));
+    http_connector.set_connect_timeout(Some(config.connect_timeout));
+    // Only HTTP/1.1 is advertised over ALPN, the client would otherwise send
+    // HTTP/1.1 requests over the negotiated HTTP/2 connections
+    let tls_connector = native_tls::TlsConnector::builder()
+        .request_alpns(&["http/1.1"])
+        .build()
+        .expect("Failed to build the upstream TLS connector");
+    let connector = MeteredConnector {
+        inner: HttpsConnector::from((http_connector, tls_connector.into())),
+        metrics,
+    };
+
+    Client::builder()
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout)
+        .build(connector)
+}
+
+/// Client of the REST API providers
+pub fn build_reqwest_client(config: &HttpClientConfig) -> reqwest::Client {
+    reqwest::Client::builder()
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout)
+        .tcp_keepalive(config.tcp_keepalive)
+        .tcp_nodelay(true)
+        .connect_timeout(config.connect_timeout)
+        .http2_adaptive_window(true)
+        .http2_keep_alive_interval(config.http2_keepalive_interval)
+        .http2_keep_alive_while_idle(true)
+        .build()
+        .expect("Failed to build the upstream HTTP client")
+}
+
+/// HTTPS connector recording the new upstream connections metrics, the
+/// connections reused from the pool are not passing through the connector
+#[derive(Debug, Clone)]
+pub struct MeteredConnector {
+    inner: HttpsConnector<HttpConnector>,
+    metrics: Arc<Metrics>,
+}
+
+impl Service<Uri> for MeteredConnector {
+    type Response = <HttpsConnector<HttpConnector> as Service<Uri>>::Response;
+    type Error = <HttpsConnector<HttpConnector> as Service<Uri>>::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let host = uri.host().unwrap_or_default().to_owned();
+        let metrics = self.metrics.clone();
+        let connect_start = SystemTime::now();
+        let connecting = self.inner.call(uri);
+        Box::pin(async move {
+            let result = connecting.await;
+            metrics.add_upstream_connection(
+                host,
+                result.is_ok(),
+                connect_start.elapsed().unwrap_or_default(),
+            );
+            result
+        })
+    }
+}
@@ -1,29 +1,24 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider, WS_PROXY_TASK_METRICS,
+        HyperClient, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
+        RpcWsProvider, UpstreamResponse,
     },
     crate::{
         env::InfuraConfig,
         error::{RpcError, RpcResult},
-        ws,
+        ws::pool::{UpstreamPool, UpstreamSession},
+        Metrics,
     },
     async_trait::async_trait,
-    axum::{
-        http::HeaderValue,
-        response::{IntoResponse, Response},
-    },
-    axum_tungstenite::WebSocketUpgrade,
-    hyper::{client::HttpConnector, http, Client, Method, StatusCode},
-    hyper_tls::HttpsConnector,
-    std::collections::HashMap,
+    axum::response::{IntoResponse, Response},
+    hyper::{http, Method, StatusCode},
+    std::{collections::HashMap, sync::Arc},
     tracing::debug,
-    wc::future::FutureExt,
 };
 
 #[derive(Debug)]
 pub struct InfuraProvider {
-    pub client: Client<HttpsConnector<HttpConnector>>,
+    pub client: HyperClient,
     pub project_id: String,
     pub supported_chains: HashMap<String, String>,
 }
@@ -32,6 +27,7 @@ pub struct InfuraProvider {
 pub struct InfuraWsProvider {
     pub project_id: String,
     pub supported_chains: HashMap<String, String>,
+    pub pool: UpstreamPool,
 }
 
 impl Provider for InfuraWsProvider {
@@ -61,26 +57,14 @@ impl RateLimited for InfuraWsProvider {
 #[async_trait]
 impl RpcWsProvider for InfuraWsProvider {
     #[tracing::instrument(skip_all, fields(provider = %self.provider_kind()), level = "debug")]
-    async fn proxy(
-        &self,
-        ws: WebSocketUpgrade,
-        query_params: RpcQueryParams,
-    ) -> RpcResult<Response> {
-        let chain = &self
+    async fn connect(&self, chain_id: &str, metrics: Arc<Metrics>) -> RpcResult<UpstreamSession> {
+        let chain = self
             .supported_chains
-            .get(&query_params.chain_id.to_lowercase())
+            .get(chain_id)
             .ok_or(RpcError::ChainNotFound)?;
 
-        let project_id = query_params.project_id;
-
         let uri = format!("wss://{}.infura.io/ws/v3/{}", chain, self.project_id);
-
-        let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri).await?;
-
-        Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
-                .with_metrics(WS_PROXY_TASK_METRICS.with_name("infura"))
-        }))
+        self.pool.session(chain_id, &uri, metrics).await
     }
 }
 
@@ -125,36 +109,29 @@ impl RpcProvider for InfuraProvider {
             .header("Content-Type", "application/json")
             .body(hyper::body::Body::from(body))?;
 
-        let response = self.client.request(hyper_request).await?;
+        let response = UpstreamResponse::read(self.client.request(hyper_request).await?).await?;
         let status = response.status();
-        let body = hyper::body::to_bytes(response.into_body()).await?;
-
-        if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
-            if let Some(error) = &response.error {
-                if status.is_success() {
-                    debug!(
-                        "Strange: provider returned JSON RPC error, but status {status} is \
-                         success: Infura: {response:?}"
-                    );
-                }
-                if error.code == -32603 {
-                    return Ok((StatusCode::INTERNAL_SERVER_ERROR, body).into_response());
-                }
+
+        if let Some(error) = response.json_rpc_error() {
+            if status.is_success() {
+                debug!(
+                    "Strange: provider returned JSON RPC error, but status {status} is success: \
+                     Infura: {error}"
+                );
+            }
+            if error.code == -32603 {
+                return Ok(response.into_response_with_status(StatusCode::INTERNAL_SERVER_ERROR));
             }
         }
 
-        let mut response = (status, body).into_response();
-        response
-            .headers_mut()
-            .insert("Content-Type", HeaderValue::from_static("application/json"));
-        Ok(response)
+        Ok(response.into_response())
     }
 }
 
 impl RpcProviderFactory<InfuraConfig> for InfuraProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &InfuraConfig) -> Self {
-        let forward_proxy_client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+    fn new(provider_config: &InfuraConfig, http_client: &HyperClient) -> Self {
+        let forward_proxy_client = http_client.clone();
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -171,7 +148,7 @@ impl RpcProviderFactory<InfuraConfig> for InfuraProvider {
 
 impl RpcProviderFactory<InfuraConfig> for InfuraWsProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &InfuraConfig) -> Self {
+    fn new(provider_config: &InfuraConfig, _http_client: &HyperClient) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_ws_chains
             .iter()
@@ -181,6 +158,7 @@ impl RpcProviderFactory<InfuraConfig> for InfuraWsProvider {
         InfuraWsProvider {
             supported_chains,
             project_id: provider_config.project_id.clone(),
+            pool: UpstreamPool::new(ProviderKind::Infura),
         }
     }
 }
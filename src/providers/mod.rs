@@ -20,19 +20,21 @@ use {
                 quotes::{OnRampBuyQuotesParams, OnRampBuyQuotesResponse},
             },
             portfolio::{PortfolioQueryParams, PortfolioResponseBody},
-            RpcQueryParams, SupportedCurrencies,
+            SupportedCurrencies,
         },
-        utils::crypto::CaipNamespaces,
+        storage::error::StorageError,
+        utils::crypto::{self, CaipNamespaces},
+        ws::pool::UpstreamSession,
         Metrics,
     },
     async_trait::async_trait,
     axum::response::Response,
-    axum_tungstenite::WebSocketUpgrade,
+    deadpool_redis::{redis::AsyncCommands, Pool},
     hyper::http::HeaderValue,
     rand::{distributions::WeightedIndex, prelude::Distribution, rngs::OsRng},
     serde::{Deserialize, Serialize},
     std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         fmt::{Debug, Display},
         hash::Hash,
         sync::Arc,
@@ -46,16 +48,24 @@ mod base;
 mod berachain;
 mod binance;
 mod coinbase;
+mod cost;
 mod getblock;
+mod http_client;
 mod infura;
 mod mantle;
 mod near;
 mod one_inch;
+mod overrides;
 mod pimlico;
 mod pokt;
+mod policy;
 mod publicnode;
 mod quicknode;
+mod regions;
+pub mod shadow;
 mod solscan;
+mod stats;
+mod upstream;
 mod weights;
 pub mod zerion;
 mod zksync;
@@ -66,21 +76,27 @@ pub use {
     base::BaseProvider,
     berachain::BerachainProvider,
     binance::BinanceProvider,
-    getblock::GetBlockProvider,
+    cost::RoutingSlo,
+    getblock::{GetBlockProvider, GetBlockWsProvider},
+    http_client::{HttpClientConfig, HyperClient},
     infura::{InfuraProvider, InfuraWsProvider},
     mantle::MantleProvider,
     near::NearProvider,
     one_inch::OneInchProvider,
+    overrides::WeightOverride,
     pimlico::PimlicoProvider,
     pokt::PoktProvider,
-    publicnode::PublicnodeProvider,
-    quicknode::QuicknodeProvider,
+    policy::{CallPolicies, CallPolicy},
+    publicnode::{PublicnodeProvider, PublicnodeWsProvider},
+    quicknode::{QuicknodeProvider, QuicknodeWsProvider},
     solscan::SolScanProvider,
+    stats::{CallStats, CallStatsSummary, CircuitState, ProviderStatus},
+    upstream::{with_body_deadline, UpstreamResponse},
     zksync::ZKSyncProvider,
     zora::{ZoraProvider, ZoraWsProvider},
 };
 
-static WS_PROXY_TASK_METRICS: TaskMetrics = TaskMetrics::new("ws_proxy_task");
+pub static WS_PROXY_TASK_METRICS: TaskMetrics = TaskMetrics::new("ws_proxy_task");
 
 pub type WeightResolver = HashMap<String, HashMap<ProviderKind, Weight>>;
 
@@ -103,6 +119,36 @@ pub struct ProvidersConfig {
     pub one_inch_referrer: Option<String>,
     /// GetBlock provider access tokens in JSON format
     pub getblock_access_tokens: Option<String>,
+    /// GetBlock provider WebSocket access tokens in JSON format
+    pub getblock_ws_access_tokens: Option<String>,
+    /// GetBlock provider access tokens of the regional endpoints by the
+    /// continent in JSON format
+    pub getblock_regional_access_tokens: Option<String>,
+    /// Shadow providers names by the chain id in JSON format
+    pub shadow_providers: Option<String>,
+    /// Percentages of the read requests mirrored to the shadow providers by
+    /// the chain id in JSON format
+    pub shadow_traffic_percentages: Option<String>,
+    /// Request costs in micro-USD by `<chain_id>/<provider>` in JSON format
+    pub request_costs: Option<String>,
+    /// Minimum success percentage of the cheaper providers to be preferred
+    pub routing_slo_success_percentage: Option<u8>,
+    /// Maximum p95 latency of the cheaper providers to be preferred
+    pub routing_slo_p95_latency_ms: Option<u64>,
+    /// Continents of the providers endpoints by the provider in JSON format,
+    /// overrides the providers configs
+    pub continents: Option<String>,
+    /// RPC calls timeouts and failover policies by the chain id and method in
+    /// JSON format
+    pub call_policies: Option<String>,
+    /// Upstream HTTP connections pool and keep-alive settings, the pool keeps
+    /// at most the max idle connections per host without limiting the open
+    /// connections
+    pub http_pool_max_idle_per_host: Option<usize>,
+    pub http_pool_idle_timeout_secs: Option<u64>,
+    pub http_tcp_keepalive_secs: Option<u64>,
+    pub http_connect_timeout_ms: Option<u64>,
+    pub http2_keepalive_interval_secs: Option<u64>,
     /// Pimlico API token key
     pub pimlico_api_key: String,
     /// SolScan API v1 and v2 token keys
@@ -114,6 +160,26 @@ pub struct ProvidersConfig {
 pub struct SupportedChains {
     pub http: HashSet<String>,
     pub ws: HashSet<String>,
+    /// Chains served by the archive providers
+    #[serde(skip)]
+    pub archive: HashSet<String>,
+}
+
+impl SupportedChains {
+    /// Whether the WebSocket clients are served for the chain, the EVM chains
+    /// without a native WebSocket provider are emulated over HTTP providers
+    pub fn is_ws_supported(&self, chain_id: &str) -> bool {
+        self.ws.contains(chain_id) || self.is_ws_emulation_supported(chain_id)
+    }
+
+    /// Whether the WebSocket interface can be emulated over the HTTP providers
+    pub fn is_ws_emulation_supported(&self, chain_id: &str) -> bool {
+        self.http.contains(chain_id)
+            && matches!(
+                crypto::disassemble_caip2(chain_id),
+                Ok((CaipNamespaces::Eip155, _))
+            )
+    }
 }
 
 pub struct ProviderRepository {
@@ -125,9 +191,30 @@ pub struct ProviderRepository {
     weight_resolver: WeightResolver,
     ws_weight_resolver: WeightResolver,
 
+    /// Shadow providers of the chains, they are never used to respond
+    shadow_routing: shadow::ShadowRouting,
+
+    /// Estimated request costs by the chain id and provider
+    request_costs: HashMap<String, HashMap<ProviderKind, u64>>,
+    /// Configured request costs overriding the default providers costs
+    request_costs_overrides: HashMap<(String, ProviderKind), u64>,
+    routing_slo: RoutingSlo,
+    /// Continents the providers endpoints are located in
+    provider_continents: HashMap<ProviderKind, Vec<String>>,
+
     prometheus_client: prometheus_http_query::Client,
     prometheus_workspace_header: String,
 
+    /// Recent RPC calls outcomes per provider and chain
+    pub call_stats: CallStats,
+    /// Timeouts and failover budgets of the RPC calls
+    pub call_policies: CallPolicies,
+    /// Redis pool for the providers responses caching and weight overrides
+    redis_pool: Option<Arc<Pool>>,
+    /// Shared upstream HTTP clients of the JSON-RPC and REST API providers
+    rpc_http_client: HyperClient,
+    pub api_http_client: reqwest::Client,
+
     pub history_providers: HashMap<CaipNamespaces, Arc<dyn HistoryProvider>>,
     pub portfolio_provider: Arc<dyn PortfolioProvider>,
     pub coinbase_pay_provider: Arc<dyn HistoryProvider>,
@@ -139,8 +226,7 @@ pub struct ProviderRepository {
 }
 
 impl ProviderRepository {
-    #[allow(clippy::new_without_default)]
-    pub fn new(config: &ProvidersConfig) -> Self {
+    pub fn new(config: &ProvidersConfig, metrics: Arc<Metrics>) -> Self {
         let prometheus_client = {
             let prometheus_query_url = config
                 .prometheus_query_url
@@ -209,13 +295,29 @@ impl ProviderRepository {
             warn!("ONE_INCH_REFERRER is not set");
         }
 
-        let zerion_provider = Arc::new(ZerionProvider::new(zerion_api_key));
-        let one_inch_provider = Arc::new(OneInchProvider::new(one_inch_api_key, one_inch_referrer));
+        let http_client_config = HttpClientConfig::new(
+            config.http_pool_max_idle_per_host,
+            config.http_pool_idle_timeout_secs,
+            config.http_tcp_keepalive_secs,
+            config.http_connect_timeout_ms,
+            config.http2_keepalive_interval_secs,
+        );
+        let rpc_http_client = http_client::build_hyper_client(&http_client_config, metrics);
+        let api_http_client = http_client::build_reqwest_client(&http_client_config);
+
+        let zerion_provider =
+            Arc::new(ZerionProvider::new(zerion_api_key, api_http_client.clone()));
+        let one_inch_provider = Arc::new(OneInchProvider::new(
+            one_inch_api_key,
+            one_inch_referrer,
+            api_http_client.clone(),
+        ));
         let portfolio_provider = zerion_provider.clone();
         let solscan_provider = Arc::new(SolScanProvider::new(
             config.solscan_api_v1_token.clone(),
             config.solscan_api_v2_token.clone(),
             redis_pool.clone(),
+            api_http_client.clone(),
         ));
 
         let mut balance_providers: HashMap<CaipNamespaces, Arc<dyn BalanceProvider>> =
@@ -232,9 +334,13 @@ impl ProviderRepository {
             coinbase_api_key,
             coinbase_app_id,
             "https://pay.coinbase.com/api/v1".into(),
+            api_http_client.clone(),
         ));
 
-        let bundler_ops_provider = Arc::new(PimlicoProvider::new(config.pimlico_api_key.clone()));
+        let bundler_ops_provider = Arc::new(PimlicoProvider::new(
+            config.pimlico_api_key.clone(),
+            api_http_client.clone(),
+        ));
 
         let mut fungible_price_providers: HashMap<CaipNamespaces, Arc<dyn FungiblePriceProvider>> =
             HashMap::new();
@@ -245,13 +351,38 @@ impl ProviderRepository {
             supported_chains: SupportedChains {
                 http: HashSet::new(),
                 ws: HashSet::new(),
+                archive: HashSet::new(),
             },
             providers: HashMap::new(),
             ws_providers: HashMap::new(),
             weight_resolver: HashMap::new(),
             ws_weight_resolver: HashMap::new(),
+            shadow_routing: shadow::ShadowRouting::new(
+                config.shadow_providers.as_deref(),
+                config.shadow_traffic_percentages.as_deref(),
+            ),
+            request_costs: HashMap::new(),
+            request_costs_overrides: config
+                .request_costs
+                .as_deref()
+                .map(cost::parse_request_costs)
+                .unwrap_or_default(),
+            routing_slo: RoutingSlo::new(
+                config.routing_slo_success_percentage,
+                config.routing_slo_p95_latency_ms,
+            ),
+            provider_continents: config
+                .continents
+                .as_deref()
+                .map(regions::parse_provider_continents)
+                .unwrap_or_default(),
             prometheus_client,
             prometheus_workspace_header,
+            call_stats: CallStats::default(),
+            call_policies: CallPolicies::new(config.call_policies.as_deref()),
+            redis_pool,
+            rpc_http_client,
+            api_http_client,
             history_providers,
             portfolio_provider,
             coinbase_pay_provider: coinbase_pay_provider.clone(),
@@ -268,7 +399,29 @@ impl ProviderRepository {
         &self,
         chain_id: &str,
         max_providers: usize,
+        continent: Option<&str>,
     ) -> Result<Vec<Arc<dyn RpcProvider>>, RpcError> {
+        self.get_provider_kinds_for_chain_id(chain_id, max_providers, continent)?
+            .into_iter()
+            .map(|provider| {
+                self.providers.get(&provider).cloned().ok_or_else(|| {
+                    RpcError::WeightedProvidersIndex(format!(
+                        "Provider not found during the weighted index check: {}",
+                        provider
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the providers kinds for the chain in the routing order, used
+    /// by the `get_provider_for_chain_id` and the routing dry runs
+    pub fn get_provider_kinds_for_chain_id(
+        &self,
+        chain_id: &str,
+        max_providers: usize,
+        continent: Option<&str>,
+    ) -> Result<Vec<ProviderKind>, RpcError> {
         let Some(providers) = self.weight_resolver.get(chain_id) else {
             return Err(RpcError::UnsupportedChain(chain_id.to_string()));
         };
@@ -277,81 +430,143 @@ impl ProviderRepository {
             return Err(RpcError::UnsupportedChain(chain_id.to_string()));
         }
 
-        let weights: Vec<_> = providers
+        // Pinned providers are tried first and excluded from the sampling
+        let mut preferred = providers
             .iter()
-            .map(|(_, weight)| weight.value())
-            .map(|w| w.min(1))
-            .collect();
-        let non_zero_weight_providers = weights.iter().filter(|&x| *x > 0).count();
-        let keys = providers.keys().cloned().collect::<Vec<_>>();
-
-        match WeightedIndex::new(weights) {
-            Ok(mut dist) => {
-                let providers_to_iterate = std::cmp::min(max_providers, non_zero_weight_providers);
-                let providers_result = (0..providers_to_iterate)
-                    .map(|i| {
-                        let dist_key = dist.sample(&mut OsRng);
-                        let provider = keys.get(dist_key).ok_or_else(|| {
-                            RpcError::WeightedProvidersIndex(format!(
-                                "Failed to get random provider for chain_id: {}",
-                                chain_id
-                            ))
-                        })?;
-
-                        // Update the weight of the provider to 0 to remove it from the next
-                        // sampling, as updating weights returns an error if
-                        // all weights are zero
-                        if i < providers_to_iterate - 1 {
-                            if let Err(e) = dist.update_weights(&[(dist_key, &0)]) {
-                                return Err(RpcError::WeightedProvidersIndex(format!(
-                                    "Failed to update weight in sampling iteration: {}",
-                                    e
-                                )));
-                            }
-                        };
-
-                        self.providers.get(provider).cloned().ok_or_else(|| {
-                            RpcError::WeightedProvidersIndex(format!(
-                                "Provider not found during the weighted index check: {}",
-                                provider
-                            ))
-                        })
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(providers_result)
+            .filter(|(_, weight)| weight.weight_override() == Some(WeightOverride::Prefer))
+            .map(|(provider_kind, _)| *provider_kind)
+            .collect::<Vec<_>>();
+        preferred.sort_by_key(|provider_kind| provider_kind.to_string());
+        preferred.truncate(max_providers);
+
+        // Providers are grouped into the tiers by meeting the SLO and by the
+        // closeness to the client, the tiers are tried in order and the
+        // providers are sampled by their weights scaled by the request costs
+        // within the tier. Providers missing the SLO are probed with a small
+        // share of the requests to recover from the demotion
+        let mut tiers = BTreeMap::<(bool, bool), Vec<(ProviderKind, u64)>>::new();
+        for (provider_kind, weight) in providers {
+            if weight.value() == 0 || preferred.contains(provider_kind) {
+                continue;
             }
-            Err(e) => {
-                // Respond with temporarily unavailable when all weights are 0 for
-                // a chain providers
-                warn!("Failed to create weighted index: {}", e);
-                Err(RpcError::ChainTemporarilyUnavailable(chain_id.to_string()))
+            // Summaries are maintained on each call record and the probe is
+            // drawn only for the providers missing the SLO
+            let meets_slo = self
+                .routing_slo
+                .is_met(&self.call_stats.summary(*provider_kind, chain_id))
+                || self.routing_slo.is_probe();
+            let is_close =
+                regions::is_close_to(self.get_provider_continents(*provider_kind), continent);
+            let cost = self.get_request_cost(chain_id, *provider_kind);
+            tiers
+                .entry((!meets_slo, !is_close))
+                .or_default()
+                .push((*provider_kind, cost::cost_weighted(weight.value(), cost)));
+        }
+        if tiers.is_empty() && preferred.is_empty() {
+            // Respond with temporarily unavailable when all weights are 0 for
+            // a chain providers
+            warn!("All providers weights are zero for the chain: {}", chain_id);
+            return Err(RpcError::ChainTemporarilyUnavailable(chain_id.to_string()));
+        }
+
+        for candidates in tiers.into_values() {
+            let remaining = max_providers.saturating_sub(preferred.len());
+            if remaining == 0 {
+                break;
             }
+            preferred.extend(sample_weighted(candidates, remaining));
         }
+        Ok(preferred)
     }
 
-    #[tracing::instrument(skip(self), level = "debug")]
-    pub fn get_ws_provider_for_chain_id(&self, chain_id: &str) -> Option<Arc<dyn RpcWsProvider>> {
-        let providers = self.ws_weight_resolver.get(chain_id)?;
-        if providers.is_empty() {
-            return None;
-        }
+    pub fn get_provider_continents(&self, provider_kind: ProviderKind) -> &[String] {
+        self.provider_continents
+            .get(&provider_kind)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
 
-        let weights: Vec<_> = providers.iter().map(|(_, weight)| weight.value()).collect();
-        let keys = providers.keys().cloned().collect::<Vec<_>>();
-        match WeightedIndex::new(weights) {
-            Ok(dist) => {
-                let random = dist.sample(&mut OsRng);
-                let provider = keys
-                    .get(random)
-                    .expect("Failed to get random provider: out of index");
+    /// Returns the estimated request cost of the provider for the chain in
+    /// micro-USD
+    pub fn get_request_cost(&self, chain_id: &str, provider_kind: ProviderKind) -> u64 {
+        self.request_costs
+            .get(chain_id)
+            .and_then(|costs| costs.get(&provider_kind))
+            .copied()
+            .unwrap_or_else(|| cost::default_request_cost(provider_kind))
+    }
 
-                self.ws_providers.get(provider).cloned()
+    /// Returns the HTTP providers status for the chain or for all of the
+    /// supported chains sorted by the chain id
+    pub fn get_providers_status(
+        &self,
+        chain_id: Option<&str>,
+    ) -> RpcResult<Vec<(String, Vec<ProviderStatus>)>> {
+        let chains = match chain_id {
+            Some(chain_id) => {
+                let providers = self
+                    .weight_resolver
+                    .get_key_value(chain_id)
+                    .ok_or_else(|| RpcError::UnsupportedChain(chain_id.to_string()))?;
+                vec![providers]
             }
-            Err(e) => {
-                warn!("Failed to create weighted index: {}", e);
-                None
+            None => {
+                let mut chains = self.weight_resolver.iter().collect::<Vec<_>>();
+                chains.sort_by(|(a, _), (b, _)| a.cmp(b));
+                chains
             }
-        }
+        };
+
+        Ok(chains
+            .into_iter()
+            .map(|(chain_id, providers)| {
+                let mut providers_status = providers
+                    .iter()
+                    .map(|(provider_kind, weight)| {
+                        let weight_value = weight.value();
+                        ProviderStatus {
+                            provider: provider_kind.to_string(),
+                            weight: weight_value,
+                            priority: weight.priority(),
+                            weight_override: weight.weight_override(),
+                            request_cost: self.get_request_cost(chain_id, *provider_kind),
+                            continents: self.get_provider_continents(*provider_kind).to_vec(),
+                            circuit: if weight_value > 0 {
+                                CircuitState::Closed
+                            } else {
+                                CircuitState::Open
+                            },
+                            recent_calls: self.call_stats.summary(*provider_kind, chain_id),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                providers_status.sort_by(|a, b| b.weight.cmp(&a.weight));
+                (chain_id.clone(), providers_status)
+            })
+            .collect())
+    }
+
+    /// Returns the WebSocket providers for the chain in the weighted random
+    /// order to fail over through them
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub fn get_ws_provider_for_chain_id(
+        &self,
+        chain_id: &str,
+        max_providers: usize,
+    ) -> Vec<Arc<dyn RpcWsProvider>> {
+        let Some(providers) = self.ws_weight_resolver.get(chain_id) else {
+            return vec![];
+        };
+
+        let candidates = providers
+            .iter()
+            .map(|(provider_kind, weight)| (*provider_kind, weight.value()))
+            .collect::<Vec<_>>();
+        sample_weighted(candidates, max_providers)
+            .into_iter()
+            .filter_map(|provider_kind| self.ws_providers.get(&provider_kind).cloned())
+            .collect()
     }
 
     pub fn add_ws_provider<
@@ -361,7 +576,7 @@ impl ProviderRepository {
         &mut self,
         provider_config: C,
     ) {
-        let ws_provider = T::new(&provider_config);
+        let ws_provider = T::new(&provider_config, &self.rpc_http_client);
         let arc_ws_provider = Arc::new(ws_provider);
 
         self.ws_providers
@@ -385,19 +600,41 @@ impl ProviderRepository {
         &mut self,
         provider_config: C,
     ) {
-        let provider = T::new(&provider_config);
+        let provider = T::new(&provider_config, &self.rpc_http_client);
         let arc_provider = Arc::new(provider);
 
         self.providers
-            .insert(provider_config.provider_kind(), arc_provider);
+            .insert(provider_config.provider_kind(), arc_provider.clone());
 
         let provider_kind = provider_config.provider_kind();
+        // Configured continents take precedence over the provider config
+        self.provider_continents
+            .entry(provider_kind)
+            .or_insert_with(|| provider_config.continents());
+        let is_archive = provider_config.is_archive();
         let supported_chains = provider_config.supported_chains();
 
         supported_chains
             .into_iter()
             .for_each(|(chain_id, (_, weight))| {
+                // Shadow providers only receive the mirrored chain requests
+                if self.shadow_routing.is_shadow(&chain_id, provider_kind) {
+                    self.shadow_routing.add(chain_id, arc_provider.clone());
+                    return;
+                }
                 self.supported_chains.http.insert(chain_id.clone());
+                if is_archive {
+                    self.supported_chains.archive.insert(chain_id.clone());
+                }
+                let cost = self
+                    .request_costs_overrides
+                    .get(&(chain_id.clone(), provider_kind))
+                    .copied()
+                    .unwrap_or_else(|| cost::default_request_cost(provider_kind));
+                self.request_costs
+                    .entry(chain_id.clone())
+                    .or_default()
+                    .insert(provider_kind, cost);
                 self.weight_resolver
                     .entry(chain_id)
                     .or_default()
@@ -406,10 +643,22 @@ impl ProviderRepository {
         debug!("Added provider: {}", provider_kind);
     }
 
+    /// Returns the shadow call to mirror the chain request to according to
+    /// the configured chain traffic percentage
+    pub fn get_shadow_call_for_chain_id(&self, chain_id: &str) -> Option<shadow::ShadowCall> {
+        self.shadow_routing.call_for_chain_id(chain_id)
+    }
+
     #[tracing::instrument(skip_all, level = "debug")]
     pub async fn update_weights(&self, metrics: &crate::Metrics) {
         debug!("Updating weights");
 
+        // Overrides are applied on top of the calculated weights, so they are
+        // synced on each update to converge with the other replicas
+        if let Err(e) = self.sync_weight_overrides().await {
+            warn!("Failed to sync the provider weight overrides: {}", e);
+        }
+
         let Ok(header_value) = HeaderValue::from_str(&self.prometheus_workspace_header) else {
             warn!(
                 "Failed to parse prometheus workspace header from {}",
@@ -436,6 +685,138 @@ impl ProviderRepository {
         }
     }
 
+    /// Sets or clears the weight override of the provider for the chain and
+    /// persists it for the other replicas
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub async fn set_weight_override(
+        &self,
+        chain_id: &str,
+        provider_kind: ProviderKind,
+        weight_override: Option<WeightOverride>,
+    ) -> RpcResult<()> {
+        let weight = self
+            .weight_resolver
+            .get(chain_id)
+            .ok_or_else(|| RpcError::UnsupportedChain(chain_id.to_string()))?
+            .get(&provider_kind)
+            .ok_or_else(|| {
+                RpcError::InvalidParameter(format!(
+                    "Provider {provider_kind} is not serving the chain {chain_id}"
+                ))
+            })?;
+
+        if let Some(redis_pool) = &self.redis_pool {
+            let mut redis = redis_pool.get().await.map_err(|e| {
+                StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+            })?;
+            let field = overrides::override_field(chain_id, provider_kind);
+            match weight_override {
+                Some(weight_override) => {
+                    redis
+                        .hset::<_, _, _, ()>(
+                            overrides::WEIGHT_OVERRIDES_KEY,
+                            field,
+                            weight_override.as_str(),
+                        )
+                        .await
+                }
+                None => {
+                    redis
+                        .hdel::<_, _, ()>(overrides::WEIGHT_OVERRIDES_KEY, field)
+                        .await
+                }
+            }
+            .map_err(|e| {
+                StorageError::Connection(format!("Error when setting the weight override: {e}"))
+            })?;
+        } else {
+            warn!("Providers Redis is not configured, the weight override is not persisted");
+        }
+
+        weight.set_weight_override(weight_override);
+        Ok(())
+    }
+
+    /// Clears the weight overrides of all providers for the chain or for all of
+    /// the chains
+    #[tracing::instrument(skip(self), level = "debug")]
+    pub async fn reset_weight_overrides(&self, chain_id: Option<&str>) -> RpcResult<()> {
+        let chains = match chain_id {
+            Some(chain_id) => {
+                let providers = self
+                    .weight_resolver
+                    .get_key_value(chain_id)
+                    .ok_or_else(|| RpcError::UnsupportedChain(chain_id.to_string()))?;
+                vec![providers]
+            }
+            None => self.weight_resolver.iter().collect(),
+        };
+
+        if let Some(redis_pool) = &self.redis_pool {
+            let mut redis = redis_pool.get().await.map_err(|e| {
+                StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+            })?;
+            let result = match chain_id {
+                Some(_) => {
+                    let fields = chains
+                        .iter()
+                        .flat_map(|(chain_id, providers)| {
+                            providers.keys().map(move |provider_kind| {
+                                overrides::override_field(chain_id, *provider_kind)
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    redis
+                        .hdel::<_, _, ()>(overrides::WEIGHT_OVERRIDES_KEY, fields)
+                        .await
+                }
+                None => redis.del::<_, ()>(overrides::WEIGHT_OVERRIDES_KEY).await,
+            };
+            result.map_err(|e| {
+                StorageError::Connection(format!("Error when resetting the weight overrides: {e}"))
+            })?;
+        }
+
+        for (_, providers) in chains {
+            for weight in providers.values() {
+                weight.set_weight_override(None);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the persisted weight overrides, overrides missing in Redis are
+    /// cleared
+    async fn sync_weight_overrides(&self) -> RpcResult<()> {
+        let Some(redis_pool) = &self.redis_pool else {
+            return Ok(());
+        };
+        let mut redis = redis_pool.get().await.map_err(|e| {
+            StorageError::Connection(format!("Error when getting the Redis pool instance {e}"))
+        })?;
+        let fields: HashMap<String, String> = redis
+            .hgetall(overrides::WEIGHT_OVERRIDES_KEY)
+            .await
+            .map_err(|e| {
+                StorageError::Connection(format!("Error when getting the weight overrides: {e}"))
+            })?;
+
+        let persisted = fields
+            .iter()
+            .filter_map(|(field, value)| {
+                let key = overrides::parse_override_field(field)?;
+                Some((key, WeightOverride::from_str(value)?))
+            })
+            .collect::<HashMap<_, _>>();
+        for (chain_id, providers) in &self.weight_resolver {
+            for (provider_kind, weight) in providers {
+                let weight_override = persisted.get(&(chain_id.as_str(), *provider_kind)).copied();
+                weight.set_weight_override(weight_override);
+            }
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self), level = "debug")]
     pub fn get_provider_by_provider_id(&self, provider_id: &str) -> Option<Arc<dyn RpcProvider>> {
         let provider = ProviderKind::from_str(provider_id)?;
@@ -522,19 +903,51 @@ impl ProviderKind {
 #[async_trait]
 pub trait RpcProvider: Provider {
     async fn proxy(&self, chain_id: &str, body: hyper::body::Bytes) -> RpcResult<Response>;
+
+    /// Proxies the request to the provider regional endpoint on the client
+    /// continent, providers without the regional endpoints use the default
+    /// endpoint
+    async fn proxy_regional(
+        &self,
+        chain_id: &str,
+        body: hyper::body::Bytes,
+        _continent: Option<&str>,
+    ) -> RpcResult<Response> {
+        self.proxy(chain_id, body).await
+    }
+}
+
+/// Samples up to the `max_providers` providers by their weights without
+/// replacement to get the order to fail over through them. Providers with
+/// zero weights are skipped
+fn sample_weighted(
+    mut candidates: Vec<(ProviderKind, u64)>,
+    max_providers: usize,
+) -> Vec<ProviderKind> {
+    candidates.retain(|(_, weight)| *weight > 0);
+    let mut sampled = Vec::with_capacity(max_providers.min(candidates.len()));
+    while !candidates.is_empty() && sampled.len() < max_providers {
+        let dist = match WeightedIndex::new(candidates.iter().map(|(_, weight)| *weight)) {
+            Ok(dist) => dist,
+            Err(e) => {
+                warn!("Failed to create weighted index: {}", e);
+                break;
+            }
+        };
+        let (provider_kind, _) = candidates.swap_remove(dist.sample(&mut OsRng));
+        sampled.push(provider_kind);
+    }
+    sampled
 }
 
 pub trait RpcProviderFactory<T: ProviderConfig>: Provider {
-    fn new(provider_config: &T) -> Self;
+    fn new(provider_config: &T, http_client: &HyperClient) -> Self;
 }
 
 #[async_trait]
 pub trait RpcWsProvider: Provider {
-    async fn proxy(
-        &self,
-        ws: WebSocketUpgrade,
-        query_params: RpcQueryParams,
-    ) -> RpcResult<Response>;
+    /// Opens a client session over the pooled upstream connection
+    async fn connect(&self, chain_id: &str, metrics: Arc<Metrics>) -> RpcResult<UpstreamSession>;
 }
 
 const MAX_PRIORITY: u64 = 100;
@@ -589,6 +1002,7 @@ impl PriorityValue {
 pub struct Weight {
     value: std::sync::atomic::AtomicU64,
     priority: PriorityValue,
+    weight_override: std::sync::atomic::AtomicU8,
 }
 
 impl Weight {
@@ -597,13 +1011,37 @@ impl Weight {
         Ok(Self {
             value: std::sync::atomic::AtomicU64::new(priority_val),
             priority: PriorityValue::new(priority_val)?,
+            weight_override: std::sync::atomic::AtomicU8::new(WeightOverride::to_u8(None)),
         })
     }
 
+    /// Effective weight, drained providers always have zero weight
     pub fn value(&self) -> u64 {
+        if self.weight_override() == Some(WeightOverride::Drain) {
+            return 0;
+        }
         self.value.load(std::sync::atomic::Ordering::SeqCst)
     }
 
+    pub fn weight_override(&self) -> Option<WeightOverride> {
+        WeightOverride::from_u8(
+            self.weight_override
+                .load(std::sync::atomic::Ordering::SeqCst),
+        )
+    }
+
+    pub fn set_weight_override(&self, weight_override: Option<WeightOverride>) {
+        self.weight_override.store(
+            WeightOverride::to_u8(weight_override),
+            std::sync::atomic::Ordering::SeqCst,
+        );
+    }
+
+    /// Static priority value the weight is scaled by
+    pub fn priority(&self) -> u64 {
+        self.priority.value()
+    }
+
     pub fn update_value(&self, value: u64) {
         self.value.store(
             // Calulate the new value based on the priority, with MAX_PRIORITY/2 being the "normal"
@@ -727,6 +1165,9 @@ pub trait ConversionProvider: Send + Sync {
         params: AllowanceQueryParams,
         metrics: Arc<Metrics>,
     ) -> RpcResult<AllowanceResponseBody>;
+
+    /// Whether the conversions are served for the CAIP-2 chain id
+    fn is_chain_supported(&self, chain_id: &str) -> bool;
 }
 
 /// List of supported bundler operations
@@ -764,4 +1205,6 @@ pub trait BundlerOpsProvider: Send + Sync + Debug {
 
     /// Maps the operations enum variant to its provider-specific operation string.
     fn to_provider_op(&self, op: &SupportedBundlerOps) -> String;
+    /// Whether the bundler is serving the CAIP-2 chain id
+    fn is_chain_supported(&self, chain_id: &str) -> bool;
 }
@@ -32,6 +32,23 @@ use {
 
 const ONEINCH_FEE: f64 = 0.85;
 
+/// Chains served by the 1inch swap API
+const SUPPORTED_CHAINS: &[&str] = &[
+    "eip155:1",
+    "eip155:10",
+    "eip155:56",
+    "eip155:100",
+    "eip155:137",
+    "eip155:250",
+    "eip155:324",
+    "eip155:8217",
+    "eip155:8453",
+    "eip155:42161",
+    "eip155:43114",
+    "eip155:59144",
+    "eip155:1313161554",
+];
+
 #[derive(Debug)]
 pub struct OneInchProvider {
     pub provider_kind: ProviderKind,
@@ -42,9 +59,8 @@ pub struct OneInchProvider {
 }
 
 impl OneInchProvider {
-    pub fn new(api_key: String, referrer: Option<String>) -> Self {
+    pub fn new(api_key: String, referrer: Option<String>, http_client: reqwest::Client) -> Self {
         let base_api_url = "https://api.1inch.dev".to_string();
-        let http_client = reqwest::Client::new();
         Self {
             provider_kind: ProviderKind::OneInch,
             api_key,
@@ -759,6 +775,10 @@ impl ConversionProvider for OneInchProvider {
             allowance: body.allowance,
         })
     }
+
+    fn is_chain_supported(&self, chain_id: &str) -> bool {
+        SUPPORTED_CHAINS.contains(&chain_id)
+    }
 }
 
 #[async_trait]
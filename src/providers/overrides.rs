@@ -0,0 +1,103 @@
+use {
+    super::ProviderKind,
+    serde::{Deserialize, Serialize},
+};
+
+/// Redis hash of the weight overrides shared by all of the replicas
+pub const WEIGHT_OVERRIDES_KEY: &str = "provider_weight_overrides";
+
+const OVERRIDE_NONE: u8 = 0;
+const OVERRIDE_DRAIN: u8 = 1;
+const OVERRIDE_PREFER: u8 = 2;
+
+/// Manual weight override set by the admin API, takes precedence over the
+/// weights calculated from the providers availability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WeightOverride {
+    /// Provider weight is forced to zero and it's excluded from the routing
+    Drain,
+    /// Provider is tried first regardless of its weight
+    Prefer,
+}
+
+#[allow(clippy::should_implement_trait)]
+impl WeightOverride {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Drain => "drain",
+            Self::Prefer => "prefer",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "drain" => Some(Self::Drain),
+            "prefer" => Some(Self::Prefer),
+            _ => None,
+        }
+    }
+
+    pub(super) fn to_u8(weight_override: Option<Self>) -> u8 {
+        match weight_override {
+            None => OVERRIDE_NONE,
+            Some(Self::Drain) => OVERRIDE_DRAIN,
+            Some(Self::Prefer) => OVERRIDE_PREFER,
+        }
+    }
+
+    pub(super) fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            OVERRIDE_DRAIN => Some(Self::Drain),
+            OVERRIDE_PREFER => Some(Self::Prefer),
+            _ => None,
+        }
+    }
+}
+
+/// Redis hash field of the override, the chain id contains `:` so `/` is
+/// used as a separator
+pub fn override_field(chain_id: &str, provider_kind: ProviderKind) -> String {
+    format!("{chain_id}/{provider_kind}")
+}
+
+pub fn parse_override_field(field: &str) -> Option<(&str, ProviderKind)> {
+    let (chain_id, provider) = field.rsplit_once('/')?;
+    Some((chain_id, ProviderKind::from_str(provider)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_field_roundtrip() {
+        let field = override_field("eip155:1", ProviderKind::Infura);
+        assert_eq!(field, "eip155:1/Infura");
+        assert_eq!(
+            parse_override_field(&field),
+            Some(("eip155:1", ProviderKind::Infura))
+        );
+        assert_eq!(parse_override_field("eip155:1/Unknown"), None);
+        assert_eq!(parse_override_field("eip155:1"), None);
+    }
+
+    #[test]
+    fn override_values() {
+        for weight_override in [
+            None,
+            Some(WeightOverride::Drain),
+            Some(WeightOverride::Prefer),
+        ] {
+            assert_eq!(
+                WeightOverride::from_u8(WeightOverride::to_u8(weight_override)),
+                weight_override
+            );
+        }
+        assert_eq!(
+            WeightOverride::from_str(WeightOverride::Drain.as_str()),
+            Some(WeightOverride::Drain)
+        );
+        assert_eq!(WeightOverride::from_str("disable"), None);
+    }
+}
@@ -7,6 +7,29 @@ use {
     async_trait::async_trait,
 };
 
+/// Chains served by the Pimlico bundler
+const SUPPORTED_CHAINS: &[&str] = &[
+    "eip155:1",
+    "eip155:10",
+    "eip155:56",
+    "eip155:100",
+    "eip155:137",
+    "eip155:324",
+    "eip155:8453",
+    "eip155:42161",
+    "eip155:42220",
+    "eip155:43114",
+    "eip155:59144",
+    "eip155:81457",
+    "eip155:534352",
+    "eip155:7777777",
+    "eip155:11155111",
+    "eip155:11155420",
+    "eip155:84532",
+    "eip155:80002",
+    "eip155:421614",
+];
+
 #[derive(Debug)]
 pub struct PimlicoProvider {
     pub api_key: String,
@@ -15,9 +38,8 @@ pub struct PimlicoProvider {
 }
 
 impl PimlicoProvider {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: String, http_client: reqwest::Client) -> Self {
         let base_api_url = "https://api.pimlico.io/v2".to_string();
-        let http_client = reqwest::Client::new();
         Self {
             api_key,
             base_api_url,
@@ -79,4 +101,8 @@ impl BundlerOpsProvider for PimlicoProvider {
             }
         }
     }
+
+    fn is_chain_supported(&self, chain_id: &str) -> bool {
+        SUPPORTED_CHAINS.contains(&chain_id)
+    }
 }
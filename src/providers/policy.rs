@@ -0,0 +1,185 @@
+use {
+    serde::Deserialize,
+    std::{
+        collections::HashMap,
+        num::{NonZeroU64, NonZeroUsize},
+        time::Duration,
+    },
+    tracing::error,
+};
+
+const DEFAULT_POLICY_NAME: &str = "default";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_PROVIDERS: usize = 3;
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Policy key matching the method on all of the chains
+const ANY_CHAIN: &str = "*";
+
+/// Provider calls budget of the RPC request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallPolicy {
+    /// Configuration key of the policy used as the metrics label
+    pub name: String,
+    /// Single provider call timeout
+    pub timeout: Duration,
+    /// Maximum number of the providers to fail over through
+    pub max_providers: usize,
+    /// Total request deadline for all of the provider calls
+    pub deadline: Duration,
+}
+
+impl Default for CallPolicy {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_POLICY_NAME.to_owned(),
+            timeout: DEFAULT_TIMEOUT,
+            max_providers: DEFAULT_MAX_PROVIDERS,
+            deadline: DEFAULT_DEADLINE,
+        }
+    }
+}
+
+/// Zero timeouts and providers numbers are rejected when parsing
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallPolicyConfig {
+    timeout_ms: Option<NonZeroU64>,
+    max_providers: Option<NonZeroUsize>,
+    deadline_ms: Option<NonZeroU64>,
+}
+
+impl CallPolicyConfig {
+    /// Fills the fields that are not set from the less specific config
+    fn or(self, other: &Self) -> Self {
+        Self {
+            timeout_ms: self.timeout_ms.or(other.timeout_ms),
+            max_providers: self.max_providers.or(other.max_providers),
+            deadline_ms: self.deadline_ms.or(other.deadline_ms),
+        }
+    }
+}
+
+/// Call policies by the chain id and method. Policies are configured in
+/// JSON format keyed by `<chain_id>`, `<chain_id>/<method>` or `*/<method>`,
+/// e.g. `{"eip155:10": {"timeoutMs": 3000}, "*/eth_getLogs": {"deadlineMs":
+/// 60000}}`. Fields that are not set in the method policy are inherited from
+/// the chain policy and then fall back to the defaults.
+#[derive(Debug, Default)]
+pub struct CallPolicies {
+    configs: HashMap<String, CallPolicyConfig>,
+}
+
+impl CallPolicies {
+    pub fn new(policies_json: Option<&str>) -> Self {
+        let Some(policies_json) = policies_json else {
+            return Self::default();
+        };
+        match serde_json::from_str(policies_json) {
+            Ok(configs) => Self { configs },
+            Err(e) => {
+                error!(
+                    "Failed to parse the call policies, using the defaults: {}",
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Returns the policy for the chain and method merged from the most
+    /// specific to the least specific configs: the chain method policy, the
+    /// any chain method policy and the chain policy. The policy is named by
+    /// the most specific config
+    pub fn resolve(&self, chain_id: &str, method: Option<&str>) -> CallPolicy {
+        let keys = method
+            .into_iter()
+            .flat_map(|method| {
+                [
+                    format!("{chain_id}/{method}"),
+                    format!("{ANY_CHAIN}/{method}"),
+                ]
+            })
+            .chain([chain_id.to_owned()]);
+
+        let mut name = None;
+        let mut config = CallPolicyConfig::default();
+        for key in keys {
+            if let Some(key_config) = self.configs.get(&key) {
+                config = config.or(key_config);
+                name.get_or_insert(key);
+            }
+        }
+
+        let default = CallPolicy::default();
+        CallPolicy {
+            name: name.unwrap_or(default.name),
+            timeout: config
+                .timeout_ms
+                .map(|timeout| Duration::from_millis(timeout.get()))
+                .unwrap_or(default.timeout),
+            max_providers: config
+                .max_providers
+                .map(NonZeroUsize::get)
+                .unwrap_or(default.max_providers),
+            deadline: config
+                .deadline_ms
+                .map(|deadline| Duration::from_millis(deadline.get()))
+                .unwrap_or(default.deadline),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_policies() {
+        let policies = CallPolicies::new(Some(
+            r#"{
+                "eip155:10": {"timeoutMs": 3000, "maxProviders": 2},
+                "eip155:10/eth_getLogs": {"timeoutMs": 20000},
+                "*/eth_getLogs": {"deadlineMs": 60000}
+            }"#,
+        ));
+
+        let chain = policies.resolve("eip155:10", Some("eth_call"));
+        assert_eq!(chain.name, "eip155:10");
+        assert_eq!(chain.timeout, Duration::from_secs(3));
+        assert_eq!(chain.max_providers, 2);
+        assert_eq!(chain.deadline, DEFAULT_DEADLINE);
+
+        // Chain method policy inherits from the any chain method and the
+        // chain policies
+        let chain_method = policies.resolve("eip155:10", Some("eth_getLogs"));
+        assert_eq!(chain_method.name, "eip155:10/eth_getLogs");
+        assert_eq!(chain_method.timeout, Duration::from_secs(20));
+        assert_eq!(chain_method.max_providers, 2);
+        assert_eq!(chain_method.deadline, Duration::from_secs(60));
+
+        let any_chain_method = policies.resolve("eip155:1", Some("eth_getLogs"));
+        assert_eq!(any_chain_method.name, "*/eth_getLogs");
+        assert_eq!(any_chain_method.deadline, Duration::from_secs(60));
+
+        assert_eq!(policies.resolve("eip155:1", None), CallPolicy::default());
+        assert_eq!(
+            CallPolicies::new(Some("not a json")).resolve("eip155:10", None),
+            CallPolicy::default()
+        );
+    }
+
+    #[test]
+    fn rejects_zero_policies() {
+        for policies_json in [
+            r#"{"eip155:10": {"timeoutMs": 0}}"#,
+            r#"{"eip155:10": {"maxProviders": 0}}"#,
+            r#"{"eip155:10": {"deadlineMs": 0}}"#,
+        ] {
+            assert_eq!(
+                CallPolicies::new(Some(policies_json)).resolve("eip155:10", None),
+                CallPolicy::default()
+            );
+        }
+    }
+}
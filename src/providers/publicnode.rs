@@ -1,23 +1,24 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        HyperClient, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
+        RpcWsProvider, UpstreamResponse,
+    },
     crate::{
         env::PublicnodeConfig,
         error::{RpcError, RpcResult},
+        ws::pool::{UpstreamPool, UpstreamSession},
+        Metrics,
     },
     async_trait::async_trait,
-    axum::{
-        http::HeaderValue,
-        response::{IntoResponse, Response},
-    },
-    hyper::{client::HttpConnector, http, Client, Method},
-    hyper_tls::HttpsConnector,
-    std::collections::HashMap,
+    axum::response::{IntoResponse, Response},
+    hyper::{http, Method},
+    std::{collections::HashMap, sync::Arc},
     tracing::debug,
 };
 
 #[derive(Debug)]
 pub struct PublicnodeProvider {
-    pub client: Client<HttpsConnector<HttpConnector>>,
+    pub client: HyperClient,
     pub supported_chains: HashMap<String, String>,
 }
 
@@ -59,31 +60,26 @@ impl RpcProvider for PublicnodeProvider {
             .header("Content-Type", "application/json")
             .body(hyper::body::Body::from(body))?;
 
-        let response = self.client.request(hyper_request).await?;
+        let response = UpstreamResponse::read(self.client.request(hyper_request).await?).await?;
         let status = response.status();
-        let body = hyper::body::to_bytes(response.into_body()).await?;
 
-        if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
-            if response.error.is_some() && status.is_success() {
+        if let Some(error) = response.json_rpc_error() {
+            if status.is_success() {
                 debug!(
                     "Strange: provider returned JSON RPC error, but status {status} is success: \
-                     PublicNode: {response:?}"
+                     PublicNode: {error}"
                 );
             }
         }
 
-        let mut response = (status, body).into_response();
-        response
-            .headers_mut()
-            .insert("Content-Type", HeaderValue::from_static("application/json"));
-        Ok(response)
+        Ok(response.into_response())
     }
 }
 
 impl RpcProviderFactory<PublicnodeConfig> for PublicnodeProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &PublicnodeConfig) -> Self {
-        let forward_proxy_client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+    fn new(provider_config: &PublicnodeConfig, http_client: &HyperClient) -> Self {
+        let forward_proxy_client = http_client.clone();
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -96,3 +92,63 @@ impl RpcProviderFactory<PublicnodeConfig> for PublicnodeProvider {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct PublicnodeWsProvider {
+    pub supported_chains: HashMap<String, String>,
+    pub pool: UpstreamPool,
+}
+
+impl Provider for PublicnodeWsProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.supported_chains.contains_key(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.supported_chains.keys().cloned().collect()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Publicnode
+    }
+}
+
+#[async_trait]
+impl RateLimited for PublicnodeWsProvider {
+    async fn is_rate_limited(&self, response: &mut Response) -> bool
+    where
+        Self: Sized,
+    {
+        response.status() == http::StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+#[async_trait]
+impl RpcWsProvider for PublicnodeWsProvider {
+    #[tracing::instrument(skip_all, fields(provider = %self.provider_kind()), level = "debug")]
+    async fn connect(&self, chain_id: &str, metrics: Arc<Metrics>) -> RpcResult<UpstreamSession> {
+        let chain = self
+            .supported_chains
+            .get(chain_id)
+            .ok_or(RpcError::ChainNotFound)?;
+
+        let uri = format!("wss://{}.publicnode.com", chain);
+        self.pool.session(chain_id, &uri, metrics).await
+    }
+}
+
+impl RpcProviderFactory<PublicnodeConfig> for PublicnodeWsProvider {
+    #[tracing::instrument(level = "debug")]
+    fn new(provider_config: &PublicnodeConfig, _http_client: &HyperClient) -> Self {
+        let supported_chains: HashMap<String, String> = provider_config
+            .supported_ws_chains
+            .iter()
+            .map(|(k, v)| (k.clone(), v.0.clone()))
+            .collect();
+
+        PublicnodeWsProvider {
+            supported_chains,
+            pool: UpstreamPool::new(ProviderKind::Publicnode),
+        }
+    }
+}
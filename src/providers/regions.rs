@@ -0,0 +1,61 @@
+use {super::ProviderKind, std::collections::HashMap, tracing::error};
+
+/// Parses the providers continents in JSON format keyed by the provider,
+/// e.g. `{"Pokt": ["NA", "EU"]}`
+pub fn parse_provider_continents(continents_json: &str) -> HashMap<ProviderKind, Vec<String>> {
+    let continents: HashMap<String, Vec<String>> = match serde_json::from_str(continents_json) {
+        Ok(continents) => continents,
+        Err(e) => {
+            error!("Failed to parse the providers continents: {}", e);
+            return HashMap::new();
+        }
+    };
+    continents
+        .into_iter()
+        .filter_map(|(provider, continents)| {
+            let Some(provider_kind) = ProviderKind::from_str(&provider) else {
+                error!("Unknown provider in the providers continents: {}", provider);
+                return None;
+            };
+            Some((provider_kind, continents))
+        })
+        .collect()
+}
+
+/// Whether the provider endpoints are close to the client continent.
+/// Providers without declared continents are global and close to everyone,
+/// as well as clients without the resolved continent.
+pub fn is_close_to(provider_continents: &[String], client_continent: Option<&str>) -> bool {
+    let Some(client_continent) = client_continent else {
+        return true;
+    };
+    provider_continents.is_empty()
+        || provider_continents
+            .iter()
+            .any(|continent| continent.eq_ignore_ascii_case(client_continent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_provider_continents() {
+        let continents = parse_provider_continents(r#"{"Pokt": ["NA", "EU"], "Unknown": ["AS"]}"#);
+        assert_eq!(continents.len(), 1);
+        assert_eq!(
+            continents.get(&ProviderKind::Pokt),
+            Some(&vec!["NA".to_owned(), "EU".to_owned()])
+        );
+        assert!(parse_provider_continents("not a json").is_empty());
+    }
+
+    #[test]
+    fn checks_closeness() {
+        let regional = vec!["NA".to_owned(), "EU".to_owned()];
+        assert!(is_close_to(&regional, Some("EU")));
+        assert!(!is_close_to(&regional, Some("AS")));
+        assert!(is_close_to(&regional, None));
+        assert!(is_close_to(&[], Some("AS")));
+    }
+}
@@ -0,0 +1,337 @@
+//! Shadow providers receive a sample of the read requests mirrored from the
+//! primary providers to evaluate them before the promotion. Their responses
+//! are only compared with the primary responses and never returned to the
+//! clients.
+//!
+//! Shadow providers are configured by the chain id and are not used for the
+//! chain primary routing. The number of the concurrent shadow calls is
+//! limited, so the slow shadow providers can't pile up the mirrored requests.
+
+use {
+    super::{ProviderKind, RpcProvider},
+    rand::{rngs::OsRng, seq::SliceRandom, Rng},
+    serde_json::Value,
+    std::{collections::HashMap, sync::Arc},
+    tokio::sync::{OwnedSemaphorePermit, Semaphore},
+    tracing::{debug, error},
+};
+
+/// Maximum number of the concurrent shadow calls, the requests sampled
+/// beyond it are not mirrored
+const MAX_CONCURRENT_SHADOW_CALLS: usize = 64;
+
+/// Read-only JSON-RPC methods that are safe to be mirrored
+const READ_METHODS: &[&str] = &[
+    "eth_blockNumber",
+    "eth_call",
+    "eth_chainId",
+    "eth_estimateGas",
+    "eth_feeHistory",
+    "eth_gasPrice",
+    "eth_maxPriorityFeePerGas",
+    "net_version",
+];
+/// Read-only methods prefixes, `eth_get*` and the Solana `get*` methods
+const READ_METHODS_PREFIXES: &[&str] = &["eth_get", "get"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowOutcome {
+    Match,
+    /// HTTP status codes are different
+    StatusMismatch,
+    /// Only one of the responses is an error or the error codes are different
+    ErrorMismatch,
+    /// Both responses succeeded with the different results
+    ResultMismatch,
+    /// Shadow provider call failed or timed out
+    ShadowError,
+}
+
+impl ShadowOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Match => "match",
+            Self::StatusMismatch => "status_mismatch",
+            Self::ErrorMismatch => "error_mismatch",
+            Self::ResultMismatch => "result_mismatch",
+            Self::ShadowError => "shadow_error",
+        }
+    }
+
+    pub fn is_mismatch(&self) -> bool {
+        matches!(
+            self,
+            Self::StatusMismatch | Self::ErrorMismatch | Self::ResultMismatch
+        )
+    }
+}
+
+/// Shadow provider of the mirrored request holding the concurrent shadow call
+/// slot until dropped
+pub struct ShadowCall {
+    pub provider: Arc<dyn RpcProvider>,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Shadow providers and the mirrored traffic percentages of the chains
+pub struct ShadowRouting {
+    /// Configured shadow providers kinds by the chain id
+    provider_kinds: HashMap<String, Vec<ProviderKind>>,
+    /// Percentage of the chain read requests mirrored to the shadow providers
+    traffic_percentages: HashMap<String, u8>,
+    /// Registered shadow providers by the chain id
+    providers: HashMap<String, Vec<Arc<dyn RpcProvider>>>,
+    /// Concurrent shadow calls slots
+    calls: Arc<Semaphore>,
+}
+
+impl ShadowRouting {
+    /// Parses the JSON configs of the shadow providers names and the traffic
+    /// percentages by the chain id
+    pub fn new(providers: Option<&str>, traffic_percentages: Option<&str>) -> Self {
+        let provider_kinds = providers
+            .map(|providers| {
+                serde_json::from_str::<HashMap<String, Vec<String>>>(providers).unwrap_or_else(
+                    |e| {
+                        error!("Failed to parse the shadow providers: {}", e);
+                        HashMap::new()
+                    },
+                )
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(chain_id, names)| {
+                let kinds = names
+                    .iter()
+                    .filter_map(|name| {
+                        let kind = ProviderKind::from_str(name);
+                        if kind.is_none() {
+                            error!("Unknown shadow provider {} for {}", name, chain_id);
+                        }
+                        kind
+                    })
+                    .collect();
+                (chain_id, kinds)
+            })
+            .collect();
+        let traffic_percentages = traffic_percentages
+            .map(|percentages| {
+                serde_json::from_str::<HashMap<String, u8>>(percentages).unwrap_or_else(|e| {
+                    error!("Failed to parse the shadow traffic percentages: {}", e);
+                    HashMap::new()
+                })
+            })
+            .unwrap_or_default();
+
+        Self {
+            provider_kinds,
+            traffic_percentages,
+            providers: HashMap::new(),
+            calls: Arc::new(Semaphore::new(MAX_CONCURRENT_SHADOW_CALLS)),
+        }
+    }
+
+    /// Whether the provider is configured as the chain shadow provider
+    pub fn is_shadow(&self, chain_id: &str, provider_kind: ProviderKind) -> bool {
+        self.provider_kinds
+            .get(chain_id)
+            .is_some_and(|kinds| kinds.contains(&provider_kind))
+    }
+
+    pub fn add(&mut self, chain_id: String, provider: Arc<dyn RpcProvider>) {
+        self.providers.entry(chain_id).or_default().push(provider);
+    }
+
+    /// Returns the shadow call to mirror the chain request to according to
+    /// the configured chain traffic percentage, if a shadow call slot is free
+    pub fn call_for_chain_id(&self, chain_id: &str) -> Option<ShadowCall> {
+        let percentage = *self.traffic_percentages.get(chain_id)?;
+        let providers = self.providers.get(chain_id)?;
+        if OsRng.gen_range(0..100) >= percentage {
+            return None;
+        }
+        let provider = providers.choose(&mut OsRng).cloned()?;
+        let Ok(permit) = self.calls.clone().try_acquire_owned() else {
+            debug!("Skipping the {chain_id} shadow call, all shadow call slots are taken");
+            return None;
+        };
+        Some(ShadowCall {
+            provider,
+            _permit: permit,
+        })
+    }
+}
+
+/// Whether the JSON-RPC method is read-only and safe to be mirrored
+pub fn is_read_method(method: &str) -> bool {
+    READ_METHODS.contains(&method)
+        || READ_METHODS_PREFIXES
+            .iter()
+            .any(|prefix| method.starts_with(prefix))
+}
+
+pub fn compare_responses(
+    primary_status: u16,
+    primary_body: &[u8],
+    shadow_status: u16,
+    shadow_body: &[u8],
+) -> ShadowOutcome {
+    if primary_status != shadow_status {
+        return ShadowOutcome::StatusMismatch;
+    }
+    let (Ok(primary), Ok(shadow)) = (
+        serde_json::from_slice::<Value>(primary_body),
+        serde_json::from_slice::<Value>(shadow_body),
+    ) else {
+        return if primary_body == shadow_body {
+            ShadowOutcome::Match
+        } else {
+            ShadowOutcome::ResultMismatch
+        };
+    };
+
+    let error_code = |response: &Value| {
+        response
+            .get("error")
+            .map(|error| error.get("code").cloned())
+    };
+    if error_code(&primary) != error_code(&shadow) {
+        return ShadowOutcome::ErrorMismatch;
+    }
+    if primary.get("result") != shadow.get("result") {
+        return ShadowOutcome::ResultMismatch;
+    }
+    ShadowOutcome::Match
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            error::RpcResult,
+            providers::{Provider, RateLimited},
+        },
+        async_trait::async_trait,
+        axum::response::Response,
+    };
+
+    #[derive(Debug)]
+    struct MockProvider;
+
+    const MOCK_RESPONSE: &str = r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#;
+
+    impl Provider for MockProvider {
+        fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+            chain_id == "eip155:1"
+        }
+
+        fn supported_caip_chains(&self) -> Vec<String> {
+            vec!["eip155:1".to_owned()]
+        }
+
+        fn provider_kind(&self) -> ProviderKind {
+            ProviderKind::Publicnode
+        }
+    }
+
+    #[async_trait]
+    impl RateLimited for MockProvider {
+        async fn is_rate_limited(&self, _data: &mut Response) -> bool {
+            false
+        }
+    }
+
+    #[async_trait]
+    impl RpcProvider for MockProvider {
+        async fn proxy(&self, _chain_id: &str, _body: hyper::body::Bytes) -> RpcResult<Response> {
+            Ok(Response::new(axum::body::boxed(axum::body::Full::from(
+                MOCK_RESPONSE,
+            ))))
+        }
+    }
+
+    #[test]
+    fn routes_configured_shadow_providers() {
+        let mut routing = ShadowRouting::new(
+            Some(r#"{"eip155:1":["Publicnode","Unknown"],"eip155:10":["Publicnode"]}"#),
+            Some(r#"{"eip155:1":100,"eip155:10":0}"#),
+        );
+        assert!(routing.is_shadow("eip155:1", ProviderKind::Publicnode));
+        assert!(!routing.is_shadow("eip155:1", ProviderKind::Infura));
+        assert!(!routing.is_shadow("eip155:56", ProviderKind::Publicnode));
+
+        // Not registered shadow providers are not mirrored to
+        assert!(routing.call_for_chain_id("eip155:1").is_none());
+
+        routing.add("eip155:1".to_owned(), Arc::new(MockProvider));
+        routing.add("eip155:10".to_owned(), Arc::new(MockProvider));
+        let call = routing.call_for_chain_id("eip155:1").unwrap();
+        assert_eq!(call.provider.provider_kind(), ProviderKind::Publicnode);
+        assert!(routing.call_for_chain_id("eip155:10").is_none());
+    }
+
+    #[tokio::test]
+    async fn limits_concurrent_shadow_calls() {
+        let mut routing = ShadowRouting::new(
+            Some(r#"{"eip155:1":["Publicnode"]}"#),
+            Some(r#"{"eip155:1":100}"#),
+        );
+        routing.add("eip155:1".to_owned(), Arc::new(MockProvider));
+
+        let calls = (0..MAX_CONCURRENT_SHADOW_CALLS)
+            .map(|_| routing.call_for_chain_id("eip155:1").unwrap())
+            .collect::<Vec<_>>();
+        assert!(routing.call_for_chain_id("eip155:1").is_none());
+
+        let response = calls[0]
+            .provider
+            .proxy("eip155:1", Default::default())
+            .await;
+        let body = hyper::body::to_bytes(response.unwrap().into_body()).await;
+        assert_eq!(body.unwrap(), MOCK_RESPONSE.as_bytes());
+
+        // Finished calls release their slots
+        drop(calls);
+        assert!(routing.call_for_chain_id("eip155:1").is_some());
+    }
+
+    #[test]
+    fn mirrors_only_read_requests() {
+        assert!(is_read_method("eth_getBalance"));
+        assert!(is_read_method("eth_call"));
+        assert!(is_read_method("getLatestBlockhash"));
+        assert!(!is_read_method("eth_sendRawTransaction"));
+        assert!(!is_read_method("sendTransaction"));
+    }
+
+    #[test]
+    fn compares_responses() {
+        let result = br#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#;
+        let other_result = br#"{"jsonrpc":"2.0","id":1,"result":"0x2"}"#;
+        let error = br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"a"}}"#;
+        let same_error = br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"b"}}"#;
+
+        assert_eq!(
+            compare_responses(200, result, 200, result),
+            ShadowOutcome::Match
+        );
+        assert_eq!(
+            compare_responses(200, result, 503, result),
+            ShadowOutcome::StatusMismatch
+        );
+        assert_eq!(
+            compare_responses(200, result, 200, other_result),
+            ShadowOutcome::ResultMismatch
+        );
+        assert_eq!(
+            compare_responses(200, result, 200, error),
+            ShadowOutcome::ErrorMismatch
+        );
+        // Error messages are provider specific
+        assert_eq!(
+            compare_responses(200, error, 200, same_error),
+            ShadowOutcome::Match
+        );
+    }
+}
@@ -91,12 +91,13 @@ impl SolScanProvider {
         api_v1_token: String,
         api_v2_token: String,
         redis_caching_pool: Option<Arc<Pool>>,
+        http_client: reqwest::Client,
     ) -> Self {
         Self {
             provider_kind: ProviderKind::SolScan,
             api_v1_token,
             api_v2_token,
-            http_client: reqwest::Client::new(),
+            http_client,
             redis_caching_pool,
         }
     }
@@ -0,0 +1,197 @@
+use {
+    super::{ProviderKind, WeightOverride},
+    serde::Serialize,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{Arc, Mutex, PoisonError, RwLock},
+        time::Duration,
+    },
+};
+
+/// Number of the most recent calls kept per provider and chain
+const CALL_STATS_WINDOW: usize = 100;
+
+#[derive(Debug, Clone, Copy)]
+struct CallRecord {
+    success: bool,
+    latency_ms: u64,
+}
+
+/// Recent calls window with the aggregates maintained on each record, so the
+/// summaries read on every routing decision are not recalculated
+#[derive(Debug, Default)]
+struct CallWindow {
+    records: VecDeque<CallRecord>,
+    failures: usize,
+    latencies_sum_ms: u64,
+    /// Latencies of the window calls in the ascending order
+    sorted_latencies_ms: Vec<u64>,
+}
+
+impl CallWindow {
+    fn push(&mut self, record: CallRecord) {
+        if self.records.len() == CALL_STATS_WINDOW {
+            if let Some(oldest) = self.records.pop_front() {
+                self.failures -= usize::from(!oldest.success);
+                self.latencies_sum_ms -= oldest.latency_ms;
+                if let Ok(index) = self.sorted_latencies_ms.binary_search(&oldest.latency_ms) {
+                    self.sorted_latencies_ms.remove(index);
+                }
+            }
+        }
+        self.failures += usize::from(!record.success);
+        self.latencies_sum_ms += record.latency_ms;
+        let index = self
+            .sorted_latencies_ms
+            .partition_point(|latency_ms| *latency_ms < record.latency_ms);
+        self.sorted_latencies_ms.insert(index, record.latency_ms);
+        self.records.push_back(record);
+    }
+
+    fn summary(&self) -> CallStatsSummary {
+        let calls = self.records.len();
+        if calls == 0 {
+            return CallStatsSummary {
+                calls,
+                failures: 0,
+                success_rate: None,
+                avg_latency_ms: None,
+                p95_latency_ms: None,
+            };
+        }
+
+        let p95_index = (calls * 95).div_ceil(100).saturating_sub(1);
+        CallStatsSummary {
+            calls,
+            failures: self.failures,
+            success_rate: Some((calls - self.failures) as f64 / calls as f64),
+            avg_latency_ms: Some(self.latencies_sum_ms / calls as u64),
+            p95_latency_ms: self.sorted_latencies_ms.get(p95_index).copied(),
+        }
+    }
+}
+
+type CallRecords = Arc<Mutex<CallWindow>>;
+
+/// Recent provider calls outcomes and latencies kept in memory of this
+/// instance to be inspected during the provider incidents
+///
+/// Records are locked per provider and chain, the map itself is write locked
+/// only when the first call of the provider for the chain is recorded.
+#[derive(Debug, Default)]
+pub struct CallStats {
+    calls: RwLock<HashMap<(ProviderKind, String), CallRecords>>,
+}
+
+/// Routing state of the provider for the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    /// Provider is sampled for the chain requests
+    Closed,
+    /// Provider weight dropped to zero and it's excluded from the routing
+    /// until the next weights update
+    Open,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStatus {
+    pub provider: String,
+    pub weight: u64,
+    pub priority: u64,
+    #[serde(rename = "override")]
+    pub weight_override: Option<WeightOverride>,
+    /// Estimated request cost in micro-USD
+    pub request_cost: u64,
+    /// Continents of the provider endpoints, empty for the global providers
+    pub continents: Vec<String>,
+    pub circuit: CircuitState,
+    pub recent_calls: CallStatsSummary,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallStatsSummary {
+    pub calls: usize,
+    pub failures: usize,
+    pub success_rate: Option<f64>,
+    pub avg_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+}
+
+impl CallStats {
+    pub fn record(
+        &self,
+        provider_kind: ProviderKind,
+        chain_id: &str,
+        success: bool,
+        latency: Duration,
+    ) {
+        let key = (provider_kind, chain_id.to_owned());
+        let records = self.records(&key).unwrap_or_else(|| {
+            self.calls
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(key)
+                .or_default()
+                .clone()
+        });
+
+        records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(CallRecord {
+                success,
+                latency_ms: latency.as_millis() as u64,
+            });
+    }
+
+    pub fn summary(&self, provider_kind: ProviderKind, chain_id: &str) -> CallStatsSummary {
+        self.records(&(provider_kind, chain_id.to_owned()))
+            .map(|records| {
+                records
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .summary()
+            })
+            .unwrap_or_else(|| CallWindow::default().summary())
+    }
+
+    fn records(&self, key: &(ProviderKind, String)) -> Option<CallRecords> {
+        self.calls
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_recent_calls() {
+        let stats = CallStats::default();
+        assert_eq!(stats.summary(ProviderKind::Infura, "eip155:1").calls, 0);
+
+        for i in 1..=CALL_STATS_WINDOW as u64 + 10 {
+            stats.record(
+                ProviderKind::Infura,
+                "eip155:1",
+                i % 10 != 0,
+                Duration::from_millis(i),
+            );
+        }
+        let summary = stats.summary(ProviderKind::Infura, "eip155:1");
+
+        // Only the last calls window is kept
+        assert_eq!(summary.calls, CALL_STATS_WINDOW);
+        assert_eq!(summary.failures, 10);
+        assert_eq!(summary.success_rate, Some(0.9));
+        assert_eq!(summary.avg_latency_ms, Some(60));
+        assert_eq!(summary.p95_latency_ms, Some(105));
+        assert_eq!(stats.summary(ProviderKind::Pokt, "eip155:1").calls, 0);
+    }
+}
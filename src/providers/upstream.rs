@@ -0,0 +1,345 @@
+use {
+    crate::error::RpcResult,
+    axum::{
+        body::{self, BoxBody},
+        response::{IntoResponse, Response},
+    },
+    futures_util::{stream, StreamExt},
+    hyper::{
+        body::{Bytes, HttpBody, SizeHint},
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        http::HeaderValue,
+        HeaderMap, StatusCode,
+    },
+    serde::Deserialize,
+    std::{
+        fmt::{self, Display},
+        future::Future,
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+    tokio::time::{sleep_until, Instant, Sleep},
+};
+
+/// Upstream responses larger than this are streamed to the client without
+/// being buffered, the responses of unknown length are buffered up to it
+const STREAMED_RESPONSE_MIN_LENGTH: usize = 512 * 1024;
+/// Length of the streamed responses prefix inspected for the JSON-RPC error
+const STREAMED_RESPONSE_SNIFF_LENGTH: usize = 4 * 1024;
+
+/// JSON-RPC provider response
+#[derive(Debug)]
+pub enum UpstreamResponse {
+    /// Response buffered to be inspected for the JSON-RPC errors
+    Buffered { status: StatusCode, body: Bytes },
+    /// Large response that is streamed to the client as is, the responses
+    /// with the JSON-RPC error are always buffered
+    Streamed(hyper::Response<hyper::Body>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpstreamError {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Display for UpstreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code, self.message)
+    }
+}
+
+impl UpstreamResponse {
+    pub async fn read(response: hyper::Response<hyper::Body>) -> RpcResult<Self> {
+        let (parts, mut upstream_body) = response.into_parts();
+        let content_length = parts
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse::<usize>().ok());
+        // Only the prefix of the responses known to be large is buffered
+        let buffered_length = match content_length {
+            Some(length) if length >= STREAMED_RESPONSE_MIN_LENGTH => {
+                STREAMED_RESPONSE_SNIFF_LENGTH
+            }
+            _ => STREAMED_RESPONSE_MIN_LENGTH,
+        };
+
+        let mut body = Vec::new();
+        let mut is_error = false;
+        while let Some(chunk) = upstream_body.data().await {
+            body.extend_from_slice(&chunk?);
+            if is_error || body.len() < buffered_length {
+                continue;
+            }
+            // Error responses are buffered entirely to be remapped and retried
+            if has_error_member(&body) {
+                is_error = true;
+                continue;
+            }
+            let prefix = Bytes::from(body);
+            let body = hyper::Body::wrap_stream(
+                stream::once(async move { Ok::<_, hyper::Error>(prefix) }).chain(upstream_body),
+            );
+            return Ok(Self::Streamed(hyper::Response::from_parts(parts, body)));
+        }
+        Ok(Self::Buffered {
+            status: parts.status,
+            body: body.into(),
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Buffered { status, .. } => *status,
+            Self::Streamed(response) => response.status(),
+        }
+    }
+
+    /// Error of the buffered single JSON-RPC response, the result is skipped
+    /// without being deserialized
+    pub fn json_rpc_error(&self) -> Option<UpstreamError> {
+        #[derive(Deserialize)]
+        struct ErrorEnvelope {
+            error: Option<UpstreamError>,
+        }
+
+        let Self::Buffered { body, .. } = self else {
+            return None;
+        };
+        serde_json::from_slice::<ErrorEnvelope>(body).ok()?.error
+    }
+
+    /// Responds with the upstream body and the overridden status code
+    pub fn into_response_with_status(self, status: StatusCode) -> Response {
+        match self {
+            Self::Buffered { body, .. } => (status, body).into_response(),
+            Self::Streamed(response) => streamed_response(response, Some(status)),
+        }
+    }
+}
+
+/// Upstream headers are not forwarded except of the content length
+fn streamed_response(
+    response: hyper::Response<hyper::Body>,
+    status: Option<StatusCode>,
+) -> Response {
+    let (parts, upstream_body) = response.into_parts();
+    let mut response = Response::new(body::boxed(upstream_body));
+    *response.status_mut() = status.unwrap_or(parts.status);
+    if let Some(length) = parts.headers.get(CONTENT_LENGTH) {
+        response
+            .headers_mut()
+            .insert(CONTENT_LENGTH, length.clone());
+    }
+    response
+}
+
+/// Fails the response body that was not received before the deadline, the
+/// streamed responses are otherwise not bounded by the call timeout as it
+/// only covers receiving the headers
+pub fn with_body_deadline(response: Response, deadline: Instant) -> Response {
+    response.map(|body| {
+        body::boxed(DeadlineBody {
+            body,
+            deadline: Box::pin(sleep_until(deadline)),
+        })
+    })
+}
+
+struct DeadlineBody {
+    body: BoxBody,
+    deadline: Pin<Box<Sleep>>,
+}
+
+impl HttpBody for DeadlineBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        // Already received chunks are yielded even after the deadline
+        if let Poll::Ready(chunk) = Pin::new(&mut self.body).poll_data(cx) {
+            return Poll::Ready(chunk);
+        }
+        match self.deadline.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Some(Err(axum::Error::new(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Upstream response body deadline exceeded",
+            ))))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.body).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+/// Whether the possibly truncated JSON object has the top-level `error` member
+fn has_error_member(json: &[u8]) -> bool {
+    if json.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&b'{') {
+        return false;
+    }
+
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut is_escaped = false;
+    let mut is_expecting_key = false;
+    let mut key_start = None;
+    let mut last_key = None;
+    for (i, &byte) in json.iter().enumerate() {
+        if in_string {
+            if is_escaped {
+                is_escaped = false;
+            } else if byte == b'\\' {
+                is_escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+                if let Some(start) = key_start.take() {
+                    last_key = Some(&json[start..i]);
+                }
+            }
+            continue;
+        }
+        match byte {
+            b'"' => {
+                in_string = true;
+                if depth == 1 && is_expecting_key {
+                    is_expecting_key = false;
+                    key_start = Some(i + 1);
+                }
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                is_expecting_key = depth == 1;
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b',' if depth == 1 => is_expecting_key = true,
+            b':' if depth == 1 => {
+                if last_key.take() == Some(b"error".as_slice()) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+impl IntoResponse for UpstreamResponse {
+    fn into_response(self) -> Response {
+        let mut response = match self {
+            Self::Buffered { status, body } => (status, body).into_response(),
+            Self::Streamed(response) => streamed_response(response, None),
+        };
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upstream_response(body: Vec<u8>, content_length: bool) -> hyper::Response<hyper::Body> {
+        let length = body.len();
+        let mut response = hyper::Response::new(hyper::Body::from(body));
+        if content_length {
+            response
+                .headers_mut()
+                .insert(CONTENT_LENGTH, HeaderValue::from(length));
+        }
+        response
+    }
+
+    fn large_result() -> Vec<u8> {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":"0x{}"}}"#,
+            "0".repeat(STREAMED_RESPONSE_MIN_LENGTH)
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn finds_error_member() {
+        assert!(has_error_member(
+            br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603"#
+        ));
+        assert!(has_error_member(br#" { "error" : null }"#));
+        assert!(!has_error_member(
+            br#"{"jsonrpc":"2.0","id":1,"result":{"error":"#
+        ));
+        assert!(!has_error_member(br#"{"result":"\"error\":","#));
+        assert!(!has_error_member(br#"[{"error":{}}]"#));
+        assert!(!has_error_member(br#"{"err"#));
+    }
+
+    #[tokio::test]
+    async fn streams_large_responses() {
+        for content_length in [true, false] {
+            let body = large_result();
+            let response = UpstreamResponse::read(upstream_response(body.clone(), content_length))
+                .await
+                .unwrap();
+            let UpstreamResponse::Streamed(response) = response else {
+                panic!("Large response is not streamed");
+            };
+            let streamed = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(streamed, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn buffers_error_responses() {
+        let mut body = br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":""#.to_vec();
+        body.extend("a".repeat(STREAMED_RESPONSE_MIN_LENGTH).as_bytes());
+        body.extend(br#""}}"#);
+        for content_length in [true, false] {
+            let response = UpstreamResponse::read(upstream_response(body.clone(), content_length))
+                .await
+                .unwrap();
+            assert!(matches!(response, UpstreamResponse::Buffered { .. }));
+            assert_eq!(response.json_rpc_error().unwrap().code, -32603);
+        }
+
+        let body = br#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#.to_vec();
+        let response = UpstreamResponse::read(upstream_response(body, false))
+            .await
+            .unwrap();
+        assert!(matches!(response, UpstreamResponse::Buffered { .. }));
+    }
+
+    #[tokio::test]
+    async fn fails_stalled_bodies_after_deadline() {
+        let (mut sender, upstream_body) = hyper::Body::channel();
+        sender.try_send_data(Bytes::from_static(b"{")).unwrap();
+        let response = Response::new(body::boxed(upstream_body));
+        let deadline = Instant::now() + std::time::Duration::from_millis(10);
+        let mut body = with_body_deadline(response, deadline).into_body();
+
+        assert_eq!(
+            body.data().await.unwrap().unwrap(),
+            Bytes::from_static(b"{")
+        );
+        assert!(body.data().await.unwrap().is_err());
+        drop(sender);
+    }
+}
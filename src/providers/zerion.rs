@@ -1,6 +1,7 @@
 use {
     super::{BalanceProvider, HistoryProvider, PortfolioProvider},
     crate::{
+        chains,
         error::{RpcError, RpcResult},
         handlers::{
             balance::{BalanceQueryParams, BalanceResponseBody},
@@ -18,7 +19,6 @@ use {
             balance::{BalanceItem, BalanceQuantity},
             ProviderKind,
         },
-        utils::crypto,
         Metrics,
     },
     async_trait::async_trait,
@@ -37,8 +37,7 @@ pub struct ZerionProvider {
 }
 
 impl ZerionProvider {
-    pub fn new(api_key: String) -> Self {
-        let http_client = reqwest::Client::new();
+    pub fn new(api_key: String, http_client: reqwest::Client) -> Self {
         Self {
             provider_kind: ProviderKind::Zerion,
             api_key,
@@ -322,7 +321,7 @@ impl HistoryProvider for ZerionProvider {
                     chain: if f.relationships.chain.data.r#type != "chains" {
                         None
                     } else {
-                        crypto::ChainId::to_caip2(&f.relationships.chain.data.id)
+                        chains::to_caip2(&f.relationships.chain.data.id)
                     },
                 },
                 transfers: f
@@ -446,8 +445,8 @@ impl BalanceProvider for ZerionProvider {
         add_filter_non_trash_only(&mut url);
 
         if let Some(chain_id) = params.chain_id {
-            let chain_name = crypto::ChainId::from_caip2(&chain_id)
-                .ok_or(RpcError::InvalidParameter(chain_id))?;
+            let chain_name =
+                chains::from_caip2(&chain_id).ok_or(RpcError::InvalidParameter(chain_id))?;
             url.query_pairs_mut()
                 .append_pair("filter[chain_ids]", &chain_name);
         }
@@ -481,7 +480,7 @@ impl BalanceProvider for ZerionProvider {
             .map(|f| BalanceItem {
                 name: f.attributes.fungible_info.name,
                 symbol: f.attributes.fungible_info.symbol,
-                chain_id: crypto::ChainId::to_caip2(&f.relationships.chain.data.id),
+                chain_id: chains::to_caip2(&f.relationships.chain.data.id),
                 address: {
                     let chain_id_human = f.relationships.chain.data.id;
                     let chain_address = f
@@ -491,7 +490,7 @@ impl BalanceProvider for ZerionProvider {
                         .iter()
                         .find(|f| f.chain_id == chain_id_human)
                         .and_then(|f| f.address.clone());
-                    let chain_id = crypto::ChainId::to_caip2(&chain_id_human);
+                    let chain_id = chains::to_caip2(&chain_id_human);
                     if let Some(chain_address) = chain_address {
                         // For Polygon native token (POL)
                         // address is returned, but address should be null
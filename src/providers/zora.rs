@@ -1,35 +1,31 @@
 use {
     super::{
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider, WS_PROXY_TASK_METRICS,
+        HyperClient, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
+        RpcWsProvider, UpstreamResponse,
     },
     crate::{
         env::ZoraConfig,
         error::{RpcError, RpcResult},
-        ws,
+        ws::pool::{UpstreamPool, UpstreamSession},
+        Metrics,
     },
     async_trait::async_trait,
-    axum::{
-        http::HeaderValue,
-        response::{IntoResponse, Response},
-    },
-    axum_tungstenite::WebSocketUpgrade,
-    hyper::{client::HttpConnector, http, Client, Method},
-    hyper_tls::HttpsConnector,
-    std::collections::HashMap,
+    axum::response::{IntoResponse, Response},
+    hyper::{http, Method},
+    std::{collections::HashMap, sync::Arc},
     tracing::debug,
-    wc::future::FutureExt,
 };
 
 #[derive(Debug)]
 pub struct ZoraProvider {
-    pub client: Client<HttpsConnector<HttpConnector>>,
+    pub client: HyperClient,
     pub supported_chains: HashMap<String, String>,
 }
 
 #[derive(Debug)]
 pub struct ZoraWsProvider {
     pub supported_chains: HashMap<String, String>,
+    pub pool: UpstreamPool,
 }
 
 impl Provider for ZoraWsProvider {
@@ -59,24 +55,13 @@ impl RateLimited for ZoraWsProvider {
 #[async_trait]
 impl RpcWsProvider for ZoraWsProvider {
     #[tracing::instrument(skip_all, fields(provider = %self.provider_kind()), level = "debug")]
-    async fn proxy(
-        &self,
-        ws: WebSocketUpgrade,
-        query_params: RpcQueryParams,
-    ) -> RpcResult<Response> {
+    async fn connect(&self, chain_id: &str, metrics: Arc<Metrics>) -> RpcResult<UpstreamSession> {
         let uri = self
             .supported_chains
-            .get(&query_params.chain_id.to_lowercase())
+            .get(chain_id)
             .ok_or(RpcError::ChainNotFound)?;
 
-        let project_id = query_params.project_id;
-
-        let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri).await?;
-
-        Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
-                .with_metrics(WS_PROXY_TASK_METRICS.with_name("Zora"))
-        }))
+        self.pool.session(chain_id, uri, metrics).await
     }
 }
 
@@ -119,31 +104,26 @@ impl RpcProvider for ZoraProvider {
             .header("Content-Type", "application/json")
             .body(hyper::body::Body::from(body))?;
 
-        let response = self.client.request(hyper_request).await?;
+        let response = UpstreamResponse::read(self.client.request(hyper_request).await?).await?;
         let status = response.status();
-        let body = hyper::body::to_bytes(response.into_body()).await?;
 
-        if let Ok(response) = serde_json::from_slice::<jsonrpc::Response>(&body) {
-            if response.error.is_some() && status.is_success() {
+        if let Some(error) = response.json_rpc_error() {
+            if status.is_success() {
                 debug!(
                     "Strange: provider returned JSON RPC error, but status {status} is success: \
-                     Zora: {response:?}"
+                     Zora: {error}"
                 );
             }
         }
 
-        let mut response = (status, body).into_response();
-        response
-            .headers_mut()
-            .insert("Content-Type", HeaderValue::from_static("application/json"));
-        Ok(response)
+        Ok(response.into_response())
     }
 }
 
 impl RpcProviderFactory<ZoraConfig> for ZoraProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &ZoraConfig) -> Self {
-        let forward_proxy_client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+    fn new(provider_config: &ZoraConfig, http_client: &HyperClient) -> Self {
+        let forward_proxy_client = http_client.clone();
         let supported_chains: HashMap<String, String> = provider_config
             .supported_chains
             .iter()
@@ -159,13 +139,16 @@ impl RpcProviderFactory<ZoraConfig> for ZoraProvider {
 
 impl RpcProviderFactory<ZoraConfig> for ZoraWsProvider {
     #[tracing::instrument(level = "debug")]
-    fn new(provider_config: &ZoraConfig) -> Self {
+    fn new(provider_config: &ZoraConfig, _http_client: &HyperClient) -> Self {
         let supported_chains: HashMap<String, String> = provider_config
             .supported_ws_chains
             .iter()
             .map(|(k, v)| (k.clone(), v.0.clone()))
             .collect();
 
-        ZoraWsProvider { supported_chains }
+        ZoraWsProvider {
+            supported_chains,
+            pool: UpstreamPool::new(ProviderKind::Zora),
+        }
     }
 }
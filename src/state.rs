@@ -5,6 +5,7 @@ use {
         error::RpcError,
         handlers::identity::IdentityResponse,
         metrics::Metrics,
+        names::{avatar::AvatarProxy, sources::IdentitySources},
         project::Registry,
         providers::ProviderRepository,
         storage::irn::Irn,
@@ -12,6 +13,7 @@ use {
         utils::{build::CompileInfo, rate_limit::RateLimit},
     },
     cerberus::project::ProjectDataWithQuota,
+    ethers::signers::LocalWallet,
     sqlx::PgPool,
     std::sync::Arc,
     tap::TapFallible,
@@ -35,6 +37,13 @@ pub struct AppState {
     pub rate_limit: Option<RateLimit>,
     // IRN client
     pub irn: Option<Irn>,
+    /// Avatar images proxy with the thumbnails cache
+    pub avatar_proxy: AvatarProxy,
+    /// Identity lookup sources and their priority order by the project
+    pub identity_sources: IdentitySources,
+    /// Signer of the names CCIP-Read gateway responses, the gateway is
+    /// disabled if not configured
+    pub names_gateway_signer: Option<LocalWallet>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -49,6 +58,8 @@ pub fn new_state(
     http_client: reqwest::Client,
     rate_limit: Option<RateLimit>,
     irn: Option<Irn>,
+    identity_sources: IdentitySources,
+    names_gateway_signer: Option<LocalWallet>,
 ) -> AppState {
     AppState {
         config,
@@ -63,6 +74,9 @@ pub fn new_state(
         http_client,
         rate_limit,
         irn,
+        avatar_proxy: AvatarProxy::new(),
+        identity_sources,
+        names_gateway_signer,
     }
 }
 
@@ -33,6 +33,9 @@ where
 
     /// Delete the value associated with the given key.
     async fn del(&self, key: &str) -> StorageResult<()>;
+
+    /// Delete the values associated with the given keys at once.
+    async fn del_many(&self, keys: &[String]) -> StorageResult<()>;
 }
 
 /// Holder the type of data will be serialized to be stored.
@@ -161,4 +161,17 @@ where
             .await
             .map_err(|e| StorageError::Other(format!("{e}")))
     }
+
+    async fn del_many(&self, keys: &[String]) -> StorageResult<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        self.write_pool
+            .get()
+            .await
+            .map_err(|e| StorageError::Connection(format!("{e}")))?
+            .del(keys)
+            .await
+            .map_err(|e| StorageError::Other(format!("{e}")))
+    }
 }
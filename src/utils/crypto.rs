@@ -1,8 +1,10 @@
 use {
-    crate::{analytics::MessageSource, error::RpcError},
+    crate::{analytics::MessageSource, chains, error::RpcError},
     alloy::primitives::Address,
     base64::prelude::*,
+    bech32::{u5, FromBase32, ToBase32, Variant},
     bs58,
+    ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey as Ed25519VerifyingKey},
     ethers::{
         abi::Token,
         core::{
@@ -19,13 +21,20 @@ use {
     relay_rpc::auth::cacao::{signature::eip6492::verify_eip6492, CacaoError},
     serde::{Deserialize, Serialize},
     std::{str::FromStr, sync::Arc},
-    strum::IntoEnumIterator,
     strum_macros::{Display, EnumIter, EnumString},
-    tracing::{error, warn},
+    tracing::error,
     url::Url,
 };
 
 const ENSIP11_MAINNET_COIN_TYPE: u32 = 60;
+/// SLIP-44 coin type of Bitcoin
+pub const SLIP44_BITCOIN_COIN_TYPE: u32 = 0;
+/// SLIP-44 coin type of Solana
+pub const SLIP44_SOLANA_COIN_TYPE: u32 = 501;
+/// CAIP-2 chain id of the Bitcoin mainnet (genesis block hash prefix)
+pub const BITCOIN_MAINNET_CAIP2: &str = "bip122:000000000019d6689c085ae165831e93";
+/// CAIP-2 chain id of the Solana mainnet (genesis block hash prefix)
+pub const SOLANA_MAINNET_CAIP2: &str = "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp";
 static CAIP_CHAIN_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"[-a-zA-Z0-9]{1,32}").expect("Failed to initialize regexp for the chain ID format")
 });
@@ -335,6 +344,36 @@ pub fn verify_secp256k1_signature(
     Ok(())
 }
 
+/// Verify ed25519 message signature of the Solana account
+/// Signature and the account address (public key) are expected to be Base58
+/// encoded
+#[tracing::instrument(level = "debug")]
+pub fn verify_ed25519_signature(
+    message: &str,
+    signature: &str,
+    address: &str,
+) -> Result<(), RpcError> {
+    let public_key: [u8; 32] = bs58::decode(address)
+        .into_vec()
+        .ok()
+        .and_then(|public_key| public_key.try_into().ok())
+        .ok_or(RpcError::InvalidAddress)?;
+    let verifying_key = Ed25519VerifyingKey::from_bytes(&public_key)
+        .map_err(|e| RpcError::KeyFormatError(e.to_string()))?;
+
+    let signature_bytes = bs58::decode(signature)
+        .into_vec()
+        .map_err(|e| RpcError::SignatureFormatError(e.to_string()))?;
+    let signature = Ed25519Signature::from_slice(&signature_bytes)
+        .map_err(|e| RpcError::SignatureFormatError(e.to_string()))?;
+
+    verifying_key
+        .verify_strict(message.as_bytes(), &signature)
+        .map_err(|e| RpcError::SignatureValidationError(e.to_string()))?;
+
+    Ok(())
+}
+
 /// Get the balance of the ERC20 token
 #[tracing::instrument(level = "debug")]
 pub async fn get_erc20_balance(
@@ -484,11 +523,34 @@ pub fn convert_coin_type_to_evm_chain_id(coin_type: u32) -> u32 {
     0x7FFFFFFF & coin_type
 }
 
+/// Convert coin type ENSIP-11 to CAIP-2 chain ID
+#[tracing::instrument(level = "debug")]
+pub fn convert_coin_type_to_caip2(coin_type: u32) -> String {
+    match coin_type {
+        SLIP44_BITCOIN_COIN_TYPE => BITCOIN_MAINNET_CAIP2.to_string(),
+        SLIP44_SOLANA_COIN_TYPE => SOLANA_MAINNET_CAIP2.to_string(),
+        _ => format!(
+            "{}:{}",
+            CaipNamespaces::Eip155,
+            convert_coin_type_to_evm_chain_id(coin_type)
+        ),
+    }
+}
+
 /// Check if the coin type is in the supported list
 #[tracing::instrument(level = "debug")]
 pub fn is_coin_type_supported(coin_type: u32) -> bool {
+    if matches!(
+        coin_type,
+        SLIP44_BITCOIN_COIN_TYPE | SLIP44_SOLANA_COIN_TYPE
+    ) {
+        return true;
+    }
+
+    // EVM chains coin types other than the mainnet have the EVM flag bit set
+    let is_evm_coin_type = coin_type == ENSIP11_MAINNET_COIN_TYPE || coin_type & 0x80000000 != 0;
     let evm_chain_id = convert_coin_type_to_evm_chain_id(coin_type);
-    ChainId::iter().any(|x| x as u64 == evm_chain_id as u64)
+    is_evm_coin_type && chains::is_evm_chain_supported(evm_chain_id as u64)
 }
 
 /// Check if the address is in correct format
@@ -509,96 +571,95 @@ pub fn is_address_valid(address: &str, namespace: &CaipNamespaces) -> bool {
                 Err(_) => false,
             }
         }
+        CaipNamespaces::Bip122 => bitcoin_script_pubkey(address).is_some(),
     }
 }
 
-/// Human readable chain ids to CAIP-2 chain ids
-#[derive(Clone, Copy, Debug, EnumString, EnumIter, Display)]
-#[strum(serialize_all = "lowercase")]
-#[repr(u64)]
-pub enum ChainId {
-    Arbitrum = 42161,
-    Aurora = 1313161554,
-    Avalanche = 43114,
-    Base = 8453,
-    #[strum(serialize = "base_sepolia_testnet", serialize = "base-sepolia-testnet")]
-    BaseSepoliaTestnet = 84532,
-    #[strum(
-        to_string = "binance-smart-chain",
-        serialize = "binance_smart_chain",
-        serialize = "bsc"
-    )]
-    BinanceSmartChain = 56,
-    Blast = 81032,
-    Celo = 42220,
-    #[strum(serialize = "ethereum", serialize = "mainnet")]
-    Ethereum = 1,
-    Fantom = 250,
-    Goerli = 5,
-    Linea = 59160,
-    Optimism = 10,
-    Polygon = 137,
-    Scroll = 8508132,
-    Sepolia = 11155111,
-    #[strum(
-        to_string = "xdai",
-        serialize = "gnosis",
-        serialize = "gnosis_chain",
-        serialize = "gnosis-chain",
-        serialize = "gnosischain"
-    )]
-    GnosisChain = 100,
-    #[strum(
-        serialize = "zksync",
-        serialize = "zksyncera",
-        serialize = "zksync-era"
-    )]
-    ZkSyncEra = 324,
-    Zora = 7854577,
-}
-
-impl ChainId {
-    /// Convert from human readable chain name id (e.g. polygon) to CAIP-2
-    /// format chain id (e.g. `eip155:137`)
-    pub fn to_caip2(chain_name: &str) -> Option<String> {
-        match ChainId::from_str(chain_name) {
-            Ok(chain_id) => Some(format!("eip155:{}", chain_id as u64)),
-            Err(_) => {
-                warn!("CAIP-2 Convertion: Chain name is not found: {}", chain_name);
-                None
-            }
+/// Convert the Bitcoin mainnet address to the ENSIP-9 binary format which is
+/// the output script (scriptPubkey) of the address
+/// Returns `None` if the address is not a valid P2PKH, P2SH or SegWit address
+pub fn bitcoin_script_pubkey(address: &str) -> Option<Vec<u8>> {
+    // SegWit addresses (BIP-173 and BIP-350)
+    if address.to_lowercase().starts_with("bc1") {
+        let (hrp, data, variant) = bech32::decode(address).ok()?;
+        let (version, program) = data.split_first()?;
+        let version = version.to_u8();
+        let program = Vec::<u8>::from_base32(program).ok()?;
+        let expected_variant = if version == 0 {
+            Variant::Bech32
+        } else {
+            Variant::Bech32m
+        };
+        if hrp != "bc"
+            || version > 16
+            || variant != expected_variant
+            || !(2..=40).contains(&program.len())
+            || (version == 0 && program.len() != 20 && program.len() != 32)
+        {
+            return None;
         }
+
+        // Witness version opcode followed by the program push
+        let version_opcode = if version == 0 { 0x00 } else { 0x50 + version };
+        let mut script = vec![version_opcode, program.len() as u8];
+        script.extend(program);
+        return Some(script);
     }
 
-    /// Convert from CAIP-2 format (e.g. `eip155:137`) to human readable chain
-    /// name id (e.g. polygon)
-    pub fn from_caip2(caip2_chain_id: &str) -> Option<String> {
-        let extracted_chain_id = caip2_chain_id
-            .split(':')
-            .collect::<Vec<&str>>()
-            .pop()
-            .unwrap_or_default()
-            .parse::<u64>()
-            .unwrap_or_default();
-
-        match ChainId::iter()
-            .find(|&x| x as u64 == extracted_chain_id)
-            .map(|x| x.to_string())
-        {
-            Some(chain_id) => Some(chain_id),
-            None => {
-                warn!(
-                    "CAIP-2 Convertion: Chain id is not found: {}",
-                    caip2_chain_id
-                );
-                None
-            }
-        }
+    // Legacy Base58Check addresses
+    let decoded = bs58::decode(address).with_check(None).into_vec().ok()?;
+    let (version, hash) = decoded.split_first()?;
+    if hash.len() != 20 {
+        return None;
     }
+    match version {
+        // P2PKH
+        0x00 => Some([&[0x76, 0xa9, 0x14][..], hash, &[0x88, 0xac]].concat()),
+        // P2SH
+        0x05 => Some([&[0xa9, 0x14][..], hash, &[0x87]].concat()),
+        _ => None,
+    }
+}
 
-    /// Is ChainID is supported
-    pub fn is_supported(chain_id: u64) -> bool {
-        ChainId::iter().any(|x| x as u64 == chain_id)
+/// Convert the ENSIP-9 binary format (scriptPubkey) of the Bitcoin mainnet
+/// address back to the address, the inverse of the `bitcoin_script_pubkey`
+/// Returns `None` if the script is not a P2PKH, P2SH or SegWit output script
+pub fn bitcoin_address(script: &[u8]) -> Option<String> {
+    match script {
+        // P2PKH
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => Some(
+            bs58::encode([&[0x00][..], hash].concat())
+                .with_check()
+                .into_string(),
+        ),
+        // P2SH
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => Some(
+            bs58::encode([&[0x05][..], hash].concat())
+                .with_check()
+                .into_string(),
+        ),
+        // SegWit, witness version opcode followed by the program push
+        [version_opcode, length, program @ ..] if *length as usize == program.len() => {
+            let version = match version_opcode {
+                0x00 => 0,
+                0x51..=0x60 => version_opcode - 0x50,
+                _ => return None,
+            };
+            if !(2..=40).contains(&program.len())
+                || (version == 0 && program.len() != 20 && program.len() != 32)
+            {
+                return None;
+            }
+            let variant = if version == 0 {
+                Variant::Bech32
+            } else {
+                Variant::Bech32m
+            };
+            let mut data = vec![u5::try_from_u8(version).ok()?];
+            data.extend(program.to_base32());
+            bech32::encode("bc", data, variant).ok()
+        }
+        _ => None,
     }
 }
 
@@ -608,6 +669,7 @@ impl ChainId {
 pub enum CaipNamespaces {
     Eip155,
     Solana,
+    Bip122,
 }
 
 pub fn format_to_caip10(namespace: CaipNamespaces, chain_id: &str, address: &str) -> String {
@@ -716,7 +778,6 @@ mod tests {
             utils::keccak256,
         },
         rand_core::OsRng,
-        std::collections::HashMap,
     };
 
     #[test]
@@ -740,46 +801,26 @@ mod tests {
         assert!(is_coin_type_supported(coin_type_eth_mainnet));
         assert!(is_coin_type_supported(coin_type_polygon));
         assert!(!is_coin_type_supported(coin_type_not_supported));
-    }
 
-    #[test]
-    fn test_human_format_to_caip2_format() {
-        let mut chains: HashMap<&str, &str> = HashMap::new();
-        chains.insert("ethereum", "eip155:1");
-        chains.insert("mainnet", "eip155:1");
-        chains.insert("goerli", "eip155:5");
-        chains.insert("optimism", "eip155:10");
-        chains.insert("bsc", "eip155:56");
-        chains.insert("gnosis", "eip155:100");
-        chains.insert("xdai", "eip155:100");
-        chains.insert("polygon", "eip155:137");
-        chains.insert("base", "eip155:8453");
-        chains.insert("base_sepolia_testnet", "eip155:84532");
-
-        for (chain_name, coin_type) in chains.iter() {
-            let result = ChainId::to_caip2(chain_name);
-            assert!(result.is_some(), "chain_name is not found: {}", chain_name);
-            assert_eq!(&result.unwrap(), coin_type);
-        }
+        // Non-EVM coin types
+        assert!(is_coin_type_supported(SLIP44_BITCOIN_COIN_TYPE));
+        assert!(is_coin_type_supported(SLIP44_SOLANA_COIN_TYPE));
+        // Polygon chain ID without the EVM flag is the SLIP-44 coin type
+        assert!(!is_coin_type_supported(137));
     }
 
     #[test]
-    fn test_caip2_format_to_human_format() {
-        let mut chains: HashMap<&str, &str> = HashMap::new();
-        chains.insert("eip155:1", "ethereum");
-        chains.insert("eip155:5", "goerli");
-        chains.insert("eip155:10", "optimism");
-        chains.insert("eip155:56", "binance-smart-chain");
-        chains.insert("eip155:100", "xdai");
-        chains.insert("eip155:137", "polygon");
-        chains.insert("eip155:8453", "base");
-        chains.insert("eip155:84532", "base-sepolia-testnet");
-
-        for (chain_id, chain_name) in chains.iter() {
-            let result = ChainId::from_caip2(chain_id);
-            assert!(result.is_some(), "chain_id is not found: {}", chain_id);
-            assert_eq!(&result.unwrap(), chain_name);
-        }
+    fn test_convert_coin_type_to_caip2() {
+        assert_eq!(convert_coin_type_to_caip2(60), "eip155:1");
+        assert_eq!(convert_coin_type_to_caip2(2147483785), "eip155:137");
+        assert_eq!(
+            convert_coin_type_to_caip2(SLIP44_SOLANA_COIN_TYPE),
+            SOLANA_MAINNET_CAIP2
+        );
+        assert_eq!(
+            convert_coin_type_to_caip2(SLIP44_BITCOIN_COIN_TYPE),
+            BITCOIN_MAINNET_CAIP2
+        );
     }
 
     #[test]
@@ -895,6 +936,95 @@ mod tests {
 
         assert!(is_address_valid(valid_sol_address, &CaipNamespaces::Solana));
         assert!(!is_address_valid(invalid_address, &CaipNamespaces::Solana));
+
+        let valid_btc_address = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+        assert!(is_address_valid(valid_btc_address, &CaipNamespaces::Bip122));
+        assert!(!is_address_valid(invalid_address, &CaipNamespaces::Bip122));
+        assert!(!is_address_valid(
+            valid_sol_address,
+            &CaipNamespaces::Bip122
+        ));
+    }
+
+    #[test]
+    fn test_bitcoin_script_pubkey() {
+        // ENSIP-9 and BIP-173/BIP-350 test vectors
+        let vectors = [
+            (
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+                "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac",
+            ),
+            (
+                "3Ai1JZ8pdJb2ksieUV8FsxSNVJCpoPi8W6",
+                "a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1887",
+            ),
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ];
+        for (address, script) in vectors {
+            assert_eq!(
+                bitcoin_script_pubkey(address).map(hex::encode),
+                Some(script.to_owned())
+            );
+        }
+
+        // Wrong checksum
+        assert!(bitcoin_script_pubkey("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb").is_none());
+        // Testnet address
+        assert!(bitcoin_script_pubkey("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx").is_none());
+        // SegWit v1 address with the Bech32 checksum
+        assert!(bitcoin_script_pubkey(
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y"
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_bitcoin_address() {
+        let addresses = [
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            "3Ai1JZ8pdJb2ksieUV8FsxSNVJCpoPi8W6",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        ];
+        for address in addresses {
+            let script = bitcoin_script_pubkey(address).unwrap();
+            assert_eq!(bitcoin_address(&script), Some(address.to_owned()));
+        }
+
+        // Unknown output script
+        assert!(bitcoin_address(&[0x6a, 0x01, 0x00]).is_none());
+        // Truncated P2PKH script
+        assert!(bitcoin_address(
+            &hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f18").unwrap()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_verify_ed25519_signature() {
+        use ed25519_dalek::{Signer as _, SigningKey as Ed25519SigningKey};
+
+        let signing_key = Ed25519SigningKey::from_bytes(&[7; 32]);
+        let address = bs58::encode(signing_key.verifying_key().as_bytes()).into_string();
+        let message = "test message";
+        let signature = bs58::encode(signing_key.sign(message.as_bytes()).to_bytes()).into_string();
+
+        assert!(is_address_valid(&address, &CaipNamespaces::Solana));
+        assert!(verify_ed25519_signature(message, &signature, &address).is_ok());
+        assert!(verify_ed25519_signature("wrong message", &signature, &address).is_err());
+        assert!(verify_ed25519_signature(
+            message,
+            &signature,
+            "CKfatsPMUf8SkiURsDXs7eK6GWb4Jsd6UDbs7twMCWxo"
+        )
+        .is_err());
     }
 
     // Ignoring this test until the RPC project ID is provided by the CI workflow
@@ -15,6 +15,15 @@ pub struct RateLimitingConfig {
     pub refill_interval_sec: Option<u32>,
     pub refill_rate: Option<u32>,
     pub ip_whitelist: Option<Vec<String>>,
+    /// Per-connection WebSocket messages token bucket size
+    pub ws_max_tokens: Option<u32>,
+    /// Per-connection WebSocket messages refill rate per second
+    pub ws_refill_rate: Option<u32>,
+    /// Maximum number of active subscriptions per WebSocket connection
+    pub ws_max_subscriptions: Option<u32>,
+    /// Interval of the project quota re-validation for open WebSocket
+    /// connections
+    pub ws_quota_check_interval_sec: Option<u32>,
 }
 
 pub struct RateLimit {
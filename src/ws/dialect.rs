@@ -0,0 +1,183 @@
+//! Subscription methods of the chain namespaces WebSocket APIs.
+
+use {
+    crate::utils::crypto::{disassemble_caip2, CaipNamespaces},
+    serde_json::Value,
+};
+
+const ETH_SUBSCRIBE_METHOD: &str = "eth_subscribe";
+const ETH_UNSUBSCRIBE_METHOD: &str = "eth_unsubscribe";
+const ETH_SUBSCRIPTION_METHOD: &str = "eth_subscription";
+
+const SOLANA_SUBSCRIBE_SUFFIX: &str = "Subscribe";
+const SOLANA_UNSUBSCRIBE_SUFFIX: &str = "Unsubscribe";
+const SOLANA_NOTIFICATION_SUFFIX: &str = "Notification";
+/// Solana subscriptions that are cancelled by the node after the first
+/// notification
+const SOLANA_SINGLE_NOTIFICATION_METHODS: &[&str] = &["signatureSubscribe"];
+/// Notification value of the `signatureSubscribe` with the
+/// `enableReceivedNotification` that is followed by the final notification
+const SOLANA_RECEIVED_SIGNATURE_VALUE: &str = "receivedSignature";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionDialect {
+    /// `eth_subscribe`, `eth_unsubscribe` and `eth_subscription`
+    /// notifications with the hex string subscription ids
+    Ethereum,
+    /// `<kind>Subscribe`, `<kind>Unsubscribe` and `<kind>Notification`
+    /// notifications with the numeric subscription ids
+    Solana,
+}
+
+impl SubscriptionDialect {
+    pub fn from_chain_id(chain_id: &str) -> Self {
+        match disassemble_caip2(chain_id) {
+            Ok((CaipNamespaces::Solana, _)) => Self::Solana,
+            _ => Self::Ethereum,
+        }
+    }
+
+    pub fn is_subscribe(&self, method: &str) -> bool {
+        match self {
+            Self::Ethereum => method == ETH_SUBSCRIBE_METHOD,
+            Self::Solana => {
+                method.len() > SOLANA_SUBSCRIBE_SUFFIX.len()
+                    && method.ends_with(SOLANA_SUBSCRIBE_SUFFIX)
+            }
+        }
+    }
+
+    pub fn is_unsubscribe(&self, method: &str) -> bool {
+        match self {
+            Self::Ethereum => method == ETH_UNSUBSCRIBE_METHOD,
+            Self::Solana => {
+                method.len() > SOLANA_UNSUBSCRIBE_SUFFIX.len()
+                    && method.ends_with(SOLANA_UNSUBSCRIBE_SUFFIX)
+            }
+        }
+    }
+
+    pub fn is_notification(&self, method: &str) -> bool {
+        match self {
+            Self::Ethereum => method == ETH_SUBSCRIPTION_METHOD,
+            Self::Solana => {
+                method.len() > SOLANA_NOTIFICATION_SUFFIX.len()
+                    && method.ends_with(SOLANA_NOTIFICATION_SUFFIX)
+            }
+        }
+    }
+
+    /// Returns the unsubscribe method for the subscription created by the
+    /// subscribe method
+    pub fn unsubscribe_method(&self, subscribe_method: &str) -> String {
+        match self {
+            Self::Ethereum => ETH_UNSUBSCRIBE_METHOD.to_owned(),
+            Self::Solana => format!(
+                "{}{SOLANA_UNSUBSCRIBE_SUFFIX}",
+                subscribe_method
+                    .strip_suffix(SOLANA_SUBSCRIBE_SUFFIX)
+                    .unwrap_or(subscribe_method)
+            ),
+        }
+    }
+
+    /// Whether the subscription is removed by the node after delivering the
+    /// first notification
+    pub fn is_single_notification(&self, subscribe_method: &str) -> bool {
+        match self {
+            Self::Ethereum => false,
+            Self::Solana => SOLANA_SINGLE_NOTIFICATION_METHODS.contains(&subscribe_method),
+        }
+    }
+
+    /// Whether the notification of the single notification subscription is
+    /// the last one, after which the subscription is removed by the node
+    pub fn is_final_notification(&self, notification: &Value) -> bool {
+        match self {
+            Self::Ethereum => true,
+            Self::Solana => {
+                notification
+                    .get("params")
+                    .and_then(|params| params.get("result"))
+                    .and_then(|result| result.get("value"))
+                    .and_then(Value::as_str)
+                    != Some(SOLANA_RECEIVED_SIGNATURE_VALUE)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dialect_from_chain_id() {
+        assert_eq!(
+            SubscriptionDialect::from_chain_id("eip155:1"),
+            SubscriptionDialect::Ethereum
+        );
+        assert_eq!(
+            SubscriptionDialect::from_chain_id("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"),
+            SubscriptionDialect::Solana
+        );
+    }
+
+    #[test]
+    fn ethereum_methods() {
+        let dialect = SubscriptionDialect::Ethereum;
+        assert!(dialect.is_subscribe("eth_subscribe"));
+        assert!(!dialect.is_subscribe("accountSubscribe"));
+        assert!(dialect.is_unsubscribe("eth_unsubscribe"));
+        assert!(dialect.is_notification("eth_subscription"));
+        assert_eq!(
+            dialect.unsubscribe_method("eth_subscribe"),
+            "eth_unsubscribe"
+        );
+        assert!(!dialect.is_single_notification("eth_subscribe"));
+    }
+
+    #[test]
+    fn solana_methods() {
+        let dialect = SubscriptionDialect::Solana;
+        assert!(dialect.is_subscribe("accountSubscribe"));
+        assert!(dialect.is_subscribe("signatureSubscribe"));
+        assert!(!dialect.is_subscribe("accountUnsubscribe"));
+        assert!(!dialect.is_subscribe("Subscribe"));
+        assert!(!dialect.is_subscribe("eth_subscribe"));
+        assert!(dialect.is_unsubscribe("logsUnsubscribe"));
+        assert!(!dialect.is_unsubscribe("logsSubscribe"));
+        assert!(dialect.is_notification("accountNotification"));
+        assert!(!dialect.is_notification("getAccountInfo"));
+        assert_eq!(
+            dialect.unsubscribe_method("accountSubscribe"),
+            "accountUnsubscribe"
+        );
+        assert!(dialect.is_single_notification("signatureSubscribe"));
+        assert!(!dialect.is_single_notification("accountSubscribe"));
+    }
+
+    #[test]
+    fn solana_received_signature_notification() {
+        let dialect = SubscriptionDialect::Solana;
+        let received = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "signatureNotification",
+            "params": {
+                "result": {"context": {"slot": 5207624}, "value": "receivedSignature"},
+                "subscription": 24006
+            }
+        });
+        assert!(!dialect.is_final_notification(&received));
+
+        let processed = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "signatureNotification",
+            "params": {
+                "result": {"context": {"slot": 5207624}, "value": {"err": null}},
+                "subscription": 24006
+            }
+        });
+        assert!(dialect.is_final_notification(&processed));
+    }
+}
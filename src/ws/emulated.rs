@@ -0,0 +1,436 @@
+//! WebSocket endpoint emulation for chains that are only served by HTTP
+//! providers.
+//!
+//! Ordinary request frames are answered through the HTTP proxy path, while
+//! `eth_subscribe` for `newHeads` and `logs` is served by polling the HTTP
+//! providers and pushing `eth_subscription` notifications to the client.
+
+use {
+    super::inspector::FrameInspector,
+    crate::{
+        error::RpcError,
+        handlers::{proxy::rpc_call, RpcQueryParams},
+        json_rpc::RequestEnvelope,
+        state::AppState,
+    },
+    axum::body::Bytes,
+    axum_tungstenite::{Message, WebSocket},
+    futures_util::{SinkExt, StreamExt},
+    hyper::HeaderMap,
+    rand::Rng,
+    serde_json::{json, Value},
+    std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration},
+    tokio::time::{interval, MissedTickBehavior},
+    tracing::log::{debug, warn},
+};
+
+/// Interval between the chain head polls of the subscriptions
+pub const POLLING_INTERVAL: Duration = Duration::from_secs(2);
+/// Maximum number of blocks processed per subscription in a single poll to
+/// bound the upstream calls after a long provider outage
+const MAX_BLOCKS_PER_POLL: u64 = 10;
+
+/// JSON-RPC error codes used for the emulated responses
+const JSON_RPC_INVALID_REQUEST: i32 = -32600;
+const JSON_RPC_INVALID_PARAMS: i32 = -32602;
+const JSON_RPC_INTERNAL_ERROR: i32 = -32603;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SubscriptionKind {
+    NewHeads,
+    Logs {
+        address: Option<Value>,
+        topics: Option<Value>,
+    },
+}
+
+#[derive(Debug)]
+struct Subscription {
+    kind: SubscriptionKind,
+    /// Last block number that was delivered for the subscription, `None`
+    /// until the first poll sets the starting point
+    last_block: Option<u64>,
+}
+
+/// Context of the client connection used to make HTTP proxy calls
+struct Upstream {
+    state: Arc<AppState>,
+    addr: SocketAddr,
+    query_params: RpcQueryParams,
+    headers: HeaderMap,
+}
+
+impl Upstream {
+    /// Proxies the raw JSON-RPC body through the HTTP providers and returns
+    /// the raw response body
+    async fn call_raw(&self, body: Bytes) -> Result<Bytes, RpcError> {
+        let response = rpc_call(
+            self.state.clone(),
+            self.addr,
+            self.query_params.clone(),
+            self.headers.clone(),
+            RequestEnvelope::parse(body),
+        )
+        .await?;
+        hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|e| RpcError::WebSocketEmulation(e.to_string()))
+    }
+
+    /// Makes a single JSON-RPC call and returns the `result` field
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": rand::thread_rng().gen::<u32>(),
+            "method": method,
+            "params": params,
+        });
+        let body = self.call_raw(Bytes::from(request.to_string())).await?;
+        let mut response: Value = serde_json::from_slice(&body)?;
+        if let Some(error) = response.get("error") {
+            return Err(RpcError::WebSocketEmulation(format!(
+                "Upstream error for {method}: {error}"
+            )));
+        }
+        Ok(response
+            .get_mut("result")
+            .map(Value::take)
+            .unwrap_or(Value::Null))
+    }
+
+    async fn block_number(&self) -> Result<u64, RpcError> {
+        let result = self.call("eth_blockNumber", json!([])).await?;
+        result
+            .as_str()
+            .and_then(parse_hex_quantity)
+            .ok_or_else(|| RpcError::WebSocketEmulation(format!("Invalid block number: {result}")))
+    }
+}
+
+/// Serves the emulated WebSocket interface until the client disconnects
+#[tracing::instrument(skip_all, fields(chain_id = %query_params.chain_id), level = "debug")]
+pub async fn proxy(
+    state: Arc<AppState>,
+    addr: SocketAddr,
+    query_params: RpcQueryParams,
+    headers: HeaderMap,
+    client_ws: WebSocket,
+    mut inspector: FrameInspector,
+) {
+    let project_id = query_params.project_id.clone();
+    let upstream = Upstream {
+        state,
+        addr,
+        query_params,
+        headers,
+    };
+    let (mut sender, mut receiver) = client_ws.split();
+    let mut subscriptions: HashMap<String, Subscription> = HashMap::new();
+    let mut poll = interval(POLLING_INTERVAL);
+    poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut quota_check = super::quota_check_timer(inspector.quota_check_interval());
+
+    loop {
+        let outgoing = tokio::select! {
+            message = receiver.next() => {
+                let frame = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Binary(data))) => String::from_utf8_lossy(&data).into_owned(),
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        debug!("Emulated WebSocket for client {project_id} failed to receive: {e}");
+                        break;
+                    }
+                };
+                match inspector.inspect(&frame, subscriptions.len()) {
+                    Ok(()) => handle_frame(&upstream, &mut subscriptions, frame.as_bytes()).await,
+                    Err(response) => vec![response],
+                }
+            },
+            _ = quota_check.tick() => {
+                if inspector.is_quota_valid().await {
+                    continue;
+                }
+                debug!("Closing emulated WebSocket for client {project_id} due to the quota limit");
+                let _ = sender.send(super::quota_limit_close_message()).await;
+                break;
+            },
+            _ = poll.tick() => {
+                if subscriptions.is_empty() {
+                    continue;
+                }
+                poll_subscriptions(&upstream, &mut subscriptions).await
+            }
+        };
+
+        for message in outgoing {
+            if let Err(e) = sender.send(Message::Text(message)).await {
+                debug!("Emulated WebSocket for client {project_id} failed to send: {e}");
+                return;
+            }
+        }
+    }
+    debug!("Emulated WebSocket for client {project_id} closed.");
+}
+
+/// Handles a single client frame and returns the messages to send back
+async fn handle_frame(
+    upstream: &Upstream,
+    subscriptions: &mut HashMap<String, Subscription>,
+    frame: &[u8],
+) -> Vec<String> {
+    let request: Value = match serde_json::from_slice(frame) {
+        Ok(request) => request,
+        Err(e) => {
+            return vec![error_response(
+                &Value::Null,
+                JSON_RPC_INVALID_REQUEST,
+                &format!("Invalid JSON-RPC request: {e}"),
+            )]
+        }
+    };
+
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    match request.get("method").and_then(Value::as_str) {
+        Some("eth_subscribe") => vec![subscribe(subscriptions, &id, &params)],
+        Some("eth_unsubscribe") => {
+            let removed = params
+                .get(0)
+                .and_then(Value::as_str)
+                .and_then(|subscription_id| subscriptions.remove(subscription_id))
+                .is_some();
+            vec![result_response(&id, Value::Bool(removed))]
+        }
+        // Ordinary requests and batches are answered through the HTTP proxy
+        _ => match upstream.call_raw(Bytes::copy_from_slice(frame)).await {
+            Ok(body) => vec![String::from_utf8_lossy(&body).into_owned()],
+            Err(e) => {
+                warn!("Emulated WebSocket request failed: {e}");
+                vec![error_response(&id, JSON_RPC_INTERNAL_ERROR, &e.to_string())]
+            }
+        },
+    }
+}
+
+fn subscribe(
+    subscriptions: &mut HashMap<String, Subscription>,
+    id: &Value,
+    params: &Value,
+) -> String {
+    let kind = match parse_subscription_kind(params) {
+        Ok(kind) => kind,
+        Err(message) => return error_response(id, JSON_RPC_INVALID_PARAMS, &message),
+    };
+    let subscription_id = format!("0x{}", hex::encode(rand::thread_rng().gen::<[u8; 16]>()));
+    subscriptions.insert(
+        subscription_id.clone(),
+        Subscription {
+            kind,
+            last_block: None,
+        },
+    );
+    result_response(id, Value::String(subscription_id))
+}
+
+fn parse_subscription_kind(params: &Value) -> Result<SubscriptionKind, String> {
+    match params.get(0).and_then(Value::as_str) {
+        Some("newHeads") => Ok(SubscriptionKind::NewHeads),
+        Some("logs") => {
+            let filter = params.get(1).cloned().unwrap_or(Value::Null);
+            Ok(SubscriptionKind::Logs {
+                address: filter.get("address").cloned(),
+                topics: filter.get("topics").cloned(),
+            })
+        }
+        Some(other) => Err(format!("Unsupported subscription type: {other}")),
+        None => Err("Missing subscription type".into()),
+    }
+}
+
+/// Polls the chain head and returns notifications for all subscriptions
+async fn poll_subscriptions(
+    upstream: &Upstream,
+    subscriptions: &mut HashMap<String, Subscription>,
+) -> Vec<String> {
+    let head = match upstream.block_number().await {
+        Ok(head) => head,
+        Err(e) => {
+            warn!("Failed to poll the block number for emulated subscriptions: {e}");
+            return vec![];
+        }
+    };
+
+    let mut notifications = vec![];
+    // Blocks are shared between the `newHeads` subscriptions of the connection
+    let mut blocks: HashMap<u64, Value> = HashMap::new();
+    for (subscription_id, subscription) in subscriptions.iter_mut() {
+        let Some(last_block) = subscription.last_block else {
+            // Start delivering from the next block after the subscription
+            subscription.last_block = Some(head);
+            continue;
+        };
+        if head <= last_block {
+            continue;
+        }
+        let to_block = head.min(last_block + MAX_BLOCKS_PER_POLL);
+
+        let results = match &subscription.kind {
+            SubscriptionKind::NewHeads => {
+                let mut headers = vec![];
+                for number in (last_block + 1)..=to_block {
+                    let block = match blocks.get(&number) {
+                        Some(block) => block.clone(),
+                        None => match upstream
+                            .call(
+                                "eth_getBlockByNumber",
+                                json!([format!("{number:#x}"), false]),
+                            )
+                            .await
+                        {
+                            Ok(block) if !block.is_null() => {
+                                blocks.insert(number, block.clone());
+                                block
+                            }
+                            Ok(_) => break,
+                            Err(e) => {
+                                warn!("Failed to get block {number} for emulated newHeads: {e}");
+                                break;
+                            }
+                        },
+                    };
+                    headers.push(block);
+                }
+                headers
+            }
+            SubscriptionKind::Logs { address, topics } => {
+                let mut filter = json!({
+                    "fromBlock": format!("{:#x}", last_block + 1),
+                    "toBlock": format!("{to_block:#x}"),
+                });
+                if let Some(address) = address {
+                    filter["address"] = address.clone();
+                }
+                if let Some(topics) = topics {
+                    filter["topics"] = topics.clone();
+                }
+                match upstream.call("eth_getLogs", json!([filter])).await {
+                    Ok(Value::Array(logs)) => logs,
+                    Ok(_) => vec![],
+                    Err(e) => {
+                        warn!("Failed to get logs for emulated logs subscription: {e}");
+                        continue;
+                    }
+                }
+            }
+        };
+
+        subscription.last_block = Some(match subscription.kind {
+            SubscriptionKind::NewHeads => last_block + results.len() as u64,
+            SubscriptionKind::Logs { .. } => to_block,
+        });
+        notifications.extend(
+            results
+                .into_iter()
+                .map(|result| notification(subscription_id, result)),
+        );
+    }
+    notifications
+}
+
+fn parse_hex_quantity(value: &str) -> Option<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+fn notification(subscription_id: &str, result: Value) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "eth_subscription",
+        "params": {
+            "subscription": subscription_id,
+            "result": result,
+        },
+    })
+    .to_string()
+}
+
+fn result_response(id: &Value, result: Value) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    })
+    .to_string()
+}
+
+fn error_response(id: &Value, code: i32, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn parses_subscription_kinds() {
+        assert_eq!(
+            parse_subscription_kind(&json!(["newHeads"])).unwrap(),
+            SubscriptionKind::NewHeads
+        );
+        assert_eq!(
+            parse_subscription_kind(&json!(["logs", {"address": "0x1", "topics": ["0x2"]}]))
+                .unwrap(),
+            SubscriptionKind::Logs {
+                address: Some(json!("0x1")),
+                topics: Some(json!(["0x2"])),
+            }
+        );
+        assert_eq!(
+            parse_subscription_kind(&json!(["logs"])).unwrap(),
+            SubscriptionKind::Logs {
+                address: None,
+                topics: None,
+            }
+        );
+        assert!(parse_subscription_kind(&json!(["newPendingTransactions"])).is_err());
+        assert!(parse_subscription_kind(&json!([])).is_err());
+    }
+
+    #[test]
+    fn subscribes() {
+        let mut subscriptions = HashMap::new();
+        let response: Value = serde_json::from_str(&subscribe(
+            &mut subscriptions,
+            &json!(1),
+            &json!(["newHeads"]),
+        ))
+        .unwrap();
+        let subscription_id = response["result"].as_str().unwrap();
+        assert!(subscriptions.contains_key(subscription_id));
+
+        let response: Value = serde_json::from_str(&subscribe(
+            &mut subscriptions,
+            &json!(2),
+            &json!(["newPendingTransactions"]),
+        ))
+        .unwrap();
+        assert_eq!(response["error"]["code"], JSON_RPC_INVALID_PARAMS);
+        assert_eq!(subscriptions.len(), 1);
+    }
+
+    #[test]
+    fn parses_hex_quantities() {
+        assert_eq!(parse_hex_quantity("0x10"), Some(16));
+        assert_eq!(parse_hex_quantity("0x0"), Some(0));
+        assert_eq!(parse_hex_quantity("0xzz"), None);
+    }
+}
@@ -0,0 +1,346 @@
+//! Inspection of the client WebSocket frames.
+//!
+//! Each JSON-RPC request of the client frames is recorded in the analytics
+//! and metrics, and the per-connection message rate and subscription limits
+//! are enforced before the frame is relayed.
+
+use {
+    super::dialect::SubscriptionDialect,
+    crate::{
+        analytics::MessageInfo, handlers::RpcQueryParams, json_rpc::JsonRpcRequest,
+        providers::ProviderKind, state::AppState, utils::network,
+    },
+    hyper::HeaderMap,
+    serde_json::{json, Value},
+    std::{
+        net::SocketAddr,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+};
+
+const DEFAULT_MAX_TOKENS: u32 = 100;
+const DEFAULT_REFILL_RATE: u32 = 20;
+const DEFAULT_MAX_SUBSCRIPTIONS: u32 = 32;
+const DEFAULT_QUOTA_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Methods recorded with their own metrics label, the other methods are
+/// recorded as `other` so the clients can't create new label values
+const METHOD_LABELS: &[&str] = &[
+    "eth_blockNumber",
+    "eth_call",
+    "eth_chainId",
+    "eth_estimateGas",
+    "eth_feeHistory",
+    "eth_gasPrice",
+    "eth_getBalance",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getCode",
+    "eth_getLogs",
+    "eth_getStorageAt",
+    "eth_getTransactionByHash",
+    "eth_getTransactionCount",
+    "eth_getTransactionReceipt",
+    "eth_maxPriorityFeePerGas",
+    "eth_sendRawTransaction",
+    "eth_subscribe",
+    "eth_unsubscribe",
+    "net_version",
+    "accountSubscribe",
+    "accountUnsubscribe",
+    "blockSubscribe",
+    "blockUnsubscribe",
+    "logsSubscribe",
+    "logsUnsubscribe",
+    "programSubscribe",
+    "programUnsubscribe",
+    "rootSubscribe",
+    "rootUnsubscribe",
+    "signatureSubscribe",
+    "signatureUnsubscribe",
+    "slotSubscribe",
+    "slotUnsubscribe",
+    "getAccountInfo",
+    "getBalance",
+    "getLatestBlockhash",
+    "getSignatureStatuses",
+    "getSlot",
+    "sendTransaction",
+];
+const OTHER_METHOD_LABEL: &str = "other";
+
+/// JSON-RPC error codes used for the rejected requests
+const JSON_RPC_INVALID_REQUEST: i32 = -32600;
+const JSON_RPC_LIMIT_EXCEEDED: i32 = -32005;
+
+/// Per-connection messages token bucket
+struct TokenBucket {
+    max_tokens: f64,
+    refill_rate: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(max_tokens: u32, refill_rate: u32) -> Self {
+        Self {
+            max_tokens: max_tokens as f64,
+            refill_rate: refill_rate as f64,
+            tokens: max_tokens as f64,
+            updated_at: Instant::now(),
+        }
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_rate).min(self.max_tokens);
+        self.updated_at = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+pub struct FrameInspector {
+    state: Arc<AppState>,
+    query_params: RpcQueryParams,
+    dialect: SubscriptionDialect,
+    /// Provider of the relayed frames, `None` if the requests are
+    /// already recorded by the HTTP proxy path
+    provider_kind: Option<ProviderKind>,
+    origin: Option<String>,
+    region: Option<Vec<String>>,
+    country: Option<Arc<str>>,
+    continent: Option<Arc<str>>,
+    bucket: TokenBucket,
+    max_subscriptions: usize,
+    quota_check_interval: Duration,
+}
+
+impl FrameInspector {
+    pub fn new(
+        state: Arc<AppState>,
+        query_params: RpcQueryParams,
+        provider_kind: Option<ProviderKind>,
+        addr: SocketAddr,
+        headers: &HeaderMap,
+    ) -> Self {
+        let origin = headers
+            .get("origin")
+            .map(|v| v.to_str().unwrap_or("invalid_header").to_string());
+        let (country, continent, region) = state
+            .analytics
+            .lookup_geo_data(
+                network::get_forwarded_ip(headers.clone()).unwrap_or_else(|| addr.ip()),
+            )
+            .map(|geo| (geo.country, geo.continent, geo.region))
+            .unwrap_or((None, None, None));
+
+        let config = &state.config.rate_limiting;
+        let bucket = TokenBucket::new(
+            config.ws_max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            config.ws_refill_rate.unwrap_or(DEFAULT_REFILL_RATE),
+        );
+        let max_subscriptions = config
+            .ws_max_subscriptions
+            .unwrap_or(DEFAULT_MAX_SUBSCRIPTIONS) as usize;
+        // Zero interval is not a valid timer period and falls back to the
+        // default one
+        let quota_check_interval = config
+            .ws_quota_check_interval_sec
+            .filter(|interval| *interval > 0)
+            .map(|interval| Duration::from_secs(interval as u64))
+            .unwrap_or(DEFAULT_QUOTA_CHECK_INTERVAL);
+
+        Self {
+            state,
+            dialect: SubscriptionDialect::from_chain_id(&query_params.chain_id),
+            query_params,
+            provider_kind,
+            origin,
+            region,
+            country,
+            continent,
+            bucket,
+            max_subscriptions,
+            quota_check_interval,
+        }
+    }
+
+    pub fn quota_check_interval(&self) -> Duration {
+        self.quota_check_interval
+    }
+
+    /// Re-validates the project quota for the open connection
+    pub async fn is_quota_valid(&self) -> bool {
+        let valid = self
+            .state
+            .validate_project_access_and_quota(&self.query_params.project_id)
+            .await
+            .is_ok();
+        if !valid {
+            self.state
+                .metrics
+                .add_ws_limited_message(self.query_params.chain_id.clone(), "quota");
+        }
+        valid
+    }
+
+    /// Inspects the client frame and returns the error response frame if the
+    /// frame must not be relayed
+    pub fn inspect(&mut self, frame: &str, active_subscriptions: usize) -> Result<(), String> {
+        let Ok(message) = serde_json::from_str::<Value>(frame) else {
+            // Malformed frames are answered by the relay
+            return Ok(());
+        };
+        let requests = match &message {
+            Value::Array(requests) => requests.iter().collect::<Vec<_>>(),
+            request => vec![request],
+        };
+        // Batches are rejected with a single error response
+        let id = match &message {
+            Value::Object(request) => request.get("id").cloned().unwrap_or(Value::Null),
+            _ => Value::Null,
+        };
+
+        if !self.bucket.try_acquire(Instant::now()) {
+            self.state
+                .metrics
+                .add_ws_limited_message(self.query_params.chain_id.clone(), "rate_limit");
+            return Err(error_response(
+                &id,
+                JSON_RPC_LIMIT_EXCEEDED,
+                "Messages rate limit exceeded",
+            ));
+        }
+
+        let requests = requests
+            .into_iter()
+            .filter_map(|request| {
+                request
+                    .get("method")
+                    .and_then(Value::as_str)
+                    .map(|method| (method, request))
+            })
+            .collect::<Vec<_>>();
+        // Subscriptions in batches are not relayed, so they can't bypass the
+        // active subscriptions limit
+        if message.is_array()
+            && requests.iter().any(|(method, _)| {
+                self.dialect.is_subscribe(method) || self.dialect.is_unsubscribe(method)
+            })
+        {
+            self.state
+                .metrics
+                .add_ws_limited_message(self.query_params.chain_id.clone(), "batch_subscriptions");
+            return Err(error_response(
+                &id,
+                JSON_RPC_INVALID_REQUEST,
+                "Subscriptions are not supported in batch requests",
+            ));
+        }
+        let subscribe_requests = requests
+            .iter()
+            .filter(|(method, _)| self.dialect.is_subscribe(method))
+            .count();
+
+        if subscribe_requests > 0
+            && active_subscriptions + subscribe_requests > self.max_subscriptions
+        {
+            self.state
+                .metrics
+                .add_ws_limited_message(self.query_params.chain_id.clone(), "subscriptions");
+            return Err(error_response(
+                &id,
+                JSON_RPC_LIMIT_EXCEEDED,
+                &format!(
+                    "Maximum number of {} subscriptions reached",
+                    self.max_subscriptions
+                ),
+            ));
+        }
+
+        // Only the relayed requests are recorded
+        for (method, request) in requests {
+            self.record_request(method, request);
+        }
+        Ok(())
+    }
+
+    fn record_request(&self, method: &str, request: &Value) {
+        self.state
+            .metrics
+            .add_ws_message(self.query_params.chain_id.clone(), method_label(method));
+
+        let Some(provider_kind) = &self.provider_kind else {
+            return;
+        };
+        if let Ok(rpc_request) = serde_json::from_value::<JsonRpcRequest>(request.clone()) {
+            self.state.analytics.message(MessageInfo::new(
+                &self.query_params,
+                rpc_request.method,
+                self.region.clone(),
+                self.country.clone(),
+                self.continent.clone(),
+                provider_kind,
+                self.origin.clone(),
+            ));
+        }
+    }
+}
+
+/// Metrics label of the client request method
+fn method_label(method: &str) -> &'static str {
+    METHOD_LABELS
+        .iter()
+        .find(|label| **label == method)
+        .copied()
+        .unwrap_or(OTHER_METHOD_LABEL)
+}
+
+fn error_response(id: &Value, code: i32, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_limits_and_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 1);
+        assert!(bucket.try_acquire(start));
+        assert!(bucket.try_acquire(start));
+        assert!(!bucket.try_acquire(start));
+
+        // One token is refilled per second
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)));
+        assert!(!bucket.try_acquire(start + Duration::from_secs(1)));
+
+        // Refill is capped by the bucket size
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_acquire(later));
+        assert!(bucket.try_acquire(later));
+        assert!(!bucket.try_acquire(later));
+    }
+
+    #[test]
+    fn labels_known_methods_only() {
+        assert_eq!(method_label("eth_subscribe"), "eth_subscribe");
+        assert_eq!(method_label("signatureSubscribe"), "signatureSubscribe");
+        assert_eq!(method_label("eth_random_12345"), OTHER_METHOD_LABEL);
+        assert_eq!(method_label(""), OTHER_METHOD_LABEL);
+    }
+}
@@ -0,0 +1,83 @@
+use {
+    self::{inspector::FrameInspector, pool::UpstreamSession},
+    async_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame},
+    axum_tungstenite::{Message, WebSocket},
+    futures_util::{SinkExt, StreamExt},
+    std::time::Duration,
+    tokio::time::{interval_at, Instant, Interval, MissedTickBehavior},
+    tracing::log::debug,
+};
+
+pub mod dialect;
+pub mod emulated;
+pub mod inspector;
+pub mod pool;
+
+#[tracing::instrument(skip(client_ws, upstream, inspector), level = "debug")]
+pub async fn proxy(
+    project_id: String,
+    client_ws: WebSocket,
+    mut upstream: UpstreamSession,
+    mut inspector: FrameInspector,
+) {
+    let (mut client_ws_sender, mut client_ws_receiver) = client_ws.split();
+    let mut quota_check = quota_check_timer(inspector.quota_check_interval());
+
+    loop {
+        tokio::select! {
+            message = client_ws_receiver.next() => {
+                let frame = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Binary(data))) => String::from_utf8_lossy(&data).into_owned(),
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        debug!("WebSocket receiving from the client {project_id} failed: {e}");
+                        break;
+                    }
+                };
+                match inspector.inspect(&frame, upstream.subscriptions_count()) {
+                    Ok(()) => upstream.send(&frame),
+                    Err(response) => {
+                        if let Err(e) = client_ws_sender.send(Message::Text(response)).await {
+                            debug!("WebSocket sending to the client {project_id} failed: {e}");
+                            break;
+                        }
+                    }
+                }
+            },
+            message = upstream.recv() => {
+                let Some(message) = message else {
+                    debug!("WebSocket upstream for the client {project_id} closed.");
+                    break;
+                };
+                if let Err(e) = client_ws_sender.send(Message::Text(message)).await {
+                    debug!("WebSocket sending to the client {project_id} failed: {e}");
+                    break;
+                }
+            },
+            _ = quota_check.tick() => {
+                if inspector.is_quota_valid().await {
+                    continue;
+                }
+                debug!("Closing WebSocket for the client {project_id} due to the quota limit");
+                let _ = client_ws_sender.send(quota_limit_close_message()).await;
+                break;
+            },
+        }
+    }
+}
+
+/// Timer for the periodic project quota re-validation of open connections
+fn quota_check_timer(period: Duration) -> Interval {
+    let mut timer = interval_at(Instant::now() + period, period);
+    timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    timer
+}
+
+fn quota_limit_close_message() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: CloseCode::Policy,
+        reason: "Quota limit reached".into(),
+    }))
+}
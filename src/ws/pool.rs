@@ -0,0 +1,1082 @@
+//! Pool of upstream WebSocket connections shared by the client sessions.
+//!
+//! A few upstream connections are kept per chain and the client sessions are
+//! multiplexed over them. Request ids are rewritten to be unique per upstream
+//! connection and identical subscriptions are de-duplicated, so one upstream
+//! subscription is fanned out to all of its subscribers. Subscriptions are
+//! tracked according to the chain namespace [`SubscriptionDialect`].
+//!
+//! Requests that are not answered by the upstream within
+//! [`PENDING_REQUEST_TIMEOUT`] are failed with the JSON-RPC error response.
+//! The session queues are bounded and the sessions that can't keep up with
+//! their frames are disconnected, so a slow client can't hold the memory of
+//! the shared upstream connection.
+
+use {
+    super::dialect::SubscriptionDialect,
+    crate::{error::RpcResult, providers::ProviderKind, Metrics},
+    async_tungstenite::tungstenite::Message,
+    futures_util::{SinkExt, StreamExt},
+    serde_json::{json, Value},
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex, MutexGuard, PoisonError,
+        },
+        time::{Duration, Instant},
+    },
+    tokio::{
+        sync::mpsc,
+        time::{interval, MissedTickBehavior},
+    },
+    tracing::log::{debug, warn},
+};
+
+/// Maximum number of upstream connections per chain
+const MAX_CONNECTIONS_PER_CHAIN: usize = 4;
+/// Number of client sessions on the least loaded upstream connection after
+/// which a new upstream connection is opened, if the chain pool is not full
+const SESSIONS_PER_CONNECTION: usize = 100;
+
+/// Time to wait for the upstream response before failing the request
+const PENDING_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval of checking the pending requests for the timeout
+const PENDING_REQUEST_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum number of the frames queued for a client session
+const SESSION_QUEUE_CAPACITY: usize = 1024;
+/// Maximum number of the frames queued for the upstream connection
+const UPSTREAM_QUEUE_CAPACITY: usize = 4096;
+
+/// JSON-RPC parse error code for the malformed client frames
+const JSON_RPC_PARSE_ERROR: i32 = -32700;
+/// JSON-RPC invalid request error code for the subscriptions in batches
+const JSON_RPC_INVALID_REQUEST: i32 = -32600;
+/// JSON-RPC internal error code for the timed out upstream requests
+const JSON_RPC_INTERNAL_ERROR: i32 = -32603;
+
+#[derive(Default)]
+struct ChainConnections {
+    connections: Vec<Arc<UpstreamConnection>>,
+    /// Number of the upstream connections being established
+    connecting: usize,
+}
+
+/// Pool of the upstream WebSocket connections of a single provider
+pub struct UpstreamPool {
+    provider_kind: ProviderKind,
+    connections: Mutex<HashMap<String, ChainConnections>>,
+}
+
+impl fmt::Debug for UpstreamPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpstreamPool")
+            .field("provider_kind", &self.provider_kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl UpstreamPool {
+    pub fn new(provider_kind: ProviderKind) -> Self {
+        Self {
+            provider_kind,
+            connections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn connections(&self) -> MutexGuard<'_, HashMap<String, ChainConnections>> {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Opens a new client session on the least loaded upstream connection of
+    /// the chain, connecting to the `uri` when the pool needs to grow
+    pub async fn session(
+        &self,
+        chain_id: &str,
+        uri: &str,
+        metrics: Arc<Metrics>,
+    ) -> RpcResult<UpstreamSession> {
+        let least_loaded = {
+            let mut pool = self.connections();
+            let chain = pool.entry(chain_id.to_owned()).or_default();
+            chain
+                .connections
+                .retain(|connection| !connection.is_closed());
+
+            let least_loaded = chain
+                .connections
+                .iter()
+                .min_by_key(|connection| connection.sessions_count())
+                .cloned();
+            match least_loaded {
+                Some(connection)
+                    if connection.sessions_count() < SESSIONS_PER_CONNECTION
+                        || chain.connections.len() + chain.connecting
+                            >= MAX_CONNECTIONS_PER_CHAIN =>
+                {
+                    Some(connection)
+                }
+                _ => {
+                    chain.connecting += 1;
+                    None
+                }
+            }
+        };
+
+        let connection = match least_loaded {
+            Some(connection) => connection,
+            None => {
+                // The pool is not locked during the upstream handshake, so a
+                // slow upstream does not block the sessions on the
+                // established connections
+                let connecting = ConnectingGuard {
+                    pool: self,
+                    chain_id,
+                };
+                let connection =
+                    UpstreamConnection::connect(uri, chain_id.to_owned(), metrics.clone()).await?;
+                drop(connecting);
+                self.connections()
+                    .entry(chain_id.to_owned())
+                    .or_default()
+                    .connections
+                    .push(connection.clone());
+                connection
+            }
+        };
+
+        let pool_size = self
+            .connections()
+            .get(chain_id)
+            .map(|chain| chain.connections.len())
+            .unwrap_or_default();
+        metrics.record_ws_upstream_pool_size(&self.provider_kind, chain_id.to_owned(), pool_size);
+        Ok(connection.new_session())
+    }
+}
+
+/// Releases the connecting slot of the chain when the upstream handshake is
+/// finished, failed or cancelled
+struct ConnectingGuard<'a> {
+    pool: &'a UpstreamPool,
+    chain_id: &'a str,
+}
+
+impl Drop for ConnectingGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(chain) = self.pool.connections().get_mut(self.chain_id) {
+            chain.connecting = chain.connecting.saturating_sub(1);
+        }
+    }
+}
+
+/// Client session multiplexed over an upstream connection
+pub struct UpstreamSession {
+    id: u64,
+    connection: Arc<UpstreamConnection>,
+    receiver: mpsc::Receiver<String>,
+}
+
+impl UpstreamSession {
+    /// Sends the client frame to the upstream
+    pub fn send(&self, frame: &str) {
+        self.connection.handle_client_frame(self.id, frame);
+    }
+
+    /// Number of the active subscriptions of the session
+    pub fn subscriptions_count(&self) -> usize {
+        self.connection
+            .state()
+            .sessions
+            .get(&self.id)
+            .map(|session| session.subscriptions.values().sum())
+            .unwrap_or_default()
+    }
+
+    /// Receives the next frame for the client, `None` if the upstream
+    /// connection is closed or the session was disconnected for lagging
+    pub async fn recv(&mut self) -> Option<String> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for UpstreamSession {
+    fn drop(&mut self) {
+        self.connection.remove_session(self.id);
+    }
+}
+
+enum PendingRequest {
+    Plain { session_id: u64, client_id: Value },
+    Subscribe { key: String, method: String },
+    Unsubscribe,
+}
+
+struct PendingEntry {
+    request: PendingRequest,
+    expires_at: Instant,
+}
+
+enum SubscriptionState {
+    /// Upstream subscription request is in flight, the subscribers are
+    /// waiting for the response with their request ids
+    Pending(Vec<(u64, Value)>),
+    Active(String),
+}
+
+struct UpstreamSubscription {
+    key: String,
+    /// Upstream subscription id as returned by the node, a hex string for
+    /// Ethereum and a number for Solana
+    id: Value,
+    unsubscribe_method: String,
+    /// Subscription is removed by the node after the first notification
+    single_notification: bool,
+    subscribers: HashSet<u64>,
+}
+
+struct SessionEntry {
+    /// Sender of the client frames, `None` once the session queue overflowed
+    /// and the session is being disconnected
+    sender: Option<mpsc::Sender<String>>,
+    /// Number of the client subscriptions by the upstream subscription id,
+    /// the same subscription can be requested by the client more than once
+    subscriptions: HashMap<String, usize>,
+}
+
+#[derive(Default)]
+struct ConnectionState {
+    next_request_id: u64,
+    next_session_id: u64,
+    sessions: HashMap<u64, SessionEntry>,
+    pending: HashMap<u64, PendingEntry>,
+    /// Upstream subscriptions by the serialized upstream subscription id
+    subscriptions: HashMap<String, UpstreamSubscription>,
+    /// Subscriptions by the subscription method and parameters for
+    /// de-duplication
+    subscription_keys: HashMap<String, SubscriptionState>,
+}
+
+impl ConnectionState {
+    fn next_request_id(&mut self) -> u64 {
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        self.next_request_id
+    }
+
+    fn insert_pending(&mut self, request_id: u64, request: PendingRequest) {
+        self.pending.insert(
+            request_id,
+            PendingEntry {
+                request,
+                expires_at: Instant::now() + PENDING_REQUEST_TIMEOUT,
+            },
+        );
+    }
+
+    fn take_pending(&mut self, request_id: u64) -> Option<PendingRequest> {
+        self.pending.remove(&request_id).map(|entry| entry.request)
+    }
+
+    /// Fails the requests that were not answered by the upstream in time
+    fn expire_pending_requests(&mut self, now: Instant) {
+        let expired = self
+            .pending
+            .iter()
+            .filter(|(_, entry)| entry.expires_at <= now)
+            .map(|(request_id, _)| *request_id)
+            .collect::<Vec<_>>();
+        for request_id in expired {
+            let Some(request) = self.take_pending(request_id) else {
+                continue;
+            };
+            match request {
+                PendingRequest::Plain {
+                    session_id,
+                    client_id,
+                } => self.send_to_session(
+                    session_id,
+                    error_response(
+                        &client_id,
+                        JSON_RPC_INTERNAL_ERROR,
+                        "Upstream request timed out",
+                    ),
+                ),
+                // The late subscription response is ignored and the upstream
+                // subscription is not tracked anymore
+                PendingRequest::Subscribe { key, .. } => {
+                    if let Some(SubscriptionState::Pending(waiters)) =
+                        self.subscription_keys.remove(&key)
+                    {
+                        for (session_id, client_id) in waiters {
+                            self.send_to_session(
+                                session_id,
+                                error_response(
+                                    &client_id,
+                                    JSON_RPC_INTERNAL_ERROR,
+                                    "Upstream subscription timed out",
+                                ),
+                            );
+                        }
+                    }
+                }
+                PendingRequest::Unsubscribe => {}
+            }
+        }
+    }
+
+    /// Counts the client subscription of the session, returns the number of
+    /// all the session subscriptions
+    fn add_session_subscription(
+        &mut self,
+        session_id: u64,
+        subscription_id: &str,
+    ) -> Option<usize> {
+        let session = self.sessions.get_mut(&session_id)?;
+        let subscription = self.subscriptions.get_mut(subscription_id)?;
+        *session
+            .subscriptions
+            .entry(subscription_id.to_owned())
+            .or_default() += 1;
+        subscription.subscribers.insert(session_id);
+        Some(session.subscriptions.values().sum())
+    }
+
+    /// Releases one client subscription of the session, returns `None` if the
+    /// session is not subscribed and `Some(true)` if it was the last one of
+    /// the session for the upstream subscription
+    fn release_session_subscription(
+        &mut self,
+        session_id: u64,
+        subscription_id: &str,
+    ) -> Option<bool> {
+        let session = self.sessions.get_mut(&session_id)?;
+        let count = session.subscriptions.get_mut(subscription_id)?;
+        *count -= 1;
+        if *count > 0 {
+            return Some(false);
+        }
+        session.subscriptions.remove(subscription_id);
+        Some(true)
+    }
+
+    /// Queues the frame for the session, the session that can't keep up is
+    /// disconnected by dropping its sender, so the client connection is
+    /// closed once the queued frames are delivered
+    fn send_to_session(&mut self, session_id: u64, frame: String) {
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return;
+        };
+        let Some(sender) = &session.sender else {
+            return;
+        };
+        // The session receiver is dropped only with the session itself
+        if let Err(mpsc::error::TrySendError::Full(_)) = sender.try_send(frame) {
+            debug!("Disconnecting the lagging WebSocket session {session_id}");
+            session.sender = None;
+        }
+    }
+
+    /// Takes the pending plain request for the upstream response and restores
+    /// the client request id
+    fn restore_plain_response(&mut self, response: &mut Value) -> Option<u64> {
+        let request_id = response.get("id").and_then(Value::as_u64)?;
+        let entry = self.pending.remove(&request_id)?;
+        match entry.request {
+            PendingRequest::Plain {
+                session_id,
+                client_id,
+            } => {
+                response["id"] = client_id;
+                Some(session_id)
+            }
+            request => {
+                self.pending.insert(
+                    request_id,
+                    PendingEntry {
+                        request,
+                        expires_at: entry.expires_at,
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    /// Removes the subscription from the connection and all of its
+    /// subscribers without unsubscribing upstream
+    fn remove_subscription(&mut self, subscription_id: &str) {
+        let Some(subscription) = self.subscriptions.remove(subscription_id) else {
+            return;
+        };
+        self.subscription_keys.remove(&subscription.key);
+        for session_id in subscription.subscribers {
+            if let Some(session) = self.sessions.get_mut(&session_id) {
+                session.subscriptions.remove(subscription_id);
+            }
+        }
+    }
+
+    /// Rewrites the client request id to the unique upstream request id
+    fn rewrite_plain_request(&mut self, session_id: u64, request: &mut Value) {
+        let Some(client_id) = request.get("id").cloned() else {
+            return;
+        };
+        let request_id = self.next_request_id();
+        request["id"] = json!(request_id);
+        self.insert_pending(
+            request_id,
+            PendingRequest::Plain {
+                session_id,
+                client_id,
+            },
+        );
+    }
+}
+
+struct UpstreamConnection {
+    chain_id: String,
+    dialect: SubscriptionDialect,
+    metrics: Arc<Metrics>,
+    sender: mpsc::Sender<Message>,
+    closed: AtomicBool,
+    state: Mutex<ConnectionState>,
+}
+
+impl UpstreamConnection {
+    async fn connect(uri: &str, chain_id: String, metrics: Arc<Metrics>) -> RpcResult<Arc<Self>> {
+        let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri).await?;
+        let (mut upstream_sender, mut upstream_receiver) = websocket_provider.split();
+        let (sender, mut receiver) = mpsc::channel::<Message>(UPSTREAM_QUEUE_CAPACITY);
+
+        let connection = Arc::new(Self {
+            dialect: SubscriptionDialect::from_chain_id(&chain_id),
+            chain_id,
+            metrics,
+            sender,
+            closed: AtomicBool::new(false),
+            state: Mutex::new(ConnectionState::default()),
+        });
+
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                if let Err(e) = upstream_sender.send(message).await {
+                    debug!("Failed to send to the upstream WebSocket: {e}");
+                    break;
+                }
+            }
+        });
+
+        let reader = connection.clone();
+        tokio::spawn(async move {
+            while let Some(message) = upstream_receiver.next().await {
+                match message {
+                    Ok(Message::Text(text)) => reader.handle_upstream_frame(&text),
+                    Ok(Message::Binary(data)) => {
+                        reader.handle_upstream_frame(&String::from_utf8_lossy(&data))
+                    }
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        debug!("Failed to receive from the upstream WebSocket: {e}");
+                        break;
+                    }
+                }
+            }
+            reader.close();
+        });
+
+        let sweeper = Arc::downgrade(&connection);
+        tokio::spawn(async move {
+            let mut sweep = interval(PENDING_REQUEST_SWEEP_INTERVAL);
+            sweep.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                sweep.tick().await;
+                let Some(connection) = sweeper.upgrade() else {
+                    break;
+                };
+                if connection.is_closed() {
+                    break;
+                }
+                connection.state().expire_pending_requests(Instant::now());
+            }
+        });
+
+        Ok(connection)
+    }
+
+    fn state(&self) -> MutexGuard<'_, ConnectionState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    fn sessions_count(&self) -> usize {
+        self.state().sessions.len()
+    }
+
+    /// Marks the connection as closed and drops all of its sessions, which
+    /// disconnects their clients
+    fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        *self.state() = ConnectionState::default();
+    }
+
+    fn new_session(self: &Arc<Self>) -> UpstreamSession {
+        let (sender, receiver) = mpsc::channel(SESSION_QUEUE_CAPACITY);
+        let mut state = self.state();
+        state.next_session_id += 1;
+        let id = state.next_session_id;
+        state.sessions.insert(
+            id,
+            SessionEntry {
+                sender: Some(sender),
+                subscriptions: HashMap::new(),
+            },
+        );
+
+        UpstreamSession {
+            id,
+            connection: self.clone(),
+            receiver,
+        }
+    }
+
+    fn remove_session(&self, session_id: u64) {
+        let mut state = self.state();
+        let Some(session) = state.sessions.remove(&session_id) else {
+            return;
+        };
+        for subscription_id in session.subscriptions.into_keys() {
+            self.remove_subscriber(&mut state, &subscription_id, session_id);
+        }
+    }
+
+    /// Queues the frame for the upstream, the frame is dropped if the
+    /// upstream can't keep up and its pending request is failed on expiry
+    fn send_upstream(&self, frame: String) {
+        match self.sender.try_send(Message::Text(frame)) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                warn!("Upstream WebSocket queue of {} is full", self.chain_id);
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                self.closed.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes the session from the subscription subscribers and unsubscribes
+    /// upstream when it was the last one
+    fn remove_subscriber(
+        &self,
+        state: &mut ConnectionState,
+        subscription_id: &str,
+        session_id: u64,
+    ) {
+        let Some(subscription) = state.subscriptions.get_mut(subscription_id) else {
+            return;
+        };
+        subscription.subscribers.remove(&session_id);
+        if !subscription.subscribers.is_empty() {
+            return;
+        }
+        if let Some(subscription) = state.subscriptions.remove(subscription_id) {
+            state.subscription_keys.remove(&subscription.key);
+            self.unsubscribe_upstream(state, &subscription.unsubscribe_method, &subscription.id);
+        }
+    }
+
+    fn unsubscribe_upstream(
+        &self,
+        state: &mut ConnectionState,
+        unsubscribe_method: &str,
+        subscription_id: &Value,
+    ) {
+        let request_id = state.next_request_id();
+        state.insert_pending(request_id, PendingRequest::Unsubscribe);
+        self.send_upstream(
+            json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "method": unsubscribe_method,
+                "params": [subscription_id],
+            })
+            .to_string(),
+        );
+    }
+
+    fn handle_client_frame(&self, session_id: u64, frame: &str) {
+        let mut state = self.state();
+        let mut request: Value = match serde_json::from_str(frame) {
+            Ok(request) => request,
+            Err(e) => {
+                state.send_to_session(
+                    session_id,
+                    error_response(
+                        &Value::Null,
+                        JSON_RPC_PARSE_ERROR,
+                        &format!("Invalid JSON-RPC request: {e}"),
+                    ),
+                );
+                return;
+            }
+        };
+
+        match request.get("method").and_then(Value::as_str) {
+            Some(method) if self.dialect.is_subscribe(method) => {
+                let method = method.to_owned();
+                self.subscribe(&mut state, session_id, &method, request)
+            }
+            Some(method) if self.dialect.is_unsubscribe(method) => {
+                self.unsubscribe(&mut state, session_id, request)
+            }
+            _ => match &mut request {
+                // Subscriptions are tracked per request, so they can't share
+                // the batch response
+                Value::Array(requests) if has_subscription_request(&self.dialect, requests) => {
+                    state.send_to_session(
+                        session_id,
+                        error_response(
+                            &Value::Null,
+                            JSON_RPC_INVALID_REQUEST,
+                            "Subscriptions are not supported in batch requests",
+                        ),
+                    );
+                }
+                Value::Array(requests) => {
+                    for request in requests.iter_mut() {
+                        state.rewrite_plain_request(session_id, request);
+                    }
+                    self.send_upstream(request.to_string());
+                }
+                request => {
+                    state.rewrite_plain_request(session_id, request);
+                    self.send_upstream(request.to_string());
+                }
+            },
+        }
+    }
+
+    fn subscribe(
+        &self,
+        state: &mut ConnectionState,
+        session_id: u64,
+        method: &str,
+        mut request: Value,
+    ) {
+        let client_id = request.get("id").cloned().unwrap_or(Value::Null);
+        let params = request
+            .get("params")
+            .map(Value::to_string)
+            .unwrap_or_default();
+        let key = format!("{method}:{params}");
+
+        match state.subscription_keys.get_mut(&key) {
+            Some(SubscriptionState::Active(subscription_id)) => {
+                let subscription_id = subscription_id.clone();
+                self.add_subscriber(state, &subscription_id, session_id, client_id);
+            }
+            Some(SubscriptionState::Pending(waiters)) => waiters.push((session_id, client_id)),
+            None => {
+                let request_id = state.next_request_id();
+                state.insert_pending(
+                    request_id,
+                    PendingRequest::Subscribe {
+                        key: key.clone(),
+                        method: method.to_owned(),
+                    },
+                );
+                state.subscription_keys.insert(
+                    key,
+                    SubscriptionState::Pending(vec![(session_id, client_id)]),
+                );
+                request["id"] = json!(request_id);
+                self.send_upstream(request.to_string());
+            }
+        }
+    }
+
+    fn add_subscriber(
+        &self,
+        state: &mut ConnectionState,
+        subscription_id: &str,
+        session_id: u64,
+        client_id: Value,
+    ) {
+        let Some(subscriptions_count) = state.add_session_subscription(session_id, subscription_id)
+        else {
+            return;
+        };
+        let upstream_id = state.subscriptions[subscription_id].id.clone();
+        state.send_to_session(session_id, result_response(&client_id, upstream_id));
+        self.metrics
+            .record_ws_client_subscriptions(self.chain_id.clone(), subscriptions_count);
+    }
+
+    fn unsubscribe(&self, state: &mut ConnectionState, session_id: u64, request: Value) {
+        let client_id = request.get("id").cloned().unwrap_or(Value::Null);
+        let subscription_id = request
+            .get("params")
+            .and_then(|params| params.get(0))
+            .map(Value::to_string)
+            .unwrap_or_default();
+
+        // Only one of the same client subscriptions is released
+        let released = state.release_session_subscription(session_id, &subscription_id);
+        if released == Some(true) {
+            self.remove_subscriber(state, &subscription_id, session_id);
+        }
+        state.send_to_session(
+            session_id,
+            result_response(&client_id, Value::Bool(released.is_some())),
+        );
+    }
+
+    fn handle_upstream_frame(&self, frame: &str) {
+        let message: Value = match serde_json::from_str(frame) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Failed to parse the upstream WebSocket frame: {e}");
+                return;
+            }
+        };
+
+        let mut state = self.state();
+        match message {
+            Value::Array(mut responses) => {
+                let mut session_id = None;
+                for response in responses.iter_mut() {
+                    session_id = state.restore_plain_response(response).or(session_id);
+                }
+                if let Some(session_id) = session_id {
+                    state.send_to_session(session_id, Value::Array(responses).to_string());
+                }
+            }
+            message
+                if message
+                    .get("method")
+                    .and_then(Value::as_str)
+                    .is_some_and(|method| self.dialect.is_notification(method)) =>
+            {
+                let Some(subscription_id) = message
+                    .get("params")
+                    .and_then(|params| params.get("subscription"))
+                    .map(Value::to_string)
+                else {
+                    return;
+                };
+                let Some(subscription) = state.subscriptions.get(&subscription_id) else {
+                    return;
+                };
+                let is_removed = subscription.single_notification
+                    && self.dialect.is_final_notification(&message);
+                let subscribers = subscription.subscribers.iter().copied().collect::<Vec<_>>();
+                for session_id in subscribers {
+                    state.send_to_session(session_id, frame.to_owned());
+                }
+                if is_removed {
+                    // The node already removed the subscription
+                    state.remove_subscription(&subscription_id);
+                }
+            }
+            message => self.handle_upstream_response(&mut state, message),
+        }
+    }
+
+    fn handle_upstream_response(&self, state: &mut ConnectionState, mut response: Value) {
+        let Some(request_id) = response.get("id").and_then(Value::as_u64) else {
+            return;
+        };
+        match state.take_pending(request_id) {
+            Some(PendingRequest::Plain {
+                session_id,
+                client_id,
+            }) => {
+                response["id"] = client_id;
+                state.send_to_session(session_id, response.to_string());
+            }
+            Some(PendingRequest::Subscribe { key, method }) => {
+                let waiters = match state.subscription_keys.remove(&key) {
+                    Some(SubscriptionState::Pending(waiters)) => waiters,
+                    _ => vec![],
+                };
+                let waiters = waiters
+                    .into_iter()
+                    .filter(|(session_id, _)| state.sessions.contains_key(session_id))
+                    .collect::<Vec<_>>();
+
+                let unsubscribe_method = self.dialect.unsubscribe_method(&method);
+                let upstream_id = response
+                    .get("result")
+                    .filter(|result| result.is_string() || result.is_number());
+                match upstream_id {
+                    Some(upstream_id) if waiters.is_empty() => {
+                        // All subscribers left before the subscription was
+                        // confirmed
+                        self.unsubscribe_upstream(state, &unsubscribe_method, upstream_id);
+                    }
+                    Some(upstream_id) => {
+                        let subscription_id = upstream_id.to_string();
+                        state.subscription_keys.insert(
+                            key.clone(),
+                            SubscriptionState::Active(subscription_id.clone()),
+                        );
+                        state.subscriptions.insert(
+                            subscription_id.clone(),
+                            UpstreamSubscription {
+                                key,
+                                id: upstream_id.clone(),
+                                unsubscribe_method,
+                                single_notification: self.dialect.is_single_notification(&method),
+                                subscribers: HashSet::new(),
+                            },
+                        );
+                        for (session_id, client_id) in waiters {
+                            self.add_subscriber(state, &subscription_id, session_id, client_id);
+                        }
+                    }
+                    None => {
+                        for (session_id, client_id) in waiters {
+                            response["id"] = client_id;
+                            state.send_to_session(session_id, response.to_string());
+                        }
+                    }
+                }
+            }
+            Some(PendingRequest::Unsubscribe) | None => {}
+        }
+    }
+}
+
+/// Whether the batch contains the subscribe or unsubscribe requests
+fn has_subscription_request(dialect: &SubscriptionDialect, requests: &[Value]) -> bool {
+    requests.iter().any(|request| {
+        request
+            .get("method")
+            .and_then(Value::as_str)
+            .is_some_and(|method| dialect.is_subscribe(method) || dialect.is_unsubscribe(method))
+    })
+}
+
+fn result_response(id: &Value, result: Value) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    })
+    .to_string()
+}
+
+fn error_response(id: &Value, code: i32, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn rewrites_and_restores_request_ids() {
+        let mut state = ConnectionState::default();
+
+        let mut first = json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId"});
+        let mut second = json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId"});
+        state.rewrite_plain_request(1, &mut first);
+        state.rewrite_plain_request(2, &mut second);
+        assert_ne!(first["id"], second["id"]);
+
+        let mut response = json!({"jsonrpc": "2.0", "id": second["id"], "result": "0x1"});
+        assert_eq!(state.restore_plain_response(&mut response), Some(2));
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(state.pending.len(), 1);
+
+        // Unknown and already restored responses are not routed
+        let mut response = json!({"jsonrpc": "2.0", "id": second["id"], "result": "0x1"});
+        assert_eq!(state.restore_plain_response(&mut response), None);
+    }
+
+    #[test]
+    fn does_not_rewrite_notifications() {
+        let mut state = ConnectionState::default();
+        let mut notification = json!({"jsonrpc": "2.0", "method": "eth_chainId"});
+        state.rewrite_plain_request(1, &mut notification);
+        assert!(notification.get("id").is_none());
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn removes_single_notification_subscription() {
+        let mut state = ConnectionState::default();
+        let (sender, _receiver) = mpsc::channel(SESSION_QUEUE_CAPACITY);
+        let subscription_id = json!(24040).to_string();
+        state.sessions.insert(
+            1,
+            SessionEntry {
+                sender: Some(sender),
+                subscriptions: HashMap::from([(subscription_id.clone(), 1)]),
+            },
+        );
+        state.subscription_keys.insert(
+            "signatureSubscribe:[]".to_owned(),
+            SubscriptionState::Active(subscription_id.clone()),
+        );
+        state.subscriptions.insert(
+            subscription_id.clone(),
+            UpstreamSubscription {
+                key: "signatureSubscribe:[]".to_owned(),
+                id: json!(24040),
+                unsubscribe_method: "signatureUnsubscribe".to_owned(),
+                single_notification: true,
+                subscribers: HashSet::from([1]),
+            },
+        );
+
+        state.remove_subscription(&subscription_id);
+        assert!(state.subscriptions.is_empty());
+        assert!(state.subscription_keys.is_empty());
+        assert!(state.sessions[&1].subscriptions.is_empty());
+    }
+
+    #[test]
+    fn counts_duplicate_client_subscriptions() {
+        let mut state = ConnectionState::default();
+        let (sender, _receiver) = mpsc::channel(SESSION_QUEUE_CAPACITY);
+        let subscription_id = json!("0x1").to_string();
+        state.sessions.insert(
+            1,
+            SessionEntry {
+                sender: Some(sender),
+                subscriptions: HashMap::new(),
+            },
+        );
+        state.subscriptions.insert(
+            subscription_id.clone(),
+            UpstreamSubscription {
+                key: "eth_subscribe:[\"newHeads\"]".to_owned(),
+                id: json!("0x1"),
+                unsubscribe_method: "eth_unsubscribe".to_owned(),
+                single_notification: false,
+                subscribers: HashSet::new(),
+            },
+        );
+
+        assert_eq!(state.add_session_subscription(1, &subscription_id), Some(1));
+        assert_eq!(state.add_session_subscription(1, &subscription_id), Some(2));
+
+        // The first unsubscribe keeps the other client subscription
+        assert_eq!(
+            state.release_session_subscription(1, &subscription_id),
+            Some(false)
+        );
+        assert_eq!(
+            state.release_session_subscription(1, &subscription_id),
+            Some(true)
+        );
+        assert_eq!(
+            state.release_session_subscription(1, &subscription_id),
+            None
+        );
+    }
+
+    #[test]
+    fn expires_pending_requests() {
+        let mut state = ConnectionState::default();
+        let (sender, mut receiver) = mpsc::channel(SESSION_QUEUE_CAPACITY);
+        state.sessions.insert(
+            1,
+            SessionEntry {
+                sender: Some(sender),
+                subscriptions: HashMap::new(),
+            },
+        );
+
+        let mut request = json!({"jsonrpc": "2.0", "id": 7, "method": "eth_chainId"});
+        state.rewrite_plain_request(1, &mut request);
+        let request_id = state.next_request_id();
+        state.insert_pending(
+            request_id,
+            PendingRequest::Subscribe {
+                key: "eth_subscribe:[\"newHeads\"]".to_owned(),
+                method: "eth_subscribe".to_owned(),
+            },
+        );
+        state.subscription_keys.insert(
+            "eth_subscribe:[\"newHeads\"]".to_owned(),
+            SubscriptionState::Pending(vec![(1, json!(8))]),
+        );
+
+        state.expire_pending_requests(Instant::now());
+        assert_eq!(state.pending.len(), 2);
+
+        state.expire_pending_requests(Instant::now() + PENDING_REQUEST_TIMEOUT);
+        assert!(state.pending.is_empty());
+        assert!(state.subscription_keys.is_empty());
+
+        let mut ids = vec![];
+        while let Ok(frame) = receiver.try_recv() {
+            let response: Value = serde_json::from_str(&frame).unwrap();
+            assert_eq!(response["error"]["code"], json!(JSON_RPC_INTERNAL_ERROR));
+            ids.push(response["id"].clone());
+        }
+        ids.sort_by_key(|id| id.as_u64());
+        assert_eq!(ids, vec![json!(7), json!(8)]);
+    }
+
+    #[test]
+    fn disconnects_lagging_sessions() {
+        let mut state = ConnectionState::default();
+        let (sender, mut receiver) = mpsc::channel(1);
+        state.sessions.insert(
+            1,
+            SessionEntry {
+                sender: Some(sender),
+                subscriptions: HashMap::new(),
+            },
+        );
+
+        state.send_to_session(1, "first".to_owned());
+        state.send_to_session(1, "second".to_owned());
+        assert!(state.sessions[&1].sender.is_none());
+
+        // The queued frame is delivered before the session is closed
+        assert_eq!(receiver.try_recv().unwrap(), "first");
+        assert_eq!(
+            receiver.try_recv(),
+            Err(mpsc::error::TryRecvError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn finds_subscriptions_in_batches() {
+        let dialect = SubscriptionDialect::Ethereum;
+        assert!(!has_subscription_request(
+            &dialect,
+            &[json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId"})]
+        ));
+        assert!(has_subscription_request(
+            &dialect,
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId"}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "eth_subscribe", "params": []}),
+            ]
+        ));
+        assert!(has_subscription_request(
+            &SubscriptionDialect::Solana,
+            &[json!({"jsonrpc": "2.0", "id": 1, "method": "slotUnsubscribe", "params": [0]})]
+        ));
+    }
+}
@@ -82,6 +82,7 @@ resource "aws_ecs_task_definition" "app_task" {
         { name = "RPC_PROXY_GEOIP_DB_BUCKET", value = var.geoip_db_bucket_name },
         { name = "RPC_PROXY_GEOIP_DB_KEY", value = var.geoip_db_key },
         { name = "RPC_PROXY_TESTING_PROJECT_ID", value = var.testing_project_id },
+        { name = "RPC_PROXY_ADMIN_API_TOKEN", value = var.admin_api_token },
 
         { name = "RPC_PROXY_BLOCKED_COUNTRIES", value = var.ofac_blocked_countries },
 
@@ -94,6 +95,7 @@ resource "aws_ecs_task_definition" "app_task" {
         { name = "RPC_PROXY_PROVIDER_ONE_INCH_API_KEY", value = var.one_inch_api_key },
         { name = "RPC_PROXY_PROVIDER_ONE_INCH_REFERRER", value = var.one_inch_referrer },
         { name = "RPC_PROXY_PROVIDER_GETBLOCK_ACCESS_TOKENS", value = var.getblock_access_tokens },
+        { name = "RPC_PROXY_PROVIDER_GETBLOCK_WS_ACCESS_TOKENS", value = var.getblock_ws_access_tokens },
         { name = "RPC_PROXY_PROVIDER_PIMLICO_API_KEY", value = var.pimlico_api_key },
         { name = "RPC_PROXY_PROVIDER_SOLSCAN_API_V1_TOKEN", value = var.solscan_api_v1_token },
         { name = "RPC_PROXY_PROVIDER_SOLSCAN_API_V2_TOKEN", value = var.solscan_api_v2_token },
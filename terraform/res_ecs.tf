@@ -63,18 +63,19 @@ module "ecs" {
   postgres_url                       = module.postgres.database_url
 
   # Providers
-  infura_project_id      = var.infura_project_id
-  pokt_project_id        = var.pokt_project_id
-  quicknode_api_tokens   = var.quicknode_api_tokens
-  zerion_api_key         = var.zerion_api_key
-  coinbase_api_key       = var.coinbase_api_key
-  coinbase_app_id        = var.coinbase_app_id
-  one_inch_api_key       = var.one_inch_api_key
-  one_inch_referrer      = var.one_inch_referrer
-  getblock_access_tokens = var.getblock_access_tokens
-  pimlico_api_key        = var.pimlico_api_key
-  solscan_api_v1_token   = var.solscan_api_v1_token
-  solscan_api_v2_token   = var.solscan_api_v2_token
+  infura_project_id         = var.infura_project_id
+  pokt_project_id           = var.pokt_project_id
+  quicknode_api_tokens      = var.quicknode_api_tokens
+  zerion_api_key            = var.zerion_api_key
+  coinbase_api_key          = var.coinbase_api_key
+  coinbase_app_id           = var.coinbase_app_id
+  one_inch_api_key          = var.one_inch_api_key
+  one_inch_referrer         = var.one_inch_referrer
+  getblock_access_tokens    = var.getblock_access_tokens
+  getblock_ws_access_tokens = var.getblock_ws_access_tokens
+  pimlico_api_key           = var.pimlico_api_key
+  solscan_api_v1_token      = var.solscan_api_v1_token
+  solscan_api_v2_token      = var.solscan_api_v2_token
 
   # Project Registry
   registry_api_endpoint   = var.registry_api_endpoint
@@ -111,5 +112,8 @@ module "ecs" {
   # Project ID used in a testing suite
   testing_project_id = var.testing_project_id
 
+  # Bearer token for the admin API on the private server
+  admin_api_token = var.admin_api_token
+
   depends_on = [aws_iam_role.application_role]
 }
@@ -133,6 +133,13 @@ variable "getblock_access_tokens" {
   sensitive   = true
 }
 
+variable "getblock_ws_access_tokens" {
+  description = "Mapping of WebSocket API access tokens for GetBlock in JSON format"
+  type        = string
+  sensitive   = true
+  default     = "{}"
+}
+
 variable "pimlico_api_key" {
   description = "Pimlico bundler API token key"
   type        = string
@@ -157,6 +164,13 @@ variable "testing_project_id" {
   sensitive   = true
 }
 
+variable "admin_api_token" {
+  description = "Bearer token for the admin API on the private server"
+  type        = string
+  sensitive   = true
+  default     = ""
+}
+
 #-------------------------------------------------------------------------------
 # Analytics
 